@@ -0,0 +1,298 @@
+use crate::error::ApiError;
+use crate::state::{lock, AppState};
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderMap},
+    middleware::Next,
+    response::Response,
+};
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// 管理者トークンを受け取るヘッダー名 (`Authorization: Bearer` の代わりに使える)
+pub const ADMIN_TOKEN_HEADER: &str = "x-admin-token";
+
+/// 起動時に生成する司会者用PINの桁数
+const HOST_PIN_DIGITS: u32 = 6;
+
+/// 続けて間違えても受け付けを止めない回数
+const ALLOWED_FAILURES: u32 = 5;
+
+/// 最初に受け付けを止める時間 (その後は間違えるたびに倍になる)
+const FIRST_LOCKOUT: Duration = Duration::from_secs(30);
+
+/// 受け付けを止める時間の上限 (最後に間違えてから、または受け付けを再開してからこの時間が経てば失敗を忘れる)
+const MAX_LOCKOUT: Duration = Duration::from_secs(15 * 60);
+
+/// 管理者 (司会者) の認証情報
+///
+/// 抽選・リセット・あみだくじの更新など状態を変更する操作には、
+/// 起動時に設定した管理者トークン、または起動時に生成した司会者用PINが必要です。
+/// 参照のみの操作は認証なしで利用できます（会場のモニターや参加者のスマートフォン向け）。
+///
+/// 6桁のPINを総当たりされないように、間違えた回数をクライアント (IPアドレス) ごとに数え、
+/// 続けて間違えたクライアントからはしばらく受け付けません。
+#[derive(Clone)]
+pub struct AdminAuth {
+    secret: String,
+    generated: bool,
+    failures: Arc<Mutex<HashMap<Option<IpAddr>, Failures>>>,
+}
+
+/// クライアントが続けて間違えた記録
+struct Failures {
+    count: u32,
+    last: Instant,
+    locked_until: Option<Instant>,
+}
+
+impl AdminAuth {
+    /// 認証情報を作成する
+    ///
+    /// # Arguments
+    /// * `token` - 設定された管理者トークン (Noneの場合は司会者用PINを生成する)
+    pub fn new(token: Option<String>) -> Self {
+        match token {
+            Some(secret) => Self {
+                secret,
+                generated: false,
+                failures: Arc::default(),
+            },
+            None => Self {
+                secret: generate_pin(),
+                generated: true,
+                failures: Arc::default(),
+            },
+        }
+    }
+
+    /// 起動時に生成した司会者用PIN (トークンが設定されている場合はNone)
+    pub fn host_pin(&self) -> Option<&str> {
+        self.generated.then_some(self.secret.as_str())
+    }
+
+    /// リクエストヘッダーの認証情報を検証する
+    ///
+    /// 認証情報がない場合は `Unauthorized` (401)、正しくない場合は `Forbidden` (403) を返す。
+    /// 続けて間違えたクライアントからの認証は、受け付けを再開するまで `TooManyAttempts` (429) になる。
+    ///
+    /// # Arguments
+    /// * `client` - クライアントのIPアドレス (不明な場合はNone、不明なクライアント同士は同じものとして数える)
+    /// * `headers` - リクエストヘッダー
+    pub fn authorize(&self, client: Option<IpAddr>, headers: &HeaderMap) -> Result<(), ApiError> {
+        self.authorize_at(client, headers, Instant::now())
+    }
+
+    fn authorize_at(
+        &self,
+        client: Option<IpAddr>,
+        headers: &HeaderMap,
+        now: Instant,
+    ) -> Result<(), ApiError> {
+        let mut failures = lock(&self.failures, "admin failures");
+        if let Some(until) = failures.get(&client).and_then(|f| f.locked_until) {
+            if until > now {
+                let wait = until - now;
+                let seconds = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+                return Err(ApiError::TooManyAttempts(seconds));
+            }
+        }
+
+        let presented = presented_token(headers).ok_or(ApiError::Unauthorized)?;
+        if constant_time_eq(presented.as_bytes(), self.secret.as_bytes()) {
+            failures.remove(&client);
+            return Ok(());
+        }
+
+        // 間違えたまま時間が経ったクライアントは忘れる
+        failures.retain(|_, f| {
+            let since = f.locked_until.map_or(f.last, |until| until.max(f.last));
+            now.saturating_duration_since(since) < MAX_LOCKOUT
+        });
+        let entry = failures.entry(client).or_insert(Failures {
+            count: 0,
+            last: now,
+            locked_until: None,
+        });
+        entry.count += 1;
+        entry.last = now;
+        if entry.count > ALLOWED_FAILURES {
+            let doublings = (entry.count - ALLOWED_FAILURES - 1).min(16);
+            let lockout = (FIRST_LOCKOUT * 2u32.pow(doublings)).min(MAX_LOCKOUT);
+            entry.locked_until = Some(now + lockout);
+        }
+        Err(ApiError::Forbidden)
+    }
+}
+
+/// 管理者のみ実行できるルートに適用するミドルウェア
+pub async fn require_admin(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let client = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    state.admin.authorize(client, request.headers())?;
+    Ok(next.run(request).await)
+}
+
+/// `Authorization: Bearer <token>` または `X-Admin-Token: <token>` からトークンを取り出す
+fn presented_token(headers: &HeaderMap) -> Option<&str> {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    let token = bearer.or_else(|| {
+        headers
+            .get(ADMIN_TOKEN_HEADER)
+            .and_then(|v| v.to_str().ok())
+    })?;
+    let token = token.trim();
+    (!token.is_empty()).then_some(token)
+}
+
+/// 比較にかかる時間から一致した長さを推測されないように、全てのバイトを比較する
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// 司会者用PINを生成する
+///
+/// 抽選用の乱数生成器はシードから再現できるため使わず、
+/// プロセスごとにランダムな鍵を持つ `RandomState` と現在時刻から生成する。
+fn generate_pin() -> String {
+    let mut hasher = RandomState::new().build_hasher();
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    hasher.write_u128(nanos);
+    let pin = hasher.finish() % 10u64.pow(HOST_PIN_DIGITS);
+    format!("{:0width$}", pin, width = HOST_PIN_DIGITS as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn headers(name: &'static str, value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(name, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_authorize_with_configured_token() {
+        let auth = AdminAuth::new(Some("secret-token".to_string()));
+        assert_eq!(auth.host_pin(), None);
+
+        assert_eq!(
+            auth.authorize(None, &headers("authorization", "Bearer secret-token")),
+            Ok(())
+        );
+        assert_eq!(
+            auth.authorize(None, &headers(ADMIN_TOKEN_HEADER, "secret-token")),
+            Ok(())
+        );
+        assert_eq!(
+            auth.authorize(None, &headers("authorization", "Bearer wrong")),
+            Err(ApiError::Forbidden)
+        );
+        assert_eq!(
+            auth.authorize(None, &HeaderMap::new()),
+            Err(ApiError::Unauthorized)
+        );
+    }
+
+    #[test]
+    fn test_generated_host_pin() {
+        let auth = AdminAuth::new(None);
+        let pin = auth.host_pin().unwrap().to_string();
+        assert_eq!(pin.len(), HOST_PIN_DIGITS as usize);
+        assert!(pin.chars().all(|c| c.is_ascii_digit()));
+        assert_eq!(
+            auth.authorize(None, &headers(ADMIN_TOKEN_HEADER, &pin)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_lockout_after_repeated_failures() {
+        let auth = AdminAuth::new(Some("secret-token".to_string()));
+        let guesser = Some(IpAddr::from([192, 168, 0, 10]));
+        let host = Some(IpAddr::from([192, 168, 0, 2]));
+        let wrong = headers(ADMIN_TOKEN_HEADER, "wrong");
+        let right = headers(ADMIN_TOKEN_HEADER, "secret-token");
+        let start = Instant::now();
+
+        // 正しいトークンで失敗の回数は戻る
+        for _ in 0..ALLOWED_FAILURES {
+            assert_eq!(
+                auth.authorize_at(guesser, &wrong, start),
+                Err(ApiError::Forbidden)
+            );
+        }
+        assert_eq!(auth.authorize_at(guesser, &right, start), Ok(()));
+
+        for _ in 0..=ALLOWED_FAILURES {
+            assert_eq!(
+                auth.authorize_at(guesser, &wrong, start),
+                Err(ApiError::Forbidden)
+            );
+        }
+        // 止めている間は正しいトークンも受け付けない
+        assert_eq!(
+            auth.authorize_at(guesser, &right, start),
+            Err(ApiError::TooManyAttempts(30))
+        );
+        assert_eq!(
+            auth.authorize_at(guesser, &right, start + Duration::from_millis(29_500)),
+            Err(ApiError::TooManyAttempts(1))
+        );
+        // 他のクライアントは影響を受けない
+        assert_eq!(auth.authorize_at(host, &right, start), Ok(()));
+        assert_eq!(auth.authorize_at(None, &right, start), Ok(()));
+
+        // 再開後にまた間違えると、止める時間が倍になる
+        let later = start + FIRST_LOCKOUT;
+        assert_eq!(
+            auth.authorize_at(guesser, &wrong, later),
+            Err(ApiError::Forbidden)
+        );
+        assert_eq!(
+            auth.authorize_at(guesser, &right, later),
+            Err(ApiError::TooManyAttempts(60))
+        );
+
+        // 上限を超えて止めることはない
+        let mut now = later;
+        for _ in 0..20 {
+            now += MAX_LOCKOUT;
+            let _ = auth.authorize_at(guesser, &wrong, now);
+        }
+        assert_eq!(
+            auth.authorize_at(guesser, &right, now),
+            Err(ApiError::TooManyAttempts(MAX_LOCKOUT.as_secs()))
+        );
+
+        // 止める時間を過ぎて成功すれば記録は消える
+        now += MAX_LOCKOUT;
+        assert_eq!(auth.authorize_at(guesser, &right, now), Ok(()));
+        assert_eq!(
+            auth.authorize_at(guesser, &wrong, now),
+            Err(ApiError::Forbidden)
+        );
+        assert_eq!(auth.authorize_at(guesser, &right, now), Ok(()));
+    }
+}
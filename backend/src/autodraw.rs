@@ -0,0 +1,421 @@
+use crate::domain::BingoGame;
+use crate::lifecycle::{GamePhase, GamePhaseError};
+use crate::state::lock;
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use utoipa::ToSchema;
+
+/// 抽選間隔の最小値 (秒)
+pub const MIN_INTERVAL_SECS: u64 = 1;
+/// 抽選間隔の最大値 (秒)
+pub const MAX_INTERVAL_SECS: u64 = 3600;
+
+/// 抽選間隔 (秒) を検証する
+///
+/// # Returns
+/// * `Result<Duration, String>` - 抽選間隔。範囲外の場合はエラーメッセージ
+pub fn interval_from_secs(secs: u64) -> Result<Duration, String> {
+    if (MIN_INTERVAL_SECS..=MAX_INTERVAL_SECS).contains(&secs) {
+        Ok(Duration::from_secs(secs))
+    } else {
+        Err(format!(
+            "{} (must be {}-{} seconds)",
+            secs, MIN_INTERVAL_SECS, MAX_INTERVAL_SECS
+        ))
+    }
+}
+
+/// 自動抽選の状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoDrawState {
+    /// 一度も開始していない
+    Idle,
+    /// 一定間隔で抽選している
+    Running,
+    /// 一時停止している (再開すると間隔の計測をやり直す)
+    Paused,
+    /// 停止した (`stop_reason` に理由がある)
+    Stopped,
+}
+
+/// 自動抽選が停止した理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoDrawStopReason {
+    /// 司会者が停止した
+    Manual,
+    /// ビンゴの申告があった
+    Claimed,
+    /// 全ての数字が出尽くした
+    Exhausted,
+    /// ゲームが抽選できないフェーズ (終了・保管済み) になった
+    PhaseChanged,
+    /// ゲームがリセットされた
+    Reset,
+}
+
+/// 自動抽選の状況
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct AutoDrawStatus {
+    /// 状態
+    pub state: AutoDrawState,
+    /// 抽選間隔 (秒)
+    pub interval_secs: u64,
+    /// 次に抽選する予定の日時 (抽選中以外はNone)
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub next_draw_at: Option<DateTime<Local>>,
+    /// 今回の自動抽選で抽選した数字の個数
+    pub draws: usize,
+    /// 今回の自動抽選で最後に抽選した数字
+    pub last: Option<u8>,
+    /// 停止した理由 (停止していない場合はNone)
+    pub stop_reason: Option<AutoDrawStopReason>,
+}
+
+/// 自動抽選の操作に失敗した理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoDrawError {
+    /// すでに自動抽選中 (一時停止中を含む)
+    AlreadyActive,
+    /// 自動抽選中ではない
+    NotActive,
+    /// ゲームが抽選できないフェーズ
+    Phase(GamePhaseError),
+}
+
+impl fmt::Display for AutoDrawError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AlreadyActive => write!(f, "auto draw is already active"),
+            Self::NotActive => write!(f, "auto draw is not active"),
+            Self::Phase(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// 実行中のタスクに渡す設定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Settings {
+    interval: Duration,
+    paused: bool,
+}
+
+/// 自動抽選のタスクと状況
+struct Scheduler {
+    /// 状況
+    status: AutoDrawStatus,
+    /// 実行中のタスクへ設定を伝える送信側 (停止中はNone)
+    settings: Option<watch::Sender<Settings>>,
+    /// 実行中のタスク
+    task: Option<JoinHandle<()>>,
+}
+
+/// ビンゴの自動抽選
+///
+/// 一定間隔でサーバー自身が抽選する tokio のタスクを管理します。
+/// 実行中でも抽選間隔の変更・一時停止・再開ができ、数字が出尽くしたとき、
+/// ゲームが抽選できないフェーズになったとき、ビンゴの申告があったときに停止します。
+/// ゲームが一時停止中 (`paused`) の間は抽選を見送り、次の間隔を待ちます。
+#[derive(Clone)]
+pub struct AutoDraw {
+    scheduler: Arc<Mutex<Scheduler>>,
+}
+
+impl AutoDraw {
+    /// 停止した状態の自動抽選を作成する
+    ///
+    /// # Arguments
+    /// * `interval` - 開始時に間隔を指定しなかった場合の抽選間隔
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            scheduler: Arc::new(Mutex::new(Scheduler {
+                status: AutoDrawStatus {
+                    state: AutoDrawState::Idle,
+                    interval_secs: interval.as_secs(),
+                    next_draw_at: None,
+                    draws: 0,
+                    last: None,
+                    stop_reason: None,
+                },
+                settings: None,
+                task: None,
+            })),
+        }
+    }
+
+    /// 現在の状況
+    pub fn status(&self) -> AutoDrawStatus {
+        self.scheduler().status.clone()
+    }
+
+    /// 自動抽選を開始する
+    ///
+    /// tokio のランタイム上で呼び出す必要があります。
+    ///
+    /// # Arguments
+    /// * `game` - 抽選するゲーム
+    /// * `interval` - 抽選間隔 (Noneの場合は前回の間隔)
+    pub fn start(
+        &self,
+        game: Arc<Mutex<BingoGame>>,
+        interval: Option<Duration>,
+    ) -> Result<AutoDrawStatus, AutoDrawError> {
+        let phase = lock(&game, "bingo").phase();
+        if !phase.can_draw() {
+            return Err(AutoDrawError::Phase(GamePhaseError::CannotDraw(phase)));
+        }
+
+        let mut scheduler = self.scheduler();
+        if scheduler.settings.is_some() {
+            return Err(AutoDrawError::AlreadyActive);
+        }
+        let interval =
+            interval.unwrap_or_else(|| Duration::from_secs(scheduler.status.interval_secs));
+        let (sender, receiver) = watch::channel(Settings {
+            interval,
+            paused: false,
+        });
+        scheduler.status = AutoDrawStatus {
+            state: AutoDrawState::Running,
+            interval_secs: interval.as_secs(),
+            next_draw_at: next_draw_at(interval),
+            draws: 0,
+            last: None,
+            stop_reason: None,
+        };
+        scheduler.settings = Some(sender);
+        scheduler.task = Some(tokio::spawn(self.clone().run(game, receiver)));
+        Ok(scheduler.status.clone())
+    }
+
+    /// 実行中の自動抽選の間隔を変更する・一時停止する・再開する
+    ///
+    /// 間隔を変更した場合や再開した場合は、その時点から間隔を計測し直します。
+    ///
+    /// # Arguments
+    /// * `interval` - 新しい抽選間隔 (Noneの場合は変更しない)
+    /// * `paused` - 一時停止するかどうか (Noneの場合は変更しない)
+    pub fn update(
+        &self,
+        interval: Option<Duration>,
+        paused: Option<bool>,
+    ) -> Result<AutoDrawStatus, AutoDrawError> {
+        let mut scheduler = self.scheduler();
+        let Some(sender) = &scheduler.settings else {
+            return Err(AutoDrawError::NotActive);
+        };
+        let mut settings = *sender.borrow();
+        settings.interval = interval.unwrap_or(settings.interval);
+        settings.paused = paused.unwrap_or(settings.paused);
+        sender.send_replace(settings);
+
+        let status = &mut scheduler.status;
+        status.interval_secs = settings.interval.as_secs();
+        if settings.paused {
+            status.state = AutoDrawState::Paused;
+            status.next_draw_at = None;
+        } else {
+            status.state = AutoDrawState::Running;
+            status.next_draw_at = next_draw_at(settings.interval);
+        }
+        Ok(status.clone())
+    }
+
+    /// 自動抽選を停止する
+    ///
+    /// 自動抽選中でない場合は何もしません。
+    ///
+    /// # Arguments
+    /// * `reason` - 停止する理由
+    pub fn stop(&self, reason: AutoDrawStopReason) -> AutoDrawStatus {
+        let mut scheduler = self.scheduler();
+        if let Some(task) = scheduler.task.take() {
+            task.abort();
+        }
+        if scheduler.settings.take().is_some() {
+            scheduler.status.state = AutoDrawState::Stopped;
+            scheduler.status.next_draw_at = None;
+            scheduler.status.stop_reason = Some(reason);
+        }
+        scheduler.status.clone()
+    }
+
+    /// 自動抽選のタスク本体
+    ///
+    /// 設定が変わるたびに間隔の計測をやり直し、間隔が経過するごとに1つ抽選する。
+    async fn run(self, game: Arc<Mutex<BingoGame>>, mut settings: watch::Receiver<Settings>) {
+        loop {
+            let current = *settings.borrow_and_update();
+            if current.paused {
+                if settings.changed().await.is_err() {
+                    return;
+                }
+                continue;
+            }
+
+            self.scheduler().status.next_draw_at = next_draw_at(current.interval);
+            tokio::select! {
+                _ = tokio::time::sleep(current.interval) => {}
+                changed = settings.changed() => {
+                    if changed.is_err() {
+                        return;
+                    }
+                    continue;
+                }
+            }
+
+            let (result, exhausted) = {
+                let mut game = lock(&game, "bingo");
+                let result = game.draw();
+                (result, game.remaining_numbers.is_empty())
+            };
+            match result {
+                Ok(Some(number)) => {
+                    let mut scheduler = self.scheduler();
+                    scheduler.status.draws += 1;
+                    scheduler.status.last = Some(number);
+                }
+                // 司会者がゲームを一時停止している間は見送る
+                Err(GamePhaseError::CannotDraw(GamePhase::Paused)) => continue,
+                Ok(None) => {}
+                Err(_) => {
+                    self.finish(AutoDrawStopReason::PhaseChanged);
+                    return;
+                }
+            }
+            if exhausted {
+                self.finish(AutoDrawStopReason::Exhausted);
+                return;
+            }
+        }
+    }
+
+    /// タスク自身が終了するときに状況を更新する
+    fn finish(&self, reason: AutoDrawStopReason) {
+        let mut scheduler = self.scheduler();
+        scheduler.task = None;
+        scheduler.settings = None;
+        scheduler.status.state = AutoDrawState::Stopped;
+        scheduler.status.next_draw_at = None;
+        scheduler.status.stop_reason = Some(reason);
+    }
+
+    fn scheduler(&self) -> std::sync::MutexGuard<'_, Scheduler> {
+        lock(&self.scheduler, "auto draw")
+    }
+}
+
+/// 今から間隔が経過した日時
+fn next_draw_at(interval: Duration) -> Option<DateTime<Local>> {
+    chrono::Duration::from_std(interval)
+        .ok()
+        .map(|interval| Local::now() + interval)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lifecycle::PhaseAction;
+    use crate::rng::XorShift;
+
+    fn game(max_number: usize) -> Arc<Mutex<BingoGame>> {
+        Arc::new(Mutex::new(BingoGame::new(
+            max_number,
+            Box::new(XorShift::new(1)),
+        )))
+    }
+
+    async fn wait(secs: u64) {
+        tokio::time::sleep(Duration::from_secs(secs)).await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_draws_until_exhausted() {
+        let game = game(3);
+        let auto_draw = AutoDraw::new(Duration::from_secs(10));
+        auto_draw.start(game.clone(), None).unwrap();
+        assert_eq!(
+            auto_draw.start(game.clone(), None),
+            Err(AutoDrawError::AlreadyActive)
+        );
+
+        wait(15).await;
+        assert_eq!(game.lock().unwrap().history.len(), 1);
+        assert_eq!(auto_draw.status().draws, 1);
+        assert!(auto_draw.status().next_draw_at.is_some());
+
+        wait(20).await;
+        let status = auto_draw.status();
+        assert_eq!(status.state, AutoDrawState::Stopped);
+        assert_eq!(status.stop_reason, Some(AutoDrawStopReason::Exhausted));
+        assert_eq!(status.draws, 3);
+        assert_eq!(status.last, game.lock().unwrap().history.last().copied());
+        assert_eq!(game.lock().unwrap().phase(), GamePhase::Finished);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_pause_resume_and_interval_change() {
+        let game = game(75);
+        let auto_draw = AutoDraw::new(Duration::from_secs(10));
+        assert_eq!(
+            auto_draw.update(None, Some(true)),
+            Err(AutoDrawError::NotActive)
+        );
+        auto_draw.start(game.clone(), None).unwrap();
+
+        wait(5).await;
+        let status = auto_draw.update(None, Some(true)).unwrap();
+        assert_eq!(status.state, AutoDrawState::Paused);
+        wait(30).await;
+        assert!(game.lock().unwrap().history.is_empty());
+
+        // 再開すると間隔の計測をやり直す
+        let status = auto_draw
+            .update(Some(Duration::from_secs(3)), Some(false))
+            .unwrap();
+        assert_eq!(status.interval_secs, 3);
+        wait(7).await;
+        assert_eq!(game.lock().unwrap().history.len(), 2);
+
+        // ゲームが一時停止中の間は見送る
+        game.lock().unwrap().transition(PhaseAction::Pause).unwrap();
+        wait(9).await;
+        assert_eq!(game.lock().unwrap().history.len(), 2);
+        assert_eq!(auto_draw.status().state, AutoDrawState::Running);
+
+        let status = auto_draw.stop(AutoDrawStopReason::Claimed);
+        assert_eq!(status.state, AutoDrawState::Stopped);
+        assert_eq!(status.stop_reason, Some(AutoDrawStopReason::Claimed));
+        game.lock()
+            .unwrap()
+            .transition(PhaseAction::Resume)
+            .unwrap();
+        wait(10).await;
+        assert_eq!(game.lock().unwrap().history.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_refuses_to_start_when_game_cannot_draw() {
+        let game = game(75);
+        game.lock().unwrap().draw().unwrap();
+        game.lock()
+            .unwrap()
+            .transition(PhaseAction::Finish)
+            .unwrap();
+        let auto_draw = AutoDraw::new(Duration::from_secs(10));
+        assert_eq!(
+            auto_draw.start(game, None),
+            Err(AutoDrawError::Phase(GamePhaseError::CannotDraw(
+                GamePhase::Finished
+            )))
+        );
+        assert_eq!(auto_draw.status().state, AutoDrawState::Idle);
+        assert!(interval_from_secs(0).is_err());
+    }
+}
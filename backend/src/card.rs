@@ -0,0 +1,561 @@
+use crate::auth::constant_time_eq;
+use crate::domain::BingoVariant;
+use crate::lifecycle::GamePhase;
+use crate::player::PlayerId;
+use crate::rng::{derive_seed, IRng, XorShift};
+use crate::session::WinPattern;
+use chrono::{DateTime, Local};
+use hmac_sha256::HMAC;
+use serde::Serialize;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::BuildHasher;
+use utoipa::ToSchema;
+
+/// カードID (発行順に1から振られる)
+pub type CardId = u32;
+
+/// 印刷するカードの最初のカードID
+///
+/// スマートフォン用に発行するカードはこれより小さいID、印刷するカードはこれ以上のIDを使い、
+/// 同じIDのカードが2枚できないようにします。
+pub const PRINTED_CARD_FIRST_ID: CardId = 100_000;
+
+/// カードの1辺のマスの数 (75ボールの5x5のカード)
+pub const CARD_SIZE: usize = 5;
+
+/// 参加コードの文字数
+pub const JOIN_CODE_LEN: usize = 6;
+
+/// 参加コードに使う文字 (読み間違えやすい `0` `O` `1` `I` を除いた32文字)
+const JOIN_CODE_ALPHABET: &[u8; 32] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+/// 印刷したカードのQRコードの内容の接頭辞
+pub const QR_PREFIX: &str = "XMASBINGO";
+
+/// QRコードに載せる署名のバイト数 (16進数で12文字)
+const QR_TAG_BYTES: usize = 6;
+
+/// 鍵が設定されていない場合に生成する鍵のバイト数 (256ビット)
+const RANDOM_KEY_BYTES: usize = 32;
+
+/// カードの数字を求める乱数のストリーム番号 (ラウンドのシード値と重ならないようにする)
+const CARD_STREAM: u32 = 0xca4d;
+
+/// カードの各マスの状態 (`[行][列]`)
+pub type CardGrid<T> = [[T; CARD_SIZE]; CARD_SIZE];
+
+/// ビンゴカード
+///
+/// 数字はセッションのシード値とカードIDから求めるため、同じシード値なら同じカードを再発行できます。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct BingoCard {
+    /// カードID
+    pub id: CardId,
+    /// カードを持つ参加者のID
+    pub player: PlayerId,
+    /// スマートフォンでカードを開くための参加コード
+    pub join_code: String,
+    /// マスの数字 (`[行][列]`、中央のフリーマスはnull)
+    #[schema(value_type = Vec<Vec<Option<u8>>>)]
+    pub numbers: CardGrid<Option<u8>>,
+    /// 発行した日時
+    #[schema(value_type = String, format = DateTime)]
+    pub issued_at: DateTime<Local>,
+}
+
+impl BingoCard {
+    /// カードの数字を求める
+    ///
+    /// B-I-N-G-Oの各列に、その列の15個の数字から5個を選びます。中央はフリーマスです。
+    ///
+    /// # Arguments
+    /// * `seed` - セッションのシード値
+    /// * `id` - カードID
+    pub fn generate(seed: u32, id: CardId) -> CardGrid<Option<u8>> {
+        let mut rng = XorShift::quiet(derive_seed(derive_seed(seed, CARD_STREAM), id));
+        Self::generate_with(&mut rng)
+    }
+
+    /// 乱数生成器を指定してカードの数字を求める
+    ///
+    /// 発行するカードではなく、シミュレーションの仮想カードを作る場合に使います。
+    pub fn generate_with(rng: &mut dyn IRng) -> CardGrid<Option<u8>> {
+        let band = 75 / CARD_SIZE;
+        let mut numbers = [[None; CARD_SIZE]; CARD_SIZE];
+        for column in 0..CARD_SIZE {
+            let first = (column * band + 1) as u8;
+            let mut candidates: Vec<u8> = (first..first + band as u8).collect();
+            rng.shuffle(&mut candidates);
+            for (row, row_numbers) in numbers.iter_mut().enumerate() {
+                row_numbers[column] = Some(candidates[row]);
+            }
+        }
+        numbers[CARD_SIZE / 2][CARD_SIZE / 2] = None;
+        numbers
+    }
+
+    /// 抽選済みの数字に印を付ける (フリーマスは常に印が付く)
+    pub fn marks(&self, history: &[u8]) -> CardGrid<bool> {
+        marks(&self.numbers, history)
+    }
+}
+
+/// マスの数字のうち抽選済みのものに印を付ける (フリーマスは常に印が付く)
+pub fn marks(numbers: &CardGrid<Option<u8>>, history: &[u8]) -> CardGrid<bool> {
+    numbers.map(|row| row.map(|number| number.is_none_or(|n| history.contains(&n))))
+}
+
+/// 印刷したカードのQRコードに署名する鍵
+///
+/// QRコードにはカードIDと、カードIDとマスの数字に対するHMAC-SHA256の先頭を載せます。
+/// 鍵を知らなければ署名を作れないため、偽造したカードや別のシード値で作ったカードを見分けられます。
+#[derive(Clone)]
+pub struct CardKey {
+    secret: Vec<u8>,
+    /// 設定された鍵かどうか (起動ごとに生成した鍵はfalse)
+    configured: bool,
+}
+
+impl CardKey {
+    /// 秘密の文字列から鍵を作る
+    pub fn new(secret: &str) -> Self {
+        Self {
+            secret: secret.as_bytes().to_vec(),
+            configured: true,
+        }
+    }
+
+    /// OSの乱数から鍵を作る
+    ///
+    /// 鍵が設定されていない場合に使います。起動ごとに変わるため、
+    /// この鍵で署名したカードは再起動すると検証できません。
+    pub fn random() -> Self {
+        let mut secret = vec![0; RANDOM_KEY_BYTES];
+        getrandom::getrandom(&mut secret).expect("OS random number generator is unavailable");
+        Self {
+            secret,
+            configured: false,
+        }
+    }
+
+    /// 設定された鍵かどうか (印刷するカードには再起動しても変わらない鍵が必要)
+    pub fn is_configured(&self) -> bool {
+        self.configured
+    }
+
+    /// 印刷したカードのQRコードの内容 (`XMASBINGO:<カードID>:<署名>`)
+    pub fn qr_payload(&self, id: CardId, numbers: &CardGrid<Option<u8>>) -> String {
+        format!("{}:{}:{}", QR_PREFIX, id, self.tag(id, numbers))
+    }
+
+    /// 読み取ったQRコードの内容を検証し、カードIDとマスの数字を返す
+    ///
+    /// マスの数字はシード値とカードIDから求め直して署名と照合するため、
+    /// 形式が違う・署名が合わない場合はどちらも `InvalidCard` になります。
+    ///
+    /// # Arguments
+    /// * `payload` - QRコードの内容 (大文字・小文字は区別しない)
+    /// * `seed` - セッションのシード値
+    pub fn verify(
+        &self,
+        payload: &str,
+        seed: u32,
+    ) -> Result<(CardId, CardGrid<Option<u8>>), CardError> {
+        let mut parts = payload.trim().split(':');
+        let (Some(prefix), Some(id), Some(tag), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(CardError::InvalidCard);
+        };
+        if !prefix.eq_ignore_ascii_case(QR_PREFIX) || !id.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(CardError::InvalidCard);
+        }
+        let id: CardId = id.parse().map_err(|_| CardError::InvalidCard)?;
+        let numbers = BingoCard::generate(seed, id);
+        let expected = self.tag(id, &numbers);
+        if !constant_time_eq(tag.to_ascii_uppercase().as_bytes(), expected.as_bytes()) {
+            return Err(CardError::InvalidCard);
+        }
+        Ok((id, numbers))
+    }
+
+    /// カードIDとマスの数字に対する署名 (HMAC-SHA256の先頭を16進数で表したもの)
+    fn tag(&self, id: CardId, numbers: &CardGrid<Option<u8>>) -> String {
+        let numbers: Vec<String> = numbers
+            .iter()
+            .flatten()
+            .map(|number| number.unwrap_or(0).to_string())
+            .collect();
+        let message = format!("{}:{}:{}", QR_PREFIX, id, numbers.join(","));
+        HMAC::mac(message, &self.secret)[..QR_TAG_BYTES]
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect()
+    }
+}
+
+impl fmt::Debug for CardKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CardKey(..)")
+    }
+}
+
+/// 当たりの形が揃っているマスを求める
+///
+/// # Arguments
+/// * `pattern` - 当たりの形
+/// * `marks` - 印の付いたマス
+///
+/// # Returns
+/// * `Option<CardGrid<bool>>` - 当たりの形を作るマス。揃っていない場合はNone
+pub fn winning_cells(pattern: WinPattern, marks: &CardGrid<bool>) -> Option<CardGrid<bool>> {
+    let complete: Vec<Vec<(usize, usize)>> = lines()
+        .into_iter()
+        .filter(|line| line.iter().all(|&(row, column)| marks[row][column]))
+        .collect();
+    let center = CARD_SIZE / 2;
+    let last = CARD_SIZE - 1;
+    let cells: Vec<(usize, usize)> = match pattern {
+        WinPattern::SingleLine => complete.first()?.clone(),
+        WinPattern::TwoLines if complete.len() >= 2 => complete[..2].concat(),
+        WinPattern::TwoLines => return None,
+        WinPattern::FourCorners => vec![(0, 0), (0, last), (last, 0), (last, last)],
+        WinPattern::Cross => (0..CARD_SIZE)
+            .flat_map(|i| [(center, i), (i, center)])
+            .collect(),
+        WinPattern::Blackout => (0..CARD_SIZE)
+            .flat_map(|row| (0..CARD_SIZE).map(move |column| (row, column)))
+            .collect(),
+    };
+    if !cells.iter().all(|&(row, column)| marks[row][column]) {
+        return None;
+    }
+    let mut grid = [[false; CARD_SIZE]; CARD_SIZE];
+    for (row, column) in cells {
+        grid[row][column] = true;
+    }
+    Some(grid)
+}
+
+/// 当たりの形が最初に揃った数字の抽選順を求める
+///
+/// 申告が遅れても、同じ数字で揃った当選者が同じ抽選順になるように、
+/// 抽選履歴の先頭から順に印を付けて最初に揃った時点を探します。
+///
+/// # Arguments
+/// * `pattern` - 当たりの形
+/// * `numbers` - カードのマスの数字
+/// * `history` - 抽選履歴
+///
+/// # Returns
+/// * `Option<usize>` - 当たりの形が揃った数字の抽選順 (1始まり)。揃っていない場合はNone
+pub fn completing_draw(
+    pattern: WinPattern,
+    numbers: &CardGrid<Option<u8>>,
+    history: &[u8],
+) -> Option<usize> {
+    (1..=history.len())
+        .find(|&count| winning_cells(pattern, &marks(numbers, &history[..count])).is_some())
+}
+
+/// 縦・横・斜めの全ての列 (横・縦・斜めの順)
+fn lines() -> Vec<Vec<(usize, usize)>> {
+    let rows = (0..CARD_SIZE).map(|row| (0..CARD_SIZE).map(|column| (row, column)).collect());
+    let columns = (0..CARD_SIZE).map(|column| (0..CARD_SIZE).map(|row| (row, column)).collect());
+    let diagonals = [
+        (0..CARD_SIZE).map(|i| (i, i)).collect(),
+        (0..CARD_SIZE).map(|i| (i, CARD_SIZE - 1 - i)).collect(),
+    ];
+    rows.chain(columns).chain(diagonals).collect()
+}
+
+/// カードの操作に失敗した理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CardError {
+    /// 準備中 (`setup`) 以外のフェーズではカードを発行できない
+    NotSetup(GamePhase),
+    /// カードの形式がないビンゴの種類 (75ボールのみ対応)
+    UnsupportedVariant(BingoVariant),
+    /// 参加コードに対応するカードがない
+    NotFound,
+    /// カードの当たりの形が揃っていない
+    NotBingo,
+    /// QRコードの内容が正しくない (形式が違う・署名が合わない)
+    InvalidCard,
+    /// スマートフォン用のカードIDを使い切った (印刷するカードのIDと重なる)
+    Exhausted,
+}
+
+impl fmt::Display for CardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotSetup(phase) => {
+                write!(f, "cannot issue a card while the game is {}", phase)
+            }
+            Self::UnsupportedVariant(variant) => {
+                let variant = serde_json::to_value(variant).unwrap_or_default();
+                write!(
+                    f,
+                    "cards are not available for {} bingo",
+                    variant.as_str().unwrap_or_default()
+                )
+            }
+            Self::NotFound => write!(f, "no card has this join code"),
+            Self::NotBingo => write!(f, "the card does not match the winning pattern"),
+            Self::InvalidCard => write!(f, "the card is forged or has been altered"),
+            Self::Exhausted => write!(
+                f,
+                "no card IDs are left below the printed card IDs ({})",
+                PRINTED_CARD_FIRST_ID
+            ),
+        }
+    }
+}
+
+/// 発行したビンゴカードの一覧
+#[derive(Debug, Clone)]
+pub struct CardRegistry {
+    /// 発行したカード (発行順)
+    cards: Vec<BingoCard>,
+    /// 次に発行するカードのID
+    next_id: CardId,
+}
+
+impl Default for CardRegistry {
+    fn default() -> Self {
+        Self {
+            cards: Vec::new(),
+            next_id: 1,
+        }
+    }
+}
+
+impl CardRegistry {
+    /// 空の一覧を作成する
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 発行したカード (発行順)
+    pub fn cards(&self) -> &[BingoCard] {
+        &self.cards
+    }
+
+    /// 参加コードでカードを探す
+    ///
+    /// 大文字・小文字、区切りの `-` や空白は区別しません。
+    pub fn find_by_code(&self, code: &str) -> Result<&BingoCard, CardError> {
+        let code: String = code
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '-')
+            .map(|c| c.to_ascii_uppercase())
+            .collect();
+        self.cards
+            .iter()
+            .find(|card| card.join_code == code)
+            .ok_or(CardError::NotFound)
+    }
+
+    /// 参加者にカードを発行する
+    ///
+    /// 1人に1枚だけ発行し、すでに発行済みの参加者には同じカードを返します
+    /// (紙のカードと違い、なくしても参加コードで開き直せるため)。
+    ///
+    /// # Arguments
+    /// * `player` - カードを持つ参加者のID
+    /// * `seed` - セッションのシード値
+    /// * `phase` - 現在のゲームのフェーズ
+    /// * `variant` - 現在のビンゴの種類
+    ///
+    /// # Returns
+    /// * `Result<(&BingoCard, bool), CardError>` - 発行したカードと、新しく発行したかどうか
+    ///   (カードIDが印刷するカードのIDに達した場合はエラー)
+    pub fn issue(
+        &mut self,
+        player: PlayerId,
+        seed: u32,
+        phase: GamePhase,
+        variant: BingoVariant,
+    ) -> Result<(&BingoCard, bool), CardError> {
+        if let Some(index) = self.cards.iter().position(|card| card.player == player) {
+            return Ok((&self.cards[index], false));
+        }
+        if variant != BingoVariant::Ball75 {
+            return Err(CardError::UnsupportedVariant(variant));
+        }
+        if phase != GamePhase::Setup {
+            return Err(CardError::NotSetup(phase));
+        }
+        if self.next_id >= PRINTED_CARD_FIRST_ID {
+            return Err(CardError::Exhausted);
+        }
+
+        let id = self.next_id;
+        let join_code = self.unused_join_code();
+        self.next_id += 1;
+        self.cards.push(BingoCard {
+            id,
+            player,
+            join_code,
+            numbers: BingoCard::generate(seed, id),
+            issued_at: Local::now(),
+        });
+        Ok((&self.cards[self.cards.len() - 1], true))
+    }
+
+    /// 発行済みのカードと重ならない参加コードを作る
+    ///
+    /// 参加コードはカードを開く鍵になるため、公開されているシード値からは求めず、
+    /// OSの乱数で初期化されたハッシュ関数から作ります。
+    fn unused_join_code(&self) -> String {
+        loop {
+            let code = join_code(RandomState::new().hash_one(self.next_id));
+            if self.find_by_code(&code).is_err() {
+                return code;
+            }
+        }
+    }
+}
+
+/// 乱数から参加コードを作る (1文字あたり5ビット)
+fn join_code(random: u64) -> String {
+    (0..JOIN_CODE_LEN)
+        .map(|i| {
+            JOIN_CODE_ALPHABET[(random >> (i * 5)) as usize % JOIN_CODE_ALPHABET.len()] as char
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cards_are_reproducible() {
+        let numbers = BingoCard::generate(1, 1);
+        assert_eq!(numbers, BingoCard::generate(1, 1));
+        assert_ne!(numbers, BingoCard::generate(1, 2));
+        assert_eq!(numbers[2][2], None);
+        for (column, range) in [(0, 1..=15), (1, 16..=30), (4, 61..=75)] {
+            let mut values: Vec<u8> = numbers.iter().filter_map(|row| row[column]).collect();
+            assert!(values.iter().all(|n| range.contains(n)));
+            values.sort();
+            values.dedup();
+            assert_eq!(values.len(), CARD_SIZE);
+        }
+
+        let mut registry = CardRegistry::new();
+        let (card, created) = registry
+            .issue(7, 1, GamePhase::Setup, BingoVariant::Ball75)
+            .unwrap();
+        assert!(created);
+        assert_eq!(card.numbers, numbers);
+        let code = card.join_code.to_lowercase();
+        assert_eq!(code.len(), JOIN_CODE_LEN);
+        assert_eq!(registry.find_by_code(&code).unwrap().player, 7);
+        assert_eq!(registry.find_by_code("??????"), Err(CardError::NotFound));
+
+        // 発行済みの参加者にはフェーズに関わらず同じカードを返す
+        let (card, created) = registry
+            .issue(7, 1, GamePhase::Running, BingoVariant::Ball75)
+            .unwrap();
+        assert!(!created);
+        assert_eq!(card.id, 1);
+        assert_eq!(
+            registry.issue(8, 1, GamePhase::Running, BingoVariant::Ball75),
+            Err(CardError::NotSetup(GamePhase::Running))
+        );
+        assert_eq!(
+            registry.issue(8, 1, GamePhase::Setup, BingoVariant::Ball90),
+            Err(CardError::UnsupportedVariant(BingoVariant::Ball90))
+        );
+
+        // 印刷するカードのIDとは重ならない
+        registry.next_id = PRINTED_CARD_FIRST_ID - 1;
+        let (card, _) = registry
+            .issue(8, 1, GamePhase::Setup, BingoVariant::Ball75)
+            .unwrap();
+        assert_eq!(card.id, PRINTED_CARD_FIRST_ID - 1);
+        assert_eq!(
+            registry.issue(9, 1, GamePhase::Setup, BingoVariant::Ball75),
+            Err(CardError::Exhausted)
+        );
+    }
+
+    #[test]
+    fn test_winning_cells() {
+        let mut registry = CardRegistry::new();
+        let (card, _) = registry
+            .issue(1, 1, GamePhase::Setup, BingoVariant::Ball75)
+            .unwrap();
+        let card = card.clone();
+        let column =
+            |c: usize| -> Vec<u8> { card.numbers.iter().filter_map(|row| row[c]).collect() };
+
+        // 中央の列はフリーマスを含むので4個で揃う
+        let marks = card.marks(&column(2));
+        let cells = winning_cells(WinPattern::SingleLine, &marks).unwrap();
+        assert!((0..CARD_SIZE).all(|row| cells[row][2]));
+        assert!(!cells[0][0]);
+        assert_eq!(winning_cells(WinPattern::TwoLines, &marks), None);
+        assert_eq!(winning_cells(WinPattern::Cross, &marks), None);
+
+        let mut history = column(2);
+        history.extend(column(0));
+        let marks = card.marks(&history);
+        assert!(winning_cells(WinPattern::TwoLines, &marks).is_some());
+        assert_eq!(winning_cells(WinPattern::FourCorners, &marks), None);
+        history.extend(column(4));
+        assert!(winning_cells(WinPattern::FourCorners, &card.marks(&history)).is_some());
+        // 後から別の列が揃っても、最初に揃った数字の抽選順のまま
+        assert_eq!(
+            completing_draw(WinPattern::SingleLine, &card.numbers, &history),
+            Some(4)
+        );
+        assert_eq!(
+            completing_draw(WinPattern::TwoLines, &card.numbers, &history),
+            Some(9)
+        );
+        assert_eq!(
+            completing_draw(WinPattern::Blackout, &card.numbers, &history),
+            None
+        );
+        assert_eq!(
+            winning_cells(WinPattern::Blackout, &card.marks(&history)),
+            None
+        );
+        assert!(winning_cells(
+            WinPattern::Blackout,
+            &card.marks(&(1..=75).collect::<Vec<_>>())
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn test_qr_payload_verification() {
+        let key = CardKey::new("secret");
+        let numbers = BingoCard::generate(7, 3);
+        let payload = key.qr_payload(3, &numbers);
+        assert!(payload.starts_with("XMASBINGO:3:"));
+        assert_eq!(key.verify(&payload, 7), Ok((3, numbers)));
+        assert_eq!(key.verify(&payload.to_lowercase(), 7), Ok((3, numbers)));
+
+        // 別の鍵・別のシード値・書き換えたカードIDや署名は受け付けない
+        let invalid = Err(CardError::InvalidCard);
+        assert_eq!(CardKey::new("other").verify(&payload, 7), invalid);
+        let random = CardKey::random();
+        assert!(key.is_configured() && !random.is_configured());
+        assert_eq!(random.verify(&payload, 7), invalid);
+        assert_ne!(random.secret, CardKey::random().secret);
+        assert_eq!(key.verify(&payload, 8), invalid);
+        assert_eq!(key.verify(&payload.replacen(":3:", ":4:", 1), 7), invalid);
+        let mut forged = payload.clone();
+        let last = if forged.ends_with('0') { "1" } else { "0" };
+        forged.replace_range(forged.len() - 1.., last);
+        assert_eq!(key.verify(&forged, 7), invalid);
+        for malformed in ["", "XMASBINGO:3", "XMASBINGO:+3:00", "OTHER:3:00"] {
+            assert_eq!(key.verify(malformed, 7), invalid);
+        }
+    }
+}
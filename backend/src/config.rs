@@ -0,0 +1,708 @@
+use crate::autodraw;
+use crate::card::PRINTED_CARD_FIRST_ID;
+use crate::cors::{AllowedOrigins, CorsPolicy};
+use crate::frontend::{self, FrontendSource};
+use crate::session::WinPattern;
+use crate::sheet::SheetFormat;
+use clap::{Parser, Subcommand};
+use serde::Deserialize;
+use std::fmt;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 設定ファイルを指定しなかった場合に探すファイル名
+const DEFAULT_CONFIG_FILE: &str = "bingo.toml";
+/// デフォルトの待ち受けアドレス
+const DEFAULT_BIND: &str = "0.0.0.0:3000";
+/// デフォルトのシードファイル
+const DEFAULT_SEEDS_FILE: &str = "seeds.txt";
+/// デフォルトの景品数ファイル
+const DEFAULT_PRIZE_FILE: &str = "prize.txt";
+/// 管理者トークンの最小の長さ
+const MIN_ADMIN_TOKEN_LEN: usize = 4;
+/// デフォルトのビンゴの数字の最大値
+const DEFAULT_BINGO_MAX_NUMBER: usize = 75;
+/// 景品数ファイルがない場合の景品数
+const DEFAULT_PRIZE_COUNT: usize = 8;
+/// デフォルトの自動抽選の間隔 (秒)
+const DEFAULT_AUTO_DRAW_INTERVAL_SECS: u64 = 30;
+
+/// コマンドライン引数
+///
+/// 各オプションは環境変数でも指定でき、コマンドライン引数が優先される。
+#[derive(Debug, Default, Parser)]
+#[command(version, about = "Christmas Bingo System Backend Server")]
+pub struct Args {
+    /// 設定ファイル (TOML) のパス
+    #[arg(long, env = "BINGO_CONFIG")]
+    pub config: Option<PathBuf>,
+    /// 待ち受けアドレス (例: 0.0.0.0:3000)
+    #[arg(long, env = "BINGO_BIND")]
+    pub bind: Option<String>,
+    /// シードファイルのパス
+    #[arg(long, env = "BINGO_SEEDS_FILE")]
+    pub seeds_file: Option<PathBuf>,
+    /// 景品数ファイルのパス
+    #[arg(long, env = "BINGO_PRIZE_FILE")]
+    pub prize_file: Option<PathBuf>,
+    /// 景品数 (指定した場合は景品数ファイルを読まない)
+    #[arg(long, env = "BINGO_PRIZE_COUNT")]
+    pub prize_count: Option<usize>,
+    /// ビンゴの数字の最大値
+    #[arg(long, env = "BINGO_MAX_NUMBER")]
+    pub bingo_max_number: Option<usize>,
+    /// 自動抽選の間隔 (秒、開始時や実行中にAPIで変更できる)
+    #[arg(long, env = "BINGO_AUTO_DRAW_INTERVAL")]
+    pub auto_draw_interval: Option<u64>,
+    /// CORSで許可するオリジン (カンマ区切り、`*` で全て許可、`same-origin` で自分自身のみ)
+    #[arg(long, env = "BINGO_CORS_ORIGINS", value_delimiter = ',')]
+    pub cors_origins: Option<Vec<String>>,
+    /// CORSで許可するメソッド (カンマ区切り、`*` で全て許可)
+    #[arg(long, env = "BINGO_CORS_METHODS", value_delimiter = ',')]
+    pub cors_methods: Option<Vec<String>>,
+    /// CORSで許可するリクエストヘッダー (カンマ区切り、`*` で全て許可)
+    #[arg(long, env = "BINGO_CORS_HEADERS", value_delimiter = ',')]
+    pub cors_headers: Option<Vec<String>>,
+    /// CORSで認証情報付きリクエストを許可するか
+    #[arg(long, env = "BINGO_CORS_CREDENTIALS")]
+    pub cors_credentials: Option<bool>,
+    /// 管理者トークン (省略時は起動時に司会者用PINを生成する)
+    #[arg(long, env = "BINGO_ADMIN_TOKEN", hide_env_values = true)]
+    pub admin_token: Option<String>,
+    /// 印刷したカードのQRコードに署名する鍵 (省略時は起動ごとに生成するため、カードを印刷できない)
+    #[arg(long, env = "BINGO_CARD_SECRET", hide_env_values = true)]
+    pub card_secret: Option<String>,
+    /// フロントエンド (`frontend/dist`) を配信するディレクトリ
+    /// (省略時はバイナリに埋め込んだファイルを配信する)
+    #[arg(long, env = "BINGO_FRONTEND_DIR")]
+    pub frontend_dir: Option<PathBuf>,
+    /// フロントエンドを配信しない (APIのみ)
+    #[arg(long, env = "BINGO_NO_FRONTEND")]
+    pub no_frontend: bool,
+    /// サブコマンド (省略時はサーバーを起動する)
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// サブコマンド
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand)]
+pub enum Command {
+    /// 印刷用のビンゴカードのシートを出力して終了する
+    ///
+    /// カードの数字はシードファイルから求めたシード値とカードIDから決まり、
+    /// 同じIDでサーバーが発行するカードと一致する。
+    Cards(CardsArgs),
+    /// 仮想カードでゲームを繰り返しシミュレーションし、ラウンドの長さを見積もって終了する
+    ///
+    /// 実際のゲームと同じ抽選と当たりの形の判定を使い、最初の当選者が出るまでの抽選回数・
+    /// 同時に揃った当選者の数・ラウンドの長さ・イベントの時間内に終えられるラウンドの数の分布を出力する。
+    Simulate(SimulateArgs),
+}
+
+/// `simulate` サブコマンドの引数
+#[derive(Debug, Clone, PartialEq, Eq, clap::Args)]
+pub struct SimulateArgs {
+    /// 参加者 (仮想カード) の数
+    #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..=1000))]
+    pub players: u32,
+    /// シミュレーションするゲームの数
+    #[arg(
+        short = 'n',
+        long,
+        default_value_t = 1000,
+        value_parser = clap::value_parser!(u32).range(1..=100_000)
+    )]
+    pub games: u32,
+    /// 当たりの形
+    #[arg(long, value_enum, default_value_t = WinPattern::SingleLine)]
+    pub pattern: WinPattern,
+    /// 抽選間隔 (秒、省略時は自動抽選の間隔)
+    #[arg(long)]
+    pub draw_interval: Option<u64>,
+    /// 1ラウンドあたりの抽選以外の時間 (秒、当選の確認や景品の受け渡し)
+    #[arg(long, default_value_t = 0)]
+    pub overhead: u64,
+    /// イベントの時間 (分)
+    #[arg(long, default_value_t = 30, value_parser = clap::value_parser!(u64).range(1..=24 * 60))]
+    pub event_minutes: u64,
+    /// シード値 (省略時はシードファイルから求めたシード値)
+    #[arg(long)]
+    pub seed: Option<u32>,
+    /// 結果をJSONで出力する
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// `cards` サブコマンドの引数
+#[derive(Debug, Clone, PartialEq, Eq, clap::Args)]
+pub struct CardsArgs {
+    /// 出力するカードの枚数
+    #[arg(short = 'n', long, value_parser = clap::value_parser!(u32).range(1..=10_000))]
+    pub count: u32,
+    /// 最初のカードID (スマートフォン用のカードと重ならないよう100000以上)
+    #[arg(
+        long,
+        default_value_t = PRINTED_CARD_FIRST_ID,
+        value_parser = clap::value_parser!(u32).range(PRINTED_CARD_FIRST_ID as i64..)
+    )]
+    pub first: u32,
+    /// 出力形式
+    #[arg(long, value_enum, default_value_t = SheetFormat::Pdf)]
+    pub format: SheetFormat,
+    /// 出力先 (省略時は `cards.pdf` / `cards.svg`、SVGが複数ページの場合は `cards-1.svg` のように連番を付ける)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+/// 設定ファイル (TOML) の内容
+///
+/// ```toml
+/// [server]
+/// bind = "0.0.0.0:3000"
+///
+/// [seed]
+/// file = "seeds.txt"
+///
+/// [bingo]
+/// max_number = 75
+/// auto_draw_interval = 30
+///
+/// [amida]
+/// prize_file = "prize.txt"
+/// # prize_count = 8
+///
+/// [cors]
+/// allowed_origins = ["*"]
+/// allowed_methods = ["GET", "POST", "PUT", "PATCH", "DELETE"]
+/// allowed_headers = ["content-type", "authorization", "x-admin-token"]
+/// allow_credentials = false
+///
+/// [auth]
+/// admin_token = "change-me"
+/// # card_secret = "change-me-too"
+///
+/// [frontend]
+/// # dir = "../frontend/dist"
+/// enabled = true
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    pub server: ServerSection,
+    pub seed: SeedSection,
+    pub bingo: BingoSection,
+    pub amida: AmidaSection,
+    pub cors: CorsSection,
+    pub auth: AuthSection,
+    pub frontend: FrontendSection,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerSection {
+    pub bind: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SeedSection {
+    pub file: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BingoSection {
+    pub max_number: Option<usize>,
+    pub auto_draw_interval: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AmidaSection {
+    pub prize_file: Option<PathBuf>,
+    pub prize_count: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CorsSection {
+    pub allowed_origins: Option<Vec<String>>,
+    pub allowed_methods: Option<Vec<String>>,
+    pub allowed_headers: Option<Vec<String>>,
+    pub allow_credentials: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthSection {
+    pub admin_token: Option<String>,
+    pub card_secret: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FrontendSection {
+    pub dir: Option<PathBuf>,
+    pub enabled: Option<bool>,
+}
+
+/// 景品数の決定方法
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrizeSource {
+    /// 設定で直接指定された
+    Configured,
+    /// 景品数ファイルから読み込んだ
+    File(PathBuf),
+    /// 景品数ファイルがなかったためデフォルト値を使用した
+    Default,
+}
+
+/// 検証済みのアプリケーション設定
+///
+/// 優先順位は「コマンドライン引数 > 環境変数 > 設定ファイル > デフォルト値」
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    /// 読み込んだ設定ファイル (なければNone)
+    pub config_file: Option<PathBuf>,
+    /// 待ち受けアドレス
+    pub bind: SocketAddr,
+    /// シードファイルのパス
+    pub seeds_file: PathBuf,
+    /// ビンゴの数字の最大値
+    pub bingo_max_number: usize,
+    /// 自動抽選のデフォルトの間隔
+    pub auto_draw_interval: Duration,
+    /// あみだくじの景品数
+    pub prize_count: usize,
+    /// 景品数の決定方法
+    pub prize_source: PrizeSource,
+    /// フロントエンドの配信元
+    pub frontend: FrontendSource,
+    /// CORSの設定
+    pub cors: CorsPolicy,
+    /// 管理者トークン (Noneの場合は起動時に司会者用PINを生成する)
+    pub admin_token: Option<String>,
+    /// 印刷したカードのQRコードに署名する鍵 (Noneの場合は起動ごとに生成する)
+    pub card_secret: Option<String>,
+    /// サブコマンド (Noneの場合はサーバーを起動する)
+    pub command: Option<Command>,
+}
+
+/// 設定の読み込み・検証エラー
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// 設定ファイルを読み込めない
+    ReadFile(PathBuf, String),
+    /// 設定ファイルの形式が正しくない
+    ParseFile(PathBuf, String),
+    /// 設定値が正しくない
+    Invalid(&'static str, String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ReadFile(path, e) => write!(f, "cannot read {}: {}", path.display(), e),
+            Self::ParseFile(path, e) => write!(f, "invalid config file {}: {}", path.display(), e),
+            Self::Invalid(key, e) => write!(f, "invalid {}: {}", key, e),
+        }
+    }
+}
+
+impl Config {
+    /// コマンドライン引数・環境変数・設定ファイルから設定を読み込む
+    pub fn load() -> Result<Self, ConfigError> {
+        Self::from_args(Args::parse())
+    }
+
+    /// 解析済みの引数から設定を読み込む
+    ///
+    /// 設定ファイルが指定されていない場合は `bingo.toml` があれば読み込む。
+    /// 明示的に指定された設定ファイル・景品数ファイルが存在しない場合はエラーとする。
+    pub fn from_args(args: Args) -> Result<Self, ConfigError> {
+        let (file, config_file) = match &args.config {
+            Some(path) => (read_config_file(path)?, Some(path.clone())),
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                let path = PathBuf::from(DEFAULT_CONFIG_FILE);
+                (read_config_file(&path)?, Some(path))
+            }
+            None => (ConfigFile::default(), None),
+        };
+        Self::resolve(args, file, config_file)
+    }
+
+    /// 引数 (環境変数を含む) と設定ファイルの内容を重ね合わせ、検証する
+    pub fn resolve(
+        args: Args,
+        file: ConfigFile,
+        config_file: Option<PathBuf>,
+    ) -> Result<Self, ConfigError> {
+        let bind = args
+            .bind
+            .or(file.server.bind)
+            .unwrap_or_else(|| DEFAULT_BIND.to_string());
+        let bind: SocketAddr = bind
+            .parse()
+            .map_err(|_| ConfigError::Invalid("bind", bind.clone()))?;
+
+        let seeds_file = args
+            .seeds_file
+            .or(file.seed.file)
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_SEEDS_FILE));
+
+        let bingo_max_number = args
+            .bingo_max_number
+            .or(file.bingo.max_number)
+            .unwrap_or(DEFAULT_BINGO_MAX_NUMBER);
+        if !(1..=u8::MAX as usize).contains(&bingo_max_number) {
+            return Err(ConfigError::Invalid(
+                "bingo max number",
+                format!("{} (must be 1-255)", bingo_max_number),
+            ));
+        }
+
+        let auto_draw_interval = autodraw::interval_from_secs(
+            args.auto_draw_interval
+                .or(file.bingo.auto_draw_interval)
+                .unwrap_or(DEFAULT_AUTO_DRAW_INTERVAL_SECS),
+        )
+        .map_err(|e| ConfigError::Invalid("auto draw interval", e))?;
+
+        let prize_file = args.prize_file.or(file.amida.prize_file);
+        let (prize_count, prize_source) = match args.prize_count.or(file.amida.prize_count) {
+            Some(count) => (count, PrizeSource::Configured),
+            None => read_prize_file(prize_file)?,
+        };
+        if !(1..=u8::MAX as usize).contains(&prize_count) {
+            return Err(ConfigError::Invalid(
+                "prize count",
+                format!("{} (must be 1-255)", prize_count),
+            ));
+        }
+
+        let enabled = !args.no_frontend && file.frontend.enabled.unwrap_or(true);
+        let frontend = match args.frontend_dir.or(file.frontend.dir) {
+            _ if !enabled => FrontendSource::Disabled,
+            Some(dir) => FrontendSource::Dir(dir),
+            None if frontend::embedded_available() => FrontendSource::Embedded,
+            None => FrontendSource::Disabled,
+        };
+        frontend
+            .validate()
+            .map_err(|e| ConfigError::Invalid("frontend dir", e))?;
+
+        // オリジンを指定しない場合、フロントエンドを自分で配信するときは自分自身のみ許可し、
+        // 別のサーバー (開発サーバーなど) から配信されるときは全て許可する
+        let default_origins = if frontend.is_served() {
+            AllowedOrigins::SameOrigin
+        } else {
+            AllowedOrigins::Any
+        };
+        let cors = CorsPolicy::parse(
+            args.cors_origins.or(file.cors.allowed_origins),
+            args.cors_methods.or(file.cors.allowed_methods),
+            args.cors_headers.or(file.cors.allowed_headers),
+            args.cors_credentials
+                .or(file.cors.allow_credentials)
+                .unwrap_or(false),
+            default_origins,
+        )?;
+
+        let admin_token = args.admin_token.or(file.auth.admin_token);
+        let card_secret = args.card_secret.or(file.auth.card_secret);
+        for (key, secret) in [("admin token", &admin_token), ("card secret", &card_secret)] {
+            if let Some(secret) = secret {
+                if secret.chars().count() < MIN_ADMIN_TOKEN_LEN
+                    || secret.chars().any(char::is_whitespace)
+                {
+                    return Err(ConfigError::Invalid(
+                        key,
+                        format!(
+                            "must be at least {} characters without spaces",
+                            MIN_ADMIN_TOKEN_LEN
+                        ),
+                    ));
+                }
+            }
+        }
+
+        Ok(Self {
+            config_file,
+            bind,
+            seeds_file,
+            bingo_max_number,
+            auto_draw_interval,
+            prize_count,
+            prize_source,
+            frontend,
+            cors,
+            admin_token,
+            card_secret,
+            command: args.command,
+        })
+    }
+
+    /// 印刷したカードのQRコードに署名する鍵
+    ///
+    /// 指定されていない場合はNone
+    /// (サーバーは起動ごとに乱数から鍵を生成するため、印刷したカードは再起動すると検証できない)。
+    /// 管理者トークンは他の人に渡すことがあるため、鍵には使わない。
+    pub fn card_key_secret(&self) -> Option<&str> {
+        self.card_secret.as_deref()
+    }
+}
+
+impl fmt::Display for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Resolved configuration:")?;
+        match &self.config_file {
+            Some(path) => writeln!(f, "  config file      : {}", path.display())?,
+            None => writeln!(f, "  config file      : (none)")?,
+        }
+        writeln!(f, "  bind             : {}", self.bind)?;
+        writeln!(f, "  seeds file       : {}", self.seeds_file.display())?;
+        writeln!(f, "  bingo max number : {}", self.bingo_max_number)?;
+        writeln!(
+            f,
+            "  auto draw        : every {}s",
+            self.auto_draw_interval.as_secs()
+        )?;
+        match &self.prize_source {
+            PrizeSource::Configured => writeln!(f, "  prize count      : {}", self.prize_count)?,
+            PrizeSource::File(path) => writeln!(
+                f,
+                "  prize count      : {} (from {})",
+                self.prize_count,
+                path.display()
+            )?,
+            PrizeSource::Default => {
+                writeln!(f, "  prize count      : {} (default)", self.prize_count)?
+            }
+        }
+        writeln!(f, "  frontend         : {}", self.frontend)?;
+        writeln!(f, "  cors             : {}", self.cors)?;
+        match &self.admin_token {
+            Some(_) => writeln!(f, "  admin token      : (configured)")?,
+            None => writeln!(f, "  admin token      : (host PIN generated at startup)")?,
+        }
+        match &self.card_secret {
+            Some(_) => write!(f, "  card secret      : (configured)"),
+            None => write!(f, "  card secret      : (random, changes at restart)"),
+        }
+    }
+}
+
+/// 設定ファイルを読み込む
+fn read_config_file(path: &Path) -> Result<ConfigFile, ConfigError> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| ConfigError::ReadFile(path.to_path_buf(), e.to_string()))?;
+    toml::from_str(&text).map_err(|e| ConfigError::ParseFile(path.to_path_buf(), e.to_string()))
+}
+
+/// 景品数ファイルから景品数を読み込む
+///
+/// パスが指定されていない場合は `prize.txt` を探し、なければデフォルト値を使う。
+fn read_prize_file(path: Option<PathBuf>) -> Result<(usize, PrizeSource), ConfigError> {
+    let explicit = path.is_some();
+    let path = path.unwrap_or_else(|| PathBuf::from(DEFAULT_PRIZE_FILE));
+    if !explicit && !path.exists() {
+        return Ok((DEFAULT_PRIZE_COUNT, PrizeSource::Default));
+    }
+
+    let text = fs::read_to_string(&path)
+        .map_err(|e| ConfigError::ReadFile(path.clone(), e.to_string()))?;
+    let count = text.trim().parse().map_err(|_| {
+        ConfigError::Invalid(
+            "prize file",
+            format!("{}: '{}'", path.display(), text.trim()),
+        )
+    })?;
+    Ok((count, PrizeSource::File(path)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(text: &str) -> ConfigFile {
+        toml::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn test_defaults() {
+        let args = Args {
+            prize_count: Some(8),
+            no_frontend: true,
+            ..Args::default()
+        };
+        let config = Config::resolve(args, ConfigFile::default(), None).unwrap();
+        assert_eq!(config.bind, "0.0.0.0:3000".parse().unwrap());
+        assert_eq!(config.seeds_file, PathBuf::from("seeds.txt"));
+        assert_eq!(config.bingo_max_number, 75);
+        assert_eq!(config.frontend, FrontendSource::Disabled);
+        assert_eq!(config.cors.origins, AllowedOrigins::Any);
+        assert_eq!(config.card_key_secret(), None);
+    }
+
+    #[test]
+    fn test_frontend_dir_defaults_to_same_origin_cors() {
+        let dir = std::env::temp_dir().join(format!("bingo-frontend-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("index.html"), "<!doctype html>").unwrap();
+
+        let args = Args {
+            prize_count: Some(8),
+            frontend_dir: Some(dir.clone()),
+            ..Args::default()
+        };
+        let config = Config::resolve(args, ConfigFile::default(), None).unwrap();
+        assert_eq!(config.frontend, FrontendSource::Dir(dir.clone()));
+        assert_eq!(config.cors.origins, AllowedOrigins::SameOrigin);
+
+        fs::remove_dir_all(&dir).unwrap();
+        let args = Args {
+            prize_count: Some(8),
+            frontend_dir: Some(dir),
+            ..Args::default()
+        };
+        assert!(Config::resolve(args, ConfigFile::default(), None).is_err());
+    }
+
+    #[test]
+    fn test_args_override_file() {
+        let file = file(
+            r#"
+            [server]
+            bind = "127.0.0.1:8000"
+            [bingo]
+            max_number = 90
+            auto_draw_interval = 45
+            [amida]
+            prize_count = 5
+            [cors]
+            allowed_origins = ["http://localhost:5173/"]
+            "#,
+        );
+        let args = Args {
+            bind: Some("127.0.0.1:9000".to_string()),
+            ..Args::default()
+        };
+
+        let config = Config::resolve(args, file, None).unwrap();
+        assert_eq!(config.bind, "127.0.0.1:9000".parse().unwrap());
+        assert_eq!(config.bingo_max_number, 90);
+        assert_eq!(config.auto_draw_interval, Duration::from_secs(45));
+        assert_eq!(config.prize_count, 5);
+        assert_eq!(config.prize_source, PrizeSource::Configured);
+        assert_eq!(
+            config.cors.origins,
+            AllowedOrigins::List(vec!["http://localhost:5173".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        let invalid = |args: Args| Config::resolve(args, ConfigFile::default(), None).is_err();
+
+        assert!(invalid(Args {
+            bind: Some("localhost".to_string()),
+            prize_count: Some(8),
+            ..Args::default()
+        }));
+        assert!(invalid(Args {
+            bingo_max_number: Some(300),
+            prize_count: Some(8),
+            ..Args::default()
+        }));
+        assert!(invalid(Args {
+            auto_draw_interval: Some(0),
+            prize_count: Some(8),
+            ..Args::default()
+        }));
+        assert!(invalid(Args {
+            prize_count: Some(0),
+            ..Args::default()
+        }));
+        assert!(invalid(Args {
+            prize_file: Some(PathBuf::from("does-not-exist.txt")),
+            ..Args::default()
+        }));
+        assert!(invalid(Args {
+            admin_token: Some("a b c d".to_string()),
+            prize_count: Some(8),
+            ..Args::default()
+        }));
+        assert!(invalid(Args {
+            card_secret: Some("abc".to_string()),
+            prize_count: Some(8),
+            ..Args::default()
+        }));
+        assert!(invalid(Args {
+            cors_origins: Some(vec!["example.com".to_string()]),
+            prize_count: Some(8),
+            ..Args::default()
+        }));
+    }
+
+    #[test]
+    fn test_unknown_keys_are_rejected() {
+        assert!(toml::from_str::<ConfigFile>("[server]\nport = 3000\n").is_err());
+    }
+
+    #[test]
+    fn test_cli_parsing() {
+        let args = Args::try_parse_from([
+            "backend",
+            "--bind",
+            "127.0.0.1:4000",
+            "--cors-origins",
+            "http://a.example,http://b.example",
+        ])
+        .unwrap();
+        assert_eq!(args.bind.as_deref(), Some("127.0.0.1:4000"));
+        assert_eq!(args.cors_origins.map(|o| o.len()), Some(2));
+
+        let args = Args::try_parse_from([
+            "backend",
+            "--admin-token",
+            "admin-token",
+            "cards",
+            "-n",
+            "12",
+            "--format",
+            "svg",
+        ])
+        .unwrap();
+        let Some(Command::Cards(cards)) = &args.command else {
+            panic!("cards subcommand is not parsed");
+        };
+        assert_eq!((cards.count, cards.first), (12, PRINTED_CARD_FIRST_ID));
+        assert_eq!(cards.format, SheetFormat::Svg);
+        let config = Config::resolve(args, ConfigFile::default(), None).unwrap();
+        assert_eq!(config.card_key_secret(), None);
+        assert!(Args::try_parse_from(["backend", "cards", "-n", "0"]).is_err());
+        assert!(Args::try_parse_from(["backend", "cards", "-n", "1", "--first", "101"]).is_err());
+
+        let args = Args::try_parse_from([
+            "backend",
+            "simulate",
+            "--players",
+            "80",
+            "--pattern",
+            "four_corners",
+            "--draw-interval",
+            "12",
+        ])
+        .unwrap();
+        let Some(Command::Simulate(simulate)) = &args.command else {
+            panic!("simulate subcommand is not parsed");
+        };
+        assert_eq!((simulate.players, simulate.games), (80, 1000));
+        assert_eq!(simulate.pattern, WinPattern::FourCorners);
+        assert_eq!(simulate.draw_interval, Some(12));
+        assert_eq!(simulate.event_minutes, 30);
+        assert!(Args::try_parse_from(["backend", "simulate"]).is_err());
+    }
+}
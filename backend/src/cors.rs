@@ -0,0 +1,306 @@
+use crate::auth::ADMIN_TOKEN_HEADER;
+use crate::config::ConfigError;
+use axum::http::{header, HeaderName, HeaderValue, Method};
+use std::fmt;
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, Any, CorsLayer};
+
+/// 全てを許可する指定
+const WILDCARD: &str = "*";
+/// 自分自身のオリジンのみ許可する指定 (空のリストと同じ)
+const SAME_ORIGIN: &str = "same-origin";
+
+/// デフォルトで許可するメソッド
+const DEFAULT_METHODS: &[Method] = &[
+    Method::GET,
+    Method::POST,
+    Method::PUT,
+    Method::PATCH,
+    Method::DELETE,
+];
+
+/// CORSで許可するオリジン
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AllowedOrigins {
+    /// 自分自身のオリジンのみ許可する (CORSヘッダーを返さない)
+    SameOrigin,
+    /// 全てのオリジンを許可する (開発用)
+    Any,
+    /// 列挙したオリジンのみ許可する
+    List(Vec<String>),
+}
+
+/// CORSで許可するメソッドまたはヘッダー
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AllowList<T> {
+    /// 全て許可する
+    Any,
+    /// 列挙したもののみ許可する
+    List(Vec<T>),
+}
+
+/// CORSの設定
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorsPolicy {
+    /// 許可するオリジン
+    pub origins: AllowedOrigins,
+    /// 許可するメソッド
+    pub methods: AllowList<Method>,
+    /// 許可するリクエストヘッダー
+    pub headers: AllowList<HeaderName>,
+    /// Cookieや `Authorization` ヘッダーなどの認証情報付きリクエストを許可するか
+    pub allow_credentials: bool,
+}
+
+impl CorsPolicy {
+    /// 設定値からCORSの設定を作成し、検証する
+    ///
+    /// 省略した項目は以下のデフォルト値になる。
+    /// * オリジン: `default_origins`
+    /// * メソッド: `GET`, `POST`, `PUT`, `PATCH`, `DELETE`
+    /// * ヘッダー: `Content-Type`, `Authorization`, `X-Admin-Token`, `If-None-Match`
+    ///
+    /// 認証情報付きリクエストを許可する場合、オリジン・メソッド・ヘッダーに `*` は指定できない。
+    ///
+    /// # Arguments
+    /// * `origins` - 許可するオリジン (`*` で全て許可、空のリストまたは `same-origin` で自分自身のみ)
+    /// * `methods` - 許可するメソッド (`*` で全て許可)
+    /// * `headers` - 許可するリクエストヘッダー (`*` で全て許可)
+    /// * `allow_credentials` - 認証情報付きリクエストを許可するか
+    /// * `default_origins` - オリジンを省略した場合の値
+    pub fn parse(
+        origins: Option<Vec<String>>,
+        methods: Option<Vec<String>>,
+        headers: Option<Vec<String>>,
+        allow_credentials: bool,
+        default_origins: AllowedOrigins,
+    ) -> Result<Self, ConfigError> {
+        let origins = match origins {
+            Some(origins) => parse_origins(origins)?,
+            None => default_origins,
+        };
+        let methods = match methods {
+            Some(methods) => parse_list(methods, "cors method", |m| {
+                Method::from_bytes(m.to_ascii_uppercase().as_bytes()).ok()
+            })?,
+            None => AllowList::List(DEFAULT_METHODS.to_vec()),
+        };
+        let headers = match headers {
+            Some(headers) => parse_list(headers, "cors header", |h| {
+                HeaderName::from_bytes(h.as_bytes()).ok()
+            })?,
+            None => AllowList::List(vec![
+                header::CONTENT_TYPE,
+                header::AUTHORIZATION,
+                HeaderName::from_static(ADMIN_TOKEN_HEADER),
+                header::IF_NONE_MATCH,
+            ]),
+        };
+
+        if allow_credentials {
+            if origins == AllowedOrigins::Any {
+                return Err(credentials_error("origins"));
+            }
+            if methods == AllowList::Any {
+                return Err(credentials_error("methods"));
+            }
+            if headers == AllowList::Any {
+                return Err(credentials_error("headers"));
+            }
+        }
+
+        Ok(Self {
+            origins,
+            methods,
+            headers,
+            allow_credentials,
+        })
+    }
+
+    /// 設定からCORSのミドルウェアを作成する
+    ///
+    /// 条件付きリクエストと非推奨APIの案内のため、`ETag`・`Deprecation`・`Link` ヘッダーを公開する。
+    pub fn layer(&self) -> CorsLayer {
+        let layer = match &self.origins {
+            // 同一オリジンからのリクエストにはCORSヘッダーは不要なため、何も許可しない
+            AllowedOrigins::SameOrigin => return CorsLayer::new(),
+            AllowedOrigins::Any => CorsLayer::new().allow_origin(Any),
+            AllowedOrigins::List(origins) => CorsLayer::new().allow_origin(AllowOrigin::list(
+                origins.iter().filter_map(|o| HeaderValue::from_str(o).ok()),
+            )),
+        };
+        let layer = match &self.methods {
+            AllowList::Any => layer.allow_methods(AllowMethods::any()),
+            AllowList::List(methods) => layer.allow_methods(methods.clone()),
+        };
+        let layer = match &self.headers {
+            AllowList::Any => layer.allow_headers(AllowHeaders::any()),
+            AllowList::List(headers) => layer.allow_headers(headers.clone()),
+        };
+        layer
+            .expose_headers([
+                header::ETAG,
+                HeaderName::from_static("deprecation"),
+                header::LINK,
+            ])
+            .allow_credentials(self.allow_credentials)
+    }
+}
+
+impl fmt::Display for CorsPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.origins {
+            AllowedOrigins::SameOrigin => write!(f, "same origin only")?,
+            AllowedOrigins::Any => write!(f, "* (any)")?,
+            AllowedOrigins::List(origins) => write!(f, "{}", origins.join(", "))?,
+        }
+        if self.origins == AllowedOrigins::SameOrigin {
+            return Ok(());
+        }
+        let methods = match &self.methods {
+            AllowList::Any => WILDCARD.to_string(),
+            AllowList::List(methods) => methods
+                .iter()
+                .map(Method::as_str)
+                .collect::<Vec<_>>()
+                .join(", "),
+        };
+        let headers = match &self.headers {
+            AllowList::Any => WILDCARD.to_string(),
+            AllowList::List(headers) => headers
+                .iter()
+                .map(HeaderName::as_str)
+                .collect::<Vec<_>>()
+                .join(", "),
+        };
+        write!(
+            f,
+            " [methods: {}; headers: {}; credentials: {}]",
+            methods, headers, self.allow_credentials
+        )
+    }
+}
+
+/// 許可するオリジンのリストを検証する
+fn parse_origins(origins: Vec<String>) -> Result<AllowedOrigins, ConfigError> {
+    let origins: Vec<String> = origins
+        .into_iter()
+        .map(|o| o.trim().trim_end_matches('/').to_string())
+        .filter(|o| !o.is_empty() && o != SAME_ORIGIN)
+        .collect();
+    if origins.is_empty() {
+        return Ok(AllowedOrigins::SameOrigin);
+    }
+    if origins.iter().any(|o| o == WILDCARD) {
+        return Ok(AllowedOrigins::Any);
+    }
+    if let Some(bad) = origins.iter().find(|o| {
+        !(o.starts_with("http://") || o.starts_with("https://"))
+            || HeaderValue::from_str(o).is_err()
+    }) {
+        return Err(ConfigError::Invalid("cors origin", bad.clone()));
+    }
+    Ok(AllowedOrigins::List(origins))
+}
+
+/// 許可するメソッドまたはヘッダーのリストを検証する
+fn parse_list<T>(
+    values: Vec<String>,
+    key: &'static str,
+    parse: impl Fn(&str) -> Option<T>,
+) -> Result<AllowList<T>, ConfigError> {
+    let values: Vec<String> = values
+        .into_iter()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect();
+    if values.iter().any(|v| v == WILDCARD) {
+        return Ok(AllowList::Any);
+    }
+    values
+        .iter()
+        .map(|v| parse(v).ok_or_else(|| ConfigError::Invalid(key, v.clone())))
+        .collect::<Result<_, _>>()
+        .map(AllowList::List)
+}
+
+fn credentials_error(field: &str) -> ConfigError {
+    ConfigError::Invalid(
+        "cors",
+        format!(
+            "`*` cannot be used for {} when credentials are allowed",
+            field
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Option<Vec<String>> {
+        Some(values.iter().map(|v| v.to_string()).collect())
+    }
+
+    #[test]
+    fn test_defaults() {
+        let policy =
+            CorsPolicy::parse(None, None, None, false, AllowedOrigins::SameOrigin).unwrap();
+        assert_eq!(policy.origins, AllowedOrigins::SameOrigin);
+        assert_eq!(policy.methods, AllowList::List(DEFAULT_METHODS.to_vec()));
+        assert!(
+            matches!(&policy.headers, AllowList::List(h) if h.contains(&header::AUTHORIZATION))
+        );
+    }
+
+    #[test]
+    fn test_parse_lists() {
+        let policy = CorsPolicy::parse(
+            strings(&["https://bingo.example/", "http://localhost:5173"]),
+            strings(&["get", "POST", "delete"]),
+            strings(&["Content-Type", "X-Admin-Token"]),
+            true,
+            AllowedOrigins::Any,
+        )
+        .unwrap();
+        assert_eq!(
+            policy.origins,
+            AllowedOrigins::List(vec![
+                "https://bingo.example".to_string(),
+                "http://localhost:5173".to_string()
+            ])
+        );
+        assert_eq!(
+            policy.methods,
+            AllowList::List(vec![Method::GET, Method::POST, Method::DELETE])
+        );
+        assert!(policy.allow_credentials);
+
+        let same = CorsPolicy::parse(strings(&[]), None, None, false, AllowedOrigins::Any).unwrap();
+        assert_eq!(same.origins, AllowedOrigins::SameOrigin);
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        let parse = |origins: &[&str], methods: &[&str], headers: &[&str], credentials| {
+            CorsPolicy::parse(
+                strings(origins),
+                strings(methods),
+                strings(headers),
+                credentials,
+                AllowedOrigins::SameOrigin,
+            )
+        };
+        assert!(parse(&["example.com"], &["GET"], &["content-type"], false).is_err());
+        assert!(parse(
+            &["http://a.example"],
+            &["NOT A METHOD"],
+            &["content-type"],
+            false
+        )
+        .is_err());
+        assert!(parse(&["http://a.example"], &["GET"], &["bad header"], false).is_err());
+        assert!(parse(&["*"], &["GET"], &["content-type"], true).is_err());
+        assert!(parse(&["http://a.example"], &["GET"], &["*"], true).is_err());
+        assert!(parse(&["*"], &["*"], &["*"], false).is_ok());
+    }
+}
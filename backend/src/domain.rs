@@ -1,14 +1,212 @@
+use crate::card::CardId;
+use crate::lifecycle::{GamePhase, GamePhaseError, Lifecycle, PhaseAction, PhaseTransition};
+use crate::player::PlayerId;
 use crate::rng::IRng;
-use std::{fmt, usize};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use utoipa::ToSchema;
+
+/// ハズレ枠の表示ラベル
+pub const BLANK_PRIZE_LABEL: &str = "ハズレ";
+
+/// あみだくじの枠の最大数 (枠番号は `u8` で表す)
+pub const MAX_AMIDA_SLOTS: usize = u8::MAX as usize;
+
+/// 制約付きシャッフルで試行する最大回数
+const MAX_CONSTRAINED_SHUFFLES: usize = 10_000;
+
+/// 制約を満たす割り当てを探索・数え上げる際に探索する最大ノード数
+const MAX_SEARCH_NODES: usize = 1_000_000;
+
+/// 参加者数と景品数が一致しない場合の割り当てルール
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AmidaRule {
+    /// 参加者数と景品数が一致する場合のみ結果を返す
+    #[default]
+    Strict,
+    /// 景品が足りない分はハズレ枠とし、余った景品は残りとして扱う
+    Leftover,
+    /// 景品が足りない分はハズレ枠とし、余った景品は参加者に順番に配る
+    MultiplePerGuest,
+}
+
+/// 特定の参加者に特定の景品を割り当てない制約
+///
+/// 例: 景品の提供者が自分の景品を受け取らないようにする
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AmidaExclusion {
+    /// 参加者ID
+    pub player: PlayerId,
+    /// 割り当てない景品番号
+    pub prize: u8,
+}
+
+/// グループ内で特定の景品が偏らないようにする制約
+///
+/// 例: 同じチームのメンバーが上位賞を独占しないようにする
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AmidaSpread {
+    /// グループに属する参加者のID
+    pub members: Vec<PlayerId>,
+    /// 対象の景品番号
+    pub prizes: Vec<u8>,
+    /// グループ全体で獲得できる対象景品の最大数
+    pub max: usize,
+}
+
+/// あみだくじの割り当て制約
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AmidaConstraints {
+    /// 除外ペアのリスト
+    pub exclusions: Vec<AmidaExclusion>,
+    /// グループ分散ルールのリスト
+    pub spreads: Vec<AmidaSpread>,
+}
+
+impl AmidaConstraints {
+    /// 制約が1つも設定されていないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.exclusions.is_empty() && self.spreads.is_empty()
+    }
+
+    /// 参加者に景品を割り当てられるかどうか (除外ペアのみを確認する)
+    fn is_excluded(&self, gest: PlayerId, prize: u8) -> bool {
+        self.exclusions
+            .iter()
+            .any(|e| e.player == gest && e.prize == prize)
+    }
+
+    /// 参加者が制約で参照されているかどうか
+    pub fn involves(&self, player: PlayerId) -> bool {
+        self.exclusions.iter().any(|e| e.player == player)
+            || self.spreads.iter().any(|s| s.members.contains(&player))
+    }
+
+    /// 参加者が景品を獲得したときに加算されるグループ分散ルールのインデックスを返す
+    fn spreads_hit(&self, gest: PlayerId, prize: u8) -> impl Iterator<Item = usize> + '_ {
+        self.spreads
+            .iter()
+            .enumerate()
+            .filter(move |(_, s)| s.prizes.contains(&prize) && s.members.contains(&gest))
+            .map(|(i, _)| i)
+    }
+
+    /// 割り当て全体が制約を満たすかどうか
+    ///
+    /// # Arguments
+    /// * `pairs` - (景品番号, 参加者ID) のリスト。ハズレ枠と空の枠は含めない
+    fn is_satisfied_by(&self, pairs: &[(u8, PlayerId)]) -> bool {
+        if pairs
+            .iter()
+            .any(|&(prize, gest)| self.is_excluded(gest, prize))
+        {
+            return false;
+        }
+
+        let mut counts = vec![0; self.spreads.len()];
+        for &(prize, gest) in pairs {
+            for i in self.spreads_hit(gest, prize) {
+                counts[i] += 1;
+            }
+        }
+        counts.iter().zip(&self.spreads).all(|(&c, s)| c <= s.max)
+    }
+}
+
+/// 制約付きの割り当てに失敗した理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AmidaConstraintError {
+    /// どのような割り当てでも制約を満たせない
+    Unsatisfiable,
+    /// 制約が複雑すぎて、割り当ての探索が上限に達した
+    TooConstrained,
+}
+
+impl fmt::Display for AmidaConstraintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsatisfiable => write!(f, "constraints cannot be satisfied"),
+            Self::TooConstrained => write!(f, "constraints are too tight to draw fairly"),
+        }
+    }
+}
+
+/// 欠席者の景品を再抽選した記録
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AmidaRedraw {
+    /// 再抽選した景品番号
+    pub prize: u8,
+    /// 欠席した元の当選者のID
+    pub absentee: PlayerId,
+    /// 新しい当選者のID
+    pub winner: PlayerId,
+    /// 再抽選に使用したサブストリーム番号
+    pub stream: u32,
+}
+
+/// 欠席者の再抽選に失敗した理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AmidaRedrawError {
+    /// 結果が確定していない
+    NotReady,
+    /// 参加者リストに存在しない
+    UnknownGuest,
+    /// すでに欠席として記録されている
+    AlreadyAbsent,
+    /// 欠席者の結果がまだ公開されていない
+    NotRevealed,
+    /// 景品を受け取れる出席者がいない
+    NoEligibleGuest,
+}
+
+impl fmt::Display for AmidaRedrawError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotReady => write!(f, "amida result is not ready"),
+            Self::UnknownGuest => write!(f, "guest is not in the list"),
+            Self::AlreadyAbsent => write!(f, "guest is already marked absent"),
+            Self::NotRevealed => write!(f, "guest's result has not been revealed yet"),
+            Self::NoEligibleGuest => write!(f, "no eligible guest is present"),
+        }
+    }
+}
+
+/// あみだくじの抽選結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AmidaResult {
+    /// (景品番号, 参加者ID) のペアリスト。ハズレ枠の景品番号は `BLANK_PRIZE_LABEL`、空の枠の参加者はNone
+    pub pairs: Vec<(String, Option<PlayerId>)>,
+    /// 誰にも割り当てられなかった景品番号
+    pub leftover: Vec<u8>,
+    /// 適用された割り当てルール
+    pub rule: AmidaRule,
+}
 
 /// あみだくじゲームのドメインロジック
 pub struct AmidaGame {
-    /// 参加者数
+    /// 景品数
     pub count: usize,
-    /// 参加者名リスト
-    pub gests: Vec<String>,
-    /// 景品番号リスト (内部でシャッフルされる)
+    /// 参加者IDリスト (空の枠はNone)
+    ///
+    /// 名簿の参加者IDで持つため、表示名を変更しても同じ参加者を指したままになる
+    pub gests: Vec<Option<PlayerId>>,
+    /// 枠番号リスト (内部でシャッフルされる)
+    ///
+    /// 景品数を超える番号はハズレ枠を表す
     pub prizes: Vec<u8>,
+    /// 割り当てルール
+    pub rule: AmidaRule,
+    /// 割り当て制約
+    pub constraints: AmidaConstraints,
+    /// 公開済みの枠番号 (公開順)
+    pub revealed: Vec<u8>,
+    /// 欠席者の参加者ID (記録順)
+    pub absentees: Vec<PlayerId>,
+    /// 欠席者の景品の再抽選記録 (実行順)
+    pub redraws: Vec<AmidaRedraw>,
     /// 乱数生成器
     rng: Box<dyn IRng>,
 }
@@ -17,6 +215,11 @@ impl fmt::Debug for AmidaGame {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("AmidaGame")
             .field("items", &self.gests)
+            .field("rule", &self.rule)
+            .field("constraints", &self.constraints)
+            .field("revealed", &self.revealed)
+            .field("absentees", &self.absentees)
+            .field("redraws", &self.redraws)
             .finish()
     }
 }
@@ -25,60 +228,685 @@ impl AmidaGame {
     /// 新しいあみだくじゲームを作成する
     ///
     /// # Arguments
-    /// * `count` - 参加者数
+    /// * `count` - 景品数
     /// * `rng` - 乱数生成器
     pub fn new(count: usize, rng: Box<dyn IRng>) -> Self {
         let mut game = Self {
             count,
             gests: Vec::new(),
-            prizes: (1..=count as u8).collect(),
+            prizes: Vec::new(),
+            rule: AmidaRule::default(),
+            constraints: AmidaConstraints::default(),
+            revealed: Vec::new(),
+            absentees: Vec::new(),
+            redraws: Vec::new(),
             rng,
         };
         game.shuffle();
         game
     }
 
-    /// 景品番号をシャッフルする
+    /// 使用している乱数生成アルゴリズムの名前
+    pub fn algorithm(&self) -> &'static str {
+        self.rng.name()
+    }
+
+    /// 枠の数 (参加者数と景品数の大きい方)
+    fn slot_count(&self) -> usize {
+        self.count.max(self.gests.len())
+    }
+
+    /// 枠番号をシャッフルする
+    ///
+    /// 乱数生成器を初期状態に戻してからシャッフルするため、
+    /// 同じシード・同じ枠数であれば常に同じ並びになる
     fn shuffle(&mut self) {
+        self.rng.reset();
+        self.prizes = (1..=self.slot_count() as u8).collect();
         self.rng.shuffle(&mut self.prizes);
     }
 
+    /// 制約を満たすように枠番号をシャッフルする
+    ///
+    /// 制約を満たす並びが出るまでシャッフルをやり直す (棄却サンプリング) ため、
+    /// 制約を満たす割り当ての中から一様に選ばれる。
+    /// 規定回数で見つからない場合は全探索で充足可能性を判定し、割り当てが存在すれば
+    /// 割り当ての数を数え上げて一様に選ぶ。探索が上限に達した場合は結果を確定させない。
+    fn arrange(&mut self) -> Result<(), AmidaConstraintError> {
+        self.shuffle();
+        if self.constraints.is_empty() || !self.is_ready() {
+            return Ok(());
+        }
+
+        for attempt in 0..MAX_CONSTRAINED_SHUFFLES {
+            if attempt > 0 {
+                self.prizes = (1..=self.slot_count() as u8).collect();
+                self.rng.shuffle(&mut self.prizes);
+            }
+            if self.satisfies_constraints(&self.prizes) {
+                return Ok(());
+            }
+        }
+
+        let error = match self.search_constrained() {
+            Some(false) => AmidaConstraintError::Unsatisfiable,
+            Some(true) => match self.sample_constrained() {
+                Some(prizes) => {
+                    self.prizes = prizes;
+                    return Ok(());
+                }
+                None => AmidaConstraintError::TooConstrained,
+            },
+            None => AmidaConstraintError::TooConstrained,
+        };
+        self.prizes.clear();
+        Err(error)
+    }
+
+    /// 枠番号の並びが制約を満たすかどうか
+    fn satisfies_constraints(&self, prizes: &[u8]) -> bool {
+        let pairs: Vec<(u8, PlayerId)> = self
+            .assign(prizes)
+            .into_iter()
+            .filter(|&(prize, _)| (prize as usize) <= self.count)
+            .filter_map(|(prize, gest)| Some((prize, self.gests[gest?]?)))
+            .collect();
+        self.constraints.is_satisfied_by(&pairs)
+    }
+
+    /// 制約を満たす割り当てが存在するかを深さ優先探索で判定する
+    ///
+    /// 参加者のいる枠にだけ景品またはハズレを割り当てていく。
+    /// ハズレ枠同士は区別しないため、ハズレの数だけを数えて探索する。
+    ///
+    /// # Returns
+    /// * `Option<bool>` - 存在する場合は true、存在しない場合は false。
+    ///   探索が上限に達した場合は None
+    fn search_constrained(&self) -> Option<bool> {
+        let blanks = self.slot_count() - self.count;
+        ConstraintSearch::new(
+            &self.gests,
+            &self.constraints,
+            self.count,
+            self.owners(),
+            blanks,
+        )
+        .run(0)
+    }
+
+    /// 制約を満たす枠番号の並びを、制約を満たす割り当ての中から一様に選ぶ
+    ///
+    /// 参加者のいる枠の割り当てを数え上げて選び、ハズレ枠の番号と参加者のいない枠の番号はシャッフルして並べる。
+    /// 参加者のいない枠があるのはハズレ枠がない場合だけなので、どの割り当ても同じ数の並びに対応し、
+    /// 並び全体でも一様になる。
+    ///
+    /// # Returns
+    /// * `Option<Vec<u8>>` - 枠番号の並び。探索が上限に達した場合は None
+    fn sample_constrained(&mut self) -> Option<Vec<u8>> {
+        let slot_count = self.slot_count();
+        let picks = ConstraintSearch::new(
+            &self.gests,
+            &self.constraints,
+            self.count,
+            self.owners(),
+            slot_count - self.count,
+        )
+        .sample(self.rng.as_mut())?;
+
+        let mut blanks: Vec<u8> = (self.count + 1..=slot_count).map(|p| p as u8).collect();
+        self.rng.shuffle(&mut blanks);
+        let mut rest: Vec<u8> = (1..=self.count as u8)
+            .filter(|prize| !picks.contains(&Some(*prize)))
+            .collect();
+        self.rng.shuffle(&mut rest);
+
+        let mut blanks = blanks.into_iter();
+        let mut prizes: Vec<u8> = picks
+            .into_iter()
+            .map(|pick| pick.or_else(|| blanks.next()))
+            .collect::<Option<_>>()?;
+        prizes.extend(rest);
+        Some(prizes)
+    }
+
+    /// 参加者のいる枠ごとの参加者インデックス (枠番号の並びの先頭から順)
+    fn owners(&self) -> Vec<usize> {
+        self.assign(&vec![0; self.slot_count()])
+            .into_iter()
+            .filter_map(|(_, gest)| gest)
+            .collect()
+    }
+
     /// 参加者リストを更新する
     ///
+    /// 参加者が変わると結果も変わるため、公開状態と欠席者の記録はリセットされる。
+    /// 割り当て制約を満たせない場合はエラーを返し、結果は確定しない。
+    ///
     /// # Arguments
-    /// * `gests` - 新しい参加者リスト
-    pub fn update(&mut self, gests: Vec<String>) {
+    /// * `gests` - 新しい参加者IDリスト (空の枠はNone)
+    pub fn update(&mut self, gests: Vec<Option<PlayerId>>) -> Result<(), AmidaConstraintError> {
         self.gests = gests;
+        self.revealed.clear();
+        self.absentees.clear();
+        self.redraws.clear();
+        self.arrange()
     }
 
-    /// 結果を取得する
+    /// 割り当て制約を変更する
+    ///
+    /// 制約は次の `update` 呼び出し時に適用される
+    ///
+    /// # Arguments
+    /// * `constraints` - 新しい割り当て制約
+    pub fn set_constraints(&mut self, constraints: AmidaConstraints) {
+        self.constraints = constraints;
+    }
+
+    /// 割り当てルールを変更する
+    ///
+    /// ルールは次の `update` 呼び出し時に適用される
     ///
-    /// 参加者数が設定値と一致する場合のみ結果を返す
+    /// # Arguments
+    /// * `rule` - 新しい割り当てルール
+    pub fn set_rule(&mut self, rule: AmidaRule) {
+        if self.rule != rule {
+            self.revealed.clear();
+        }
+        self.rule = rule;
+    }
+
+    /// 枠番号の表示ラベルを返す
+    fn prize_label(&self, prize: u8) -> String {
+        if (prize as usize) <= self.count {
+            prize.to_string()
+        } else {
+            BLANK_PRIZE_LABEL.to_string()
+        }
+    }
+
+    /// 現在の参加者数とルールで結果を確定できるかどうか
+    pub fn is_ready(&self) -> bool {
+        let gest_count = self.gests.len();
+        match self.rule {
+            AmidaRule::Strict => gest_count == self.count && gest_count > 0,
+            _ => gest_count > 0,
+        }
+    }
+
+    /// 枠番号ごとの割り当てを計算する
     ///
     /// # Returns
-    /// * `Option<Vec<(String, String)>>` - (参加者名, 景品番号) のペアリスト
-    pub fn get_result(&self) -> Option<Vec<(String, String)>> {
-        if self.gests.len() != self.count {
+    /// * `Option<Vec<(u8, Option<usize>)>>` - (枠番号, 参加者のインデックス) のリスト。
+    ///   誰にも割り当てられない景品の参加者は None
+    fn assignments(&self) -> Option<Vec<(u8, Option<usize>)>> {
+        if !self.is_ready() || self.prizes.len() != self.slot_count() {
             return None;
         }
+        Some(self.assign(&self.prizes))
+    }
 
-        let mut result = Vec::new();
-        for (gest, prize) in self.gests.iter().zip(self.prizes.iter()) {
-            result.push((prize.to_string(), gest.clone()));
+    /// 枠番号の並びに参加者を割り当てる
+    fn assign(&self, prizes: &[u8]) -> Vec<(u8, Option<usize>)> {
+        let gest_count = self.gests.len();
+        prizes
+            .iter()
+            .enumerate()
+            .map(|(slot, &prize)| {
+                let gest = if slot < gest_count {
+                    Some(slot)
+                } else if self.rule == AmidaRule::MultiplePerGuest {
+                    Some(slot % gest_count)
+                } else {
+                    None
+                };
+                (prize, gest)
+            })
+            .collect()
+    }
+
+    /// 割り当てのリストから結果を組み立てる
+    fn build_result(&self, assignments: &[(u8, Option<usize>)]) -> AmidaResult {
+        let mut pairs = Vec::new();
+        let mut leftover = Vec::new();
+        for &(prize, gest) in assignments {
+            match gest {
+                Some(index) => pairs.push((self.prize_label(prize), self.gests[index])),
+                None => leftover.push(prize),
+            }
         }
 
+        AmidaResult {
+            pairs,
+            leftover,
+            rule: self.rule,
+        }
+    }
+
+    /// 結果を取得する
+    ///
+    /// `Strict` ルールでは参加者数が景品数と一致する場合のみ結果を返す。
+    /// それ以外のルールでは参加者が1人以上いれば結果を返す。
+    ///
+    /// # Returns
+    /// * `Option<AmidaResult>` - 抽選結果
+    pub fn get_result(&self) -> Option<AmidaResult> {
+        let assignments = self.assignments()?;
+        Some(self.build_result(&assignments))
+    }
+
+    /// 公開済みの結果のみを取得する
+    ///
+    /// ペアは公開順に並ぶ。余った景品は、割り当てのある枠が全て公開されるまで返さない。
+    ///
+    /// # Returns
+    /// * `Option<AmidaResult>` - 公開済みの抽選結果。結果が確定していない場合はNone
+    pub fn get_revealed_result(&self) -> Option<AmidaResult> {
+        let assignments = self.assignments()?;
+        let revealed: Vec<(u8, Option<usize>)> = self
+            .revealed
+            .iter()
+            .filter_map(|prize| assignments.iter().find(|(p, _)| p == prize).copied())
+            .collect();
+
+        let mut result = self.build_result(&revealed);
+        if self.is_fully_revealed() {
+            result.leftover = self.build_result(&assignments).leftover;
+        }
         Some(result)
     }
+
+    /// 割り当てのある枠の数を返す
+    pub fn assigned_count(&self) -> usize {
+        self.assignments()
+            .map(|assignments| {
+                assignments
+                    .iter()
+                    .filter(|(_, gest)| gest.is_some())
+                    .count()
+            })
+            .unwrap_or(0)
+    }
+
+    /// 割り当てのある枠が全て公開済みかどうか
+    pub fn is_fully_revealed(&self) -> bool {
+        self.revealed.len() >= self.assigned_count()
+    }
+
+    /// 指定した枠の結果を公開する
+    ///
+    /// 公開済みの枠を再度指定した場合は、同じ結果をそのまま返す。
+    ///
+    /// # Arguments
+    /// * `prize` - 公開する枠番号
+    ///
+    /// # Returns
+    /// * `Option<(String, Option<PlayerId>)>` - (景品番号, 参加者ID) のペア。
+    ///   結果が確定していない場合や、参加者の割り当てがない枠の場合はNone
+    pub fn reveal(&mut self, prize: u8) -> Option<(String, Option<PlayerId>)> {
+        let assignments = self.assignments()?;
+        let (_, gest) = assignments.iter().find(|(p, _)| *p == prize)?;
+        let gest = (*gest)?;
+
+        if !self.revealed.contains(&prize) {
+            self.revealed.push(prize);
+        }
+        Some((self.prize_label(prize), self.gests[gest]))
+    }
+
+    /// 再抽選を反映した、現在の景品の保持者を返す
+    ///
+    /// # Returns
+    /// * `Option<Vec<(u8, Option<PlayerId>)>>` - (景品番号, 参加者ID) のリスト。
+    ///   ハズレ枠は含まず、空の枠の参加者はNone
+    pub fn current_holders(&self) -> Option<Vec<(u8, Option<PlayerId>)>> {
+        let mut holders: Vec<(u8, Option<PlayerId>)> = self
+            .assignments()?
+            .into_iter()
+            .filter(|&(prize, _)| (prize as usize) <= self.count)
+            .filter_map(|(prize, gest)| Some((prize, self.gests[gest?])))
+            .collect();
+        for redraw in &self.redraws {
+            if let Some(holder) = holders.iter_mut().find(|(p, _)| *p == redraw.prize) {
+                holder.1 = Some(redraw.winner);
+            }
+        }
+        Some(holders)
+    }
+
+    /// 参加者を欠席として記録し、その参加者の景品を出席者の中から再抽選する
+    ///
+    /// 再抽選は再抽選ごとに番号を振ったサブストリームの乱数で行うため、
+    /// 同じ順序で欠席を記録すれば常に同じ結果になる。
+    /// 割り当て制約を満たし、まだ景品を持っていない出席者を優先して選ぶ。
+    /// (全員が景品を持っている場合は、制約を満たす出席者全員が対象)
+    ///
+    /// # Arguments
+    /// * `gest` - 欠席した参加者のID
+    ///
+    /// # Returns
+    /// * `Result<Vec<AmidaRedraw>, AmidaRedrawError>` - 今回行った再抽選の記録
+    pub fn mark_absent(&mut self, gest: PlayerId) -> Result<Vec<AmidaRedraw>, AmidaRedrawError> {
+        let mut holders = self.current_holders().ok_or(AmidaRedrawError::NotReady)?;
+        if !self.gests.contains(&Some(gest)) {
+            return Err(AmidaRedrawError::UnknownGuest);
+        }
+        if self.absentees.contains(&gest) {
+            return Err(AmidaRedrawError::AlreadyAbsent);
+        }
+
+        let held: Vec<u8> = holders
+            .iter()
+            .filter(|&&(_, holder)| holder == Some(gest))
+            .map(|&(prize, _)| prize)
+            .collect();
+        if held.iter().any(|prize| !self.revealed.contains(prize)) {
+            return Err(AmidaRedrawError::NotRevealed);
+        }
+
+        let mut present: Vec<PlayerId> = Vec::new();
+        for &g in self.gests.iter().flatten() {
+            if g != gest && !self.absentees.contains(&g) && !present.contains(&g) {
+                present.push(g);
+            }
+        }
+
+        let mut redraws = Vec::new();
+        for prize in held {
+            let allowed: Vec<PlayerId> = present
+                .iter()
+                .copied()
+                .filter(|&candidate| self.can_receive(&holders, candidate, prize))
+                .collect();
+            let without_prize: Vec<PlayerId> = allowed
+                .iter()
+                .copied()
+                .filter(|&candidate| !holders.iter().any(|&(_, h)| h == Some(candidate)))
+                .collect();
+            let candidates = if without_prize.is_empty() {
+                allowed
+            } else {
+                without_prize
+            };
+            if candidates.is_empty() {
+                return Err(AmidaRedrawError::NoEligibleGuest);
+            }
+
+            let stream = (self.redraws.len() + redraws.len() + 1) as u32;
+            let mut rng = self.rng.fork(stream);
+            let winner = candidates[uniform_below(rng.as_mut(), candidates.len() as u64) as usize];
+
+            if let Some(holder) = holders.iter_mut().find(|(p, _)| *p == prize) {
+                holder.1 = Some(winner);
+            }
+            redraws.push(AmidaRedraw {
+                prize,
+                absentee: gest,
+                winner,
+                stream,
+            });
+        }
+
+        self.absentees.push(gest);
+        self.redraws.extend(redraws.iter().cloned());
+        Ok(redraws)
+    }
+
+    /// 参加者が枠または割り当て制約で参照されているかどうか
+    ///
+    /// 欠席と再抽選の記録は枠の参加者だけを参照するため、枠を確認すれば足りる。
+    pub fn involves(&self, player: PlayerId) -> bool {
+        self.gests.contains(&Some(player)) || self.constraints.involves(player)
+    }
+
+    /// 現在の保持状況で、参加者が景品を受け取っても制約を満たすかどうか
+    fn can_receive(&self, holders: &[(u8, Option<PlayerId>)], gest: PlayerId, prize: u8) -> bool {
+        let mut pairs: Vec<(u8, PlayerId)> = holders
+            .iter()
+            .filter(|&&(p, _)| p != prize)
+            .filter_map(|&(p, h)| Some((p, h?)))
+            .collect();
+        pairs.push((prize, gest));
+        self.constraints.is_satisfied_by(&pairs)
+    }
+}
+
+/// 割り当て制約を満たす割り当てを探索する状態
+///
+/// 参加者のいる枠 (枠番号の並びの先頭から順) に、景品番号またはハズレを割り当てていく。
+struct ConstraintSearch<'a> {
+    /// 参加者IDリスト (空の枠はNone)
+    gests: &'a [Option<PlayerId>],
+    /// 割り当て制約
+    constraints: &'a AmidaConstraints,
+    /// 参加者のいる枠ごとの参加者インデックス
+    owners: Vec<usize>,
+    /// 景品番号ごとの使用済みフラグ
+    used: Vec<bool>,
+    /// 残りのハズレ枠の数
+    blanks: usize,
+    /// グループ分散ルールごとの獲得数
+    counts: Vec<usize>,
+    /// 残りの探索ノード数
+    budget: usize,
+}
+
+impl<'a> ConstraintSearch<'a> {
+    /// あみだくじの設定から探索の初期状態を作る
+    ///
+    /// 乱数生成器を借用したまま使えるように、必要な項目だけを受け取る。
+    fn new(
+        gests: &'a [Option<PlayerId>],
+        constraints: &'a AmidaConstraints,
+        count: usize,
+        owners: Vec<usize>,
+        blanks: usize,
+    ) -> Self {
+        Self {
+            gests,
+            constraints,
+            owners,
+            used: vec![false; count + 1],
+            blanks,
+            counts: vec![0; constraints.spreads.len()],
+            budget: MAX_SEARCH_NODES,
+        }
+    }
+
+    /// 枠に割り当てられる選択肢 (ハズレはNone) を、ハズレ・景品番号の順に返す
+    fn choices(&self, index: usize) -> Vec<Option<u8>> {
+        let gest = self.gests[self.owners[index]];
+        let mut choices = Vec::new();
+        if self.blanks > 0 {
+            choices.push(None);
+        }
+        for prize in (1..self.used.len()).map(|prize| prize as u8) {
+            let allowed = !self.used[prize as usize]
+                && gest.is_none_or(|gest| {
+                    !self.constraints.is_excluded(gest, prize)
+                        && self
+                            .constraints
+                            .spreads_hit(gest, prize)
+                            .all(|i| self.counts[i] < self.constraints.spreads[i].max)
+                });
+            if allowed {
+                choices.push(Some(prize));
+            }
+        }
+        choices
+    }
+
+    /// 枠に選択肢を割り当てる (`undo` が true の場合は割り当てを取り消す)
+    fn apply(&mut self, index: usize, choice: Option<u8>, undo: bool) {
+        let Some(prize) = choice else {
+            match undo {
+                true => self.blanks += 1,
+                false => self.blanks -= 1,
+            }
+            return;
+        };
+        self.used[prize as usize] = !undo;
+        let Some(gest) = self.gests[self.owners[index]] else {
+            return;
+        };
+        for i in self.constraints.spreads_hit(gest, prize) {
+            match undo {
+                true => self.counts[i] -= 1,
+                false => self.counts[i] += 1,
+            }
+        }
+    }
+
+    /// 探索ノードを1つ消費する (上限に達した場合はNone)
+    fn spend(&mut self) -> Option<()> {
+        self.budget = self.budget.checked_sub(1)?;
+        Some(())
+    }
+
+    /// 枠 `index` 以降に制約を満たす割り当てが存在するかどうか
+    fn run(&mut self, index: usize) -> Option<bool> {
+        if index == self.owners.len() {
+            return Some(true);
+        }
+        for choice in self.choices(index) {
+            self.spend()?;
+            self.apply(index, choice, false);
+            let found = self.run(index + 1);
+            self.apply(index, choice, true);
+            if found != Some(false) {
+                return found;
+            }
+        }
+        Some(false)
+    }
+
+    /// 枠 `index` 以降の、制約を満たす割り当ての数を数える
+    fn count(&mut self, index: usize) -> Option<u64> {
+        if index == self.owners.len() {
+            return Some(1);
+        }
+        let mut total = 0;
+        for choice in self.choices(index) {
+            self.spend()?;
+            self.apply(index, choice, false);
+            let count = self.count(index + 1);
+            self.apply(index, choice, true);
+            total += count?;
+        }
+        Some(total)
+    }
+
+    /// 制約を満たす割り当ての中から1つを一様に選ぶ
+    ///
+    /// 先頭の枠から順に、選択肢ごとに残りの枠の割り当ての数を数え、その数に比例した確率で選択肢を選ぶ。
+    ///
+    /// # Returns
+    /// * `Option<Vec<Option<u8>>>` - 参加者のいる枠ごとの景品番号 (ハズレはNone)。
+    ///   探索が上限に達した場合や、割り当てが存在しない場合はNone
+    fn sample(&mut self, rng: &mut dyn IRng) -> Option<Vec<Option<u8>>> {
+        let mut picks = Vec::with_capacity(self.owners.len());
+        for index in 0..self.owners.len() {
+            let mut weights = Vec::new();
+            for choice in self.choices(index) {
+                self.apply(index, choice, false);
+                let count = self.count(index + 1);
+                self.apply(index, choice, true);
+                weights.push((choice, count?));
+            }
+
+            let total = weights.iter().map(|&(_, weight)| weight).sum();
+            if total == 0 {
+                return None;
+            }
+            let mut target = uniform_below(rng, total);
+            let &(choice, _) = weights.iter().find(|&&(_, weight)| {
+                let hit = target < weight;
+                target = target.saturating_sub(weight);
+                hit
+            })?;
+            self.apply(index, choice, false);
+            picks.push(choice);
+        }
+        Some(picks)
+    }
+}
+
+/// 0以上 `bound` 未満の一様な乱数を求める
+///
+/// 32ビットの乱数2つから64ビットの乱数を作り、剰余の偏りが出る範囲は引き直す。
+fn uniform_below(rng: &mut dyn IRng, bound: u64) -> u64 {
+    // 2^64 を `bound` で割った余り (この数だけ上側の値を捨てる)
+    let rejected = (u64::MAX % bound + 1) % bound;
+    loop {
+        let value = (u64::from(rng.next()) << 32) | u64::from(rng.next());
+        if rejected == 0 || value < rejected.wrapping_neg() {
+            return value % bound;
+        }
+    }
+}
+
+/// ビンゴの種類
+///
+/// 数字の最大値から決まり、カードの形式に対応します。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BingoVariant {
+    /// 75ボール (5x5のカード、B-I-N-G-Oの列)
+    Ball75,
+    /// 90ボール (3x9のカード)
+    Ball90,
+    /// その他の最大値 (カードの形式なし)
+    Custom,
+}
+
+impl BingoVariant {
+    /// 数字の最大値からビンゴの種類を求める
+    pub fn from_max_number(max_number: usize) -> Self {
+        match max_number {
+            75 => Self::Ball75,
+            90 => Self::Ball90,
+            _ => Self::Custom,
+        }
+    }
+}
+
+/// ビンゴの申告 (「ビンゴ!」の声が上がった記録)
+///
+/// 申告の内容 (カードが本当に揃っているか) は司会者が確認します。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct BingoClaim {
+    /// 申告した参加者のID
+    pub player: PlayerId,
+    /// 申告に使ったビンゴカード (司会者が記録した申告はNone)
+    pub card: Option<CardId>,
+    /// 申告した時点で抽選済みの数字の個数
+    pub draw_count: usize,
+    /// 申告した時点で最後に抽選された数字
+    pub last: Option<u8>,
+    /// 当たりの形が揃った数字の抽選順 (カードを確認していない申告は申告した時点の抽選済みの個数)
+    pub draw_index: usize,
+    /// 当たりの形が揃った数字
+    pub number: Option<u8>,
+    /// 申告した日時
+    #[schema(value_type = String, format = DateTime)]
+    pub at: DateTime<Local>,
 }
 
 /// ビンゴゲームのドメインロジック
 pub struct BingoGame {
     /// 数字の最大値
     count: usize,
+    /// 状態を変更するたびに増える版数
+    version: u64,
+    /// フェーズと遷移の記録
+    lifecycle: Lifecycle,
     /// 残りの数字リスト
     pub remaining_numbers: Vec<u8>,
     /// 抽選履歴
     pub history: Vec<u8>,
+    /// 抽選日時 (`history` と同じ順序)
+    pub drawn_at: Vec<DateTime<Local>>,
+    /// ビンゴの申告 (古い順)
+    pub claims: Vec<BingoClaim>,
     /// 乱数生成器
     rng: Box<dyn IRng>,
 }
@@ -101,14 +929,50 @@ impl BingoGame {
     pub fn new(count: usize, rng: Box<dyn IRng>) -> Self {
         let mut game = Self {
             count,
+            version: 0,
+            lifecycle: Lifecycle::default(),
             remaining_numbers: (1..=count as u8).collect(),
             history: Vec::new(),
+            drawn_at: Vec::new(),
+            claims: Vec::new(),
             rng,
         };
         game.shuffle();
         game
     }
 
+    /// 使用している乱数生成アルゴリズムの名前
+    pub fn algorithm(&self) -> &'static str {
+        self.rng.name()
+    }
+
+    /// 数字の最大値
+    pub fn max_number(&self) -> usize {
+        self.count
+    }
+
+    /// ビンゴの種類
+    pub fn variant(&self) -> BingoVariant {
+        BingoVariant::from_max_number(self.count)
+    }
+
+    /// 現在のフェーズ
+    pub fn phase(&self) -> GamePhase {
+        self.lifecycle.phase()
+    }
+
+    /// これまでのフェーズの遷移の記録
+    pub fn transitions(&self) -> &[PhaseTransition] {
+        self.lifecycle.transitions()
+    }
+
+    /// 状態の版数
+    ///
+    /// 抽選・リセット・フェーズの遷移で状態が変わるたびに増えます。表示側が変化を検知するために使います。
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
     /// 数字をシャッフルする
     fn shuffle(&mut self) {
         self.rng.shuffle(&mut self.remaining_numbers);
@@ -116,23 +980,103 @@ impl BingoGame {
 
     /// 次の数字を抽選する
     ///
+    /// 準備中・確定済みのフェーズでは、最初の抽選の前に自動的に確定 (`lock`)・開始 (`start`) し、
+    /// 最後の数字を抽選すると自動的に終了 (`finish`) する。それぞれの遷移は記録される。
+    ///
     /// # Returns
-    /// * `Option<u8>` - 抽選された数字。全て出尽くした場合はNone
-    pub fn get_next_number(&mut self) -> Option<u8> {
-        if let Some(num) = self.remaining_numbers.pop() {
-            self.history.push(num);
-            Some(num)
-        } else {
-            None
+    /// * `Result<Option<u8>, GamePhaseError>` - 抽選された数字。全て出尽くしている場合はNone。
+    ///   一時停止中・保管済み、または途中で終了したゲームではエラー
+    pub fn draw(&mut self) -> Result<Option<u8>, GamePhaseError> {
+        let phase = self.phase();
+        if phase == GamePhase::Finished && self.remaining_numbers.is_empty() {
+            return Ok(None);
+        }
+        if !phase.can_draw() {
+            return Err(GamePhaseError::CannotDraw(phase));
+        }
+        if phase == GamePhase::Setup {
+            self.transition(PhaseAction::Lock)?;
+        }
+        if self.phase() == GamePhase::Locked {
+            self.transition(PhaseAction::Start)?;
         }
+
+        let Some(num) = self.remaining_numbers.pop() else {
+            return Ok(None);
+        };
+        self.history.push(num);
+        self.drawn_at.push(Local::now());
+        self.version += 1;
+        if self.remaining_numbers.is_empty() {
+            self.transition(PhaseAction::Finish)?;
+        }
+        Ok(Some(num))
+    }
+
+    /// ビンゴの申告を記録する
+    ///
+    /// # Arguments
+    /// * `player` - 申告した参加者のID
+    /// * `card` - 申告に使ったビンゴカード
+    /// * `draw_index` - カードで確認した、当たりの形が揃った数字の抽選順 (Noneの場合は申告した時点)
+    ///
+    /// # Returns
+    /// * `BingoClaim` - 記録した申告
+    pub fn claim(
+        &mut self,
+        player: PlayerId,
+        card: Option<CardId>,
+        draw_index: Option<usize>,
+    ) -> BingoClaim {
+        let draw_index = draw_index.unwrap_or(self.history.len());
+        let claim = BingoClaim {
+            player,
+            card,
+            draw_count: self.history.len(),
+            last: self.history.last().copied(),
+            draw_index,
+            number: draw_index
+                .checked_sub(1)
+                .and_then(|i| self.history.get(i))
+                .copied(),
+            at: Local::now(),
+        };
+        self.claims.push(claim.clone());
+        self.version += 1;
+        claim
+    }
+
+    /// フェーズを遷移する
+    ///
+    /// `reset` の場合は抽選履歴も消去する。
+    ///
+    /// # Arguments
+    /// * `action` - 行う操作
+    ///
+    /// # Returns
+    /// * `Result<GamePhase, GamePhaseError>` - 遷移後のフェーズ (現在のフェーズで許可されない操作の場合はエラー)
+    pub fn transition(&mut self, action: PhaseAction) -> Result<GamePhase, GamePhaseError> {
+        let phase = self.lifecycle.apply(action)?;
+        if action == PhaseAction::Reset {
+            self.clear();
+        }
+        self.version += 1;
+        Ok(phase)
     }
 
     /// ゲームをリセットする
     ///
+    /// 数字を全て戻して再度シャッフルし、準備中のフェーズに戻す。保管済みのゲームはリセットできない。
+    pub fn reset(&mut self) -> Result<(), GamePhaseError> {
+        self.transition(PhaseAction::Reset).map(|_| ())
+    }
+
     /// 数字を全て戻し、再度シャッフルする
-    pub fn reset(&mut self) {
+    fn clear(&mut self) {
         self.remaining_numbers = (1..=self.count as u8).collect();
         self.history.clear();
+        self.drawn_at.clear();
+        self.claims.clear();
         self.rng.reset();
         self.shuffle();
     }
@@ -165,7 +1109,7 @@ mod tests {
         let initial_len = game.remaining_numbers.len();
 
         // 1回引く
-        let num = game.get_next_number();
+        let num = game.draw().unwrap();
         assert!(num.is_some());
         assert_eq!(game.remaining_numbers.len(), initial_len - 1);
         assert_eq!(game.history.len(), 1);
@@ -184,29 +1128,455 @@ mod tests {
 
         // 75回引く
         for _ in 0..75 {
-            assert!(game.get_next_number().is_some());
+            assert!(game.draw().unwrap().is_some());
         }
 
         assert_eq!(game.remaining_numbers.len(), 0);
         assert_eq!(game.history.len(), 75);
 
         // 76回目はNoneになるはず
-        assert!(game.get_next_number().is_none());
+        assert!(game.draw().unwrap().is_none());
     }
 
     #[test]
     fn test_reset() {
         let rng = Box::new(XorShift::new(123));
         let mut game = BingoGame::new(75, rng);
-        game.get_next_number();
-        game.get_next_number();
+        game.draw().unwrap();
+        game.draw().unwrap();
+        game.claim(1, None, None);
 
         assert_ne!(game.remaining_numbers.len(), 75);
         assert_ne!(game.history.len(), 0);
 
-        game.reset();
+        game.reset().unwrap();
 
         assert_eq!(game.remaining_numbers.len(), 75);
         assert_eq!(game.history.len(), 0);
+        assert!(game.claims.is_empty());
+    }
+
+    #[test]
+    fn test_claim() {
+        let mut game = BingoGame::new(75, Box::new(XorShift::new(123)));
+        game.draw().unwrap();
+        game.draw().unwrap();
+
+        // 揃った抽選を指定しない場合は、申告した時点の最後の抽選で揃ったものとする
+        let claim = game.claim(1, None, None);
+        assert_eq!(claim.draw_count, 2);
+        assert_eq!(claim.draw_index, 2);
+        assert_eq!(claim.number, claim.last);
+
+        let claim = game.claim(1, Some(1), Some(1));
+        assert_eq!(claim.draw_index, 1);
+        assert_eq!(claim.number, game.history.first().copied());
+        assert_eq!(claim.last, game.history.last().copied());
+        assert_eq!(game.claims.len(), 2);
+    }
+
+    #[test]
+    fn test_phase_and_version() {
+        let mut game = BingoGame::new(2, Box::new(XorShift::new(123)));
+        assert_eq!(game.variant(), BingoVariant::Custom);
+        assert_eq!(game.phase(), GamePhase::Setup);
+        assert_eq!(game.version(), 0);
+
+        // 最初の抽選で確定・開始し、最後の抽選で終了する
+        game.draw().unwrap();
+        assert_eq!(game.phase(), GamePhase::Running);
+        game.draw().unwrap();
+        assert_eq!(game.phase(), GamePhase::Finished);
+        let actions: Vec<_> = game.transitions().iter().map(|t| t.action).collect();
+        assert_eq!(
+            actions,
+            [PhaseAction::Lock, PhaseAction::Start, PhaseAction::Finish]
+        );
+        let version = game.version();
+
+        // 出尽くした後の抽選では状態は変わらない
+        assert_eq!(game.draw().unwrap(), None);
+        assert_eq!(game.version(), version);
+
+        game.reset().unwrap();
+        assert_eq!(game.phase(), GamePhase::Setup);
+        assert!(game.history.is_empty());
+        assert!(game.version() > version);
+        assert_eq!(BingoVariant::from_max_number(75), BingoVariant::Ball75);
+    }
+
+    #[test]
+    fn test_operations_refused_by_phase() {
+        let mut game = BingoGame::new(75, Box::new(XorShift::new(123)));
+        game.draw().unwrap();
+        game.transition(PhaseAction::Pause).unwrap();
+        assert_eq!(
+            game.draw(),
+            Err(GamePhaseError::CannotDraw(GamePhase::Paused))
+        );
+        assert_eq!(game.history.len(), 1);
+
+        // 途中で終了したゲームでは抽選できない
+        game.transition(PhaseAction::Finish).unwrap();
+        assert_eq!(
+            game.draw(),
+            Err(GamePhaseError::CannotDraw(GamePhase::Finished))
+        );
+
+        game.transition(PhaseAction::Archive).unwrap();
+        assert!(game.reset().is_err());
+        assert_eq!(game.history.len(), 1);
+    }
+
+    fn guests(count: usize) -> Vec<Option<PlayerId>> {
+        (0..count as PlayerId).map(Some).collect()
+    }
+
+    #[test]
+    fn test_amida_strict_requires_equal_counts() {
+        let mut amida = AmidaGame::new(3, Box::new(XorShift::new(123)));
+        amida.update(guests(2)).unwrap();
+        assert!(amida.get_result().is_none());
+
+        amida.update(guests(3)).unwrap();
+        let result = amida.get_result().unwrap();
+        assert_eq!(result.pairs.len(), 3);
+        assert!(result.leftover.is_empty());
+    }
+
+    #[test]
+    fn test_amida_blank_slots_when_guests_exceed_prizes() {
+        let mut amida = AmidaGame::new(3, Box::new(XorShift::new(123)));
+        amida.set_rule(AmidaRule::Leftover);
+        amida.update(guests(5)).unwrap();
+
+        let result = amida.get_result().unwrap();
+        assert_eq!(result.pairs.len(), 5);
+        let blanks = result
+            .pairs
+            .iter()
+            .filter(|(prize, _)| prize == BLANK_PRIZE_LABEL)
+            .count();
+        assert_eq!(blanks, 2);
+
+        let prizes: HashSet<&str> = result.pairs.iter().map(|(p, _)| p.as_str()).collect();
+        assert!(prizes.contains("1") && prizes.contains("2") && prizes.contains("3"));
+    }
+
+    #[test]
+    fn test_amida_leftover_prizes() {
+        let mut amida = AmidaGame::new(5, Box::new(XorShift::new(123)));
+        amida.set_rule(AmidaRule::Leftover);
+        amida.update(guests(2)).unwrap();
+
+        let result = amida.get_result().unwrap();
+        assert_eq!(result.pairs.len(), 2);
+        assert_eq!(result.leftover.len(), 3);
+        assert_eq!(result.rule, AmidaRule::Leftover);
+    }
+
+    #[test]
+    fn test_amida_multiple_prizes_per_guest() {
+        let mut amida = AmidaGame::new(5, Box::new(XorShift::new(123)));
+        amida.set_rule(AmidaRule::MultiplePerGuest);
+        amida.update(guests(2)).unwrap();
+
+        let result = amida.get_result().unwrap();
+        assert_eq!(result.pairs.len(), 5);
+        assert!(result.leftover.is_empty());
+        let first = result.pairs.iter().filter(|(_, g)| *g == Some(0)).count();
+        let second = result.pairs.iter().filter(|(_, g)| *g == Some(1)).count();
+        assert_eq!((first, second), (3, 2));
+    }
+
+    #[test]
+    fn test_amida_result_is_reproducible() {
+        let mut a = AmidaGame::new(4, Box::new(XorShift::new(99)));
+        let mut b = AmidaGame::new(4, Box::new(XorShift::new(99)));
+        a.update(guests(4)).unwrap();
+        b.update(guests(2)).unwrap();
+        b.update(guests(4)).unwrap();
+        assert_eq!(a.get_result(), b.get_result());
+    }
+
+    #[test]
+    fn test_amida_reveal_one_at_a_time() {
+        let mut amida = AmidaGame::new(3, Box::new(XorShift::new(123)));
+        amida.update(guests(3)).unwrap();
+        let full = amida.get_result().unwrap();
+
+        assert!(amida.get_revealed_result().unwrap().pairs.is_empty());
+
+        let pair = amida.reveal(2).unwrap();
+        assert_eq!(pair.0, "2");
+        assert!(full.pairs.contains(&pair));
+        assert_eq!(
+            amida.get_revealed_result().unwrap().pairs,
+            vec![pair.clone()]
+        );
+
+        // 同じ枠を再度公開しても重複しない
+        assert_eq!(amida.reveal(2).unwrap(), pair);
+        assert_eq!(amida.revealed.len(), 1);
+        assert!(!amida.is_fully_revealed());
+
+        assert!(amida.reveal(9).is_none());
+        amida.reveal(1);
+        amida.reveal(3);
+        assert!(amida.is_fully_revealed());
+    }
+
+    #[test]
+    fn test_amida_leftover_hidden_until_fully_revealed() {
+        let mut amida = AmidaGame::new(4, Box::new(XorShift::new(123)));
+        amida.set_rule(AmidaRule::Leftover);
+        amida.update(guests(2)).unwrap();
+        let full = amida.get_result().unwrap();
+
+        // 余った景品は公開できない
+        assert!(amida.reveal(full.leftover[0]).is_none());
+
+        let (first, _) = full.pairs[0].clone();
+        amida.reveal(first.parse().unwrap());
+        assert!(amida.get_revealed_result().unwrap().leftover.is_empty());
+
+        let (second, _) = full.pairs[1].clone();
+        amida.reveal(second.parse().unwrap());
+        assert_eq!(amida.get_revealed_result().unwrap(), full);
+    }
+
+    #[test]
+    fn test_amida_update_clears_reveals() {
+        let mut amida = AmidaGame::new(2, Box::new(XorShift::new(123)));
+        amida.update(guests(2)).unwrap();
+        amida.reveal(1);
+        amida.update(guests(2)).unwrap();
+        assert!(amida.revealed.is_empty());
+    }
+
+    #[test]
+    fn test_amida_exclusion_is_respected() {
+        let constraints = AmidaConstraints {
+            exclusions: vec![AmidaExclusion {
+                player: 0,
+                prize: 1,
+            }],
+            spreads: Vec::new(),
+        };
+
+        for seed in 1..50 {
+            let mut amida = AmidaGame::new(3, Box::new(XorShift::new(seed)));
+            amida.set_constraints(constraints.clone());
+            amida.update(guests(3)).unwrap();
+            let result = amida.get_result().unwrap();
+            assert!(!result.pairs.contains(&("1".to_string(), Some(0))));
+        }
+    }
+
+    #[test]
+    fn test_amida_spread_limits_group_prizes() {
+        let constraints = AmidaConstraints {
+            exclusions: Vec::new(),
+            spreads: vec![AmidaSpread {
+                members: vec![0, 1],
+                prizes: vec![1, 2],
+                max: 1,
+            }],
+        };
+
+        for seed in 1..50 {
+            let mut amida = AmidaGame::new(4, Box::new(XorShift::new(seed)));
+            amida.set_constraints(constraints.clone());
+            amida.update(guests(4)).unwrap();
+            let top = amida
+                .get_result()
+                .unwrap()
+                .pairs
+                .iter()
+                .filter(|(p, g)| (p == "1" || p == "2") && matches!(g, Some(0 | 1)))
+                .count();
+            assert!(top <= 1);
+        }
+    }
+
+    /// 参加者 i が景品 i+1 と i+2 (最後の参加者は景品1) だけを受け取れる制約
+    ///
+    /// 制約を満たす割り当ては全員が i+1 か全員が i+2 の2通りしかなく、
+    /// 棄却サンプリングではまず見つからない。
+    fn cyclic_constraints(count: usize) -> AmidaConstraints {
+        let exclusions = (0..count)
+            .flat_map(|gest| {
+                (1..=count as u8)
+                    .filter(move |&prize| {
+                        prize as usize != gest + 1 && prize as usize != (gest + 1) % count + 1
+                    })
+                    .map(move |prize| AmidaExclusion {
+                        player: gest as PlayerId,
+                        prize,
+                    })
+            })
+            .collect();
+        AmidaConstraints {
+            exclusions,
+            spreads: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_amida_tight_constraints_are_sampled_exactly() {
+        let mut outcomes = HashSet::new();
+        for seed in 1..20 {
+            let mut amida = AmidaGame::new(10, Box::new(XorShift::new(seed)));
+            amida.set_constraints(cyclic_constraints(10));
+            amida.update(guests(10)).unwrap();
+            let pairs = amida.get_result().unwrap().pairs;
+            let first = pairs.iter().find(|(_, g)| *g == Some(0)).unwrap().0.clone();
+            for (prize, gest) in &pairs {
+                let gest = gest.unwrap() as usize;
+                let shift = if first == "1" { 0 } else { 1 };
+                assert_eq!(prize.parse::<usize>().unwrap(), (gest + shift) % 10 + 1);
+            }
+            outcomes.insert(first);
+
+            // 同じシード値なら同じ割り当てになる
+            let mut again = AmidaGame::new(10, Box::new(XorShift::new(seed)));
+            again.set_constraints(cyclic_constraints(10));
+            again.update(guests(10)).unwrap();
+            assert_eq!(again.get_result().unwrap().pairs, pairs);
+        }
+        // 2通りの割り当てがどちらも選ばれる
+        assert_eq!(outcomes.len(), 2);
+
+        // ハズレ枠がある場合も、全ての枠番号を1回ずつ使う
+        let mut amida = AmidaGame::new(10, Box::new(XorShift::new(7)));
+        amida.set_rule(AmidaRule::Leftover);
+        amida.set_constraints(cyclic_constraints(10));
+        amida.update(guests(12)).unwrap();
+        let mut prizes = amida.prizes.clone();
+        prizes.sort_unstable();
+        assert_eq!(prizes, (1..=12).collect::<Vec<u8>>());
+        assert!(amida.satisfies_constraints(&amida.prizes));
+    }
+
+    #[test]
+    fn test_amida_unsatisfiable_constraints() {
+        let mut amida = AmidaGame::new(2, Box::new(XorShift::new(123)));
+        amida.set_constraints(AmidaConstraints {
+            exclusions: vec![
+                AmidaExclusion {
+                    player: 0,
+                    prize: 1,
+                },
+                AmidaExclusion {
+                    player: 0,
+                    prize: 2,
+                },
+            ],
+            spreads: Vec::new(),
+        });
+
+        assert_eq!(
+            amida.update(guests(2)),
+            Err(AmidaConstraintError::Unsatisfiable)
+        );
+        assert!(amida.get_result().is_none());
+    }
+
+    #[test]
+    fn test_amida_constraints_allow_blank_slots() {
+        // 景品を受け取れない参加者でも、ハズレ枠があれば割り当て可能
+        let mut amida = AmidaGame::new(1, Box::new(XorShift::new(123)));
+        amida.set_rule(AmidaRule::Leftover);
+        amida.set_constraints(AmidaConstraints {
+            exclusions: vec![AmidaExclusion {
+                player: 0,
+                prize: 1,
+            }],
+            spreads: Vec::new(),
+        });
+        amida.update(guests(2)).unwrap();
+
+        let result = amida.get_result().unwrap();
+        assert!(result
+            .pairs
+            .contains(&(BLANK_PRIZE_LABEL.to_string(), Some(0))));
+    }
+
+    #[test]
+    fn test_amida_absent_redraw_is_reproducible() {
+        let play = || {
+            let mut amida = AmidaGame::new(2, Box::new(XorShift::new(123)));
+            amida.set_rule(AmidaRule::Leftover);
+            amida.update(guests(5)).unwrap();
+            for prize in amida.prizes.clone() {
+                amida.reveal(prize);
+            }
+            let absentee = amida.get_result().unwrap().pairs[..]
+                .iter()
+                .find(|(p, _)| p == "1")
+                .and_then(|&(_, gest)| gest)
+                .unwrap();
+            let redraws = amida.mark_absent(absentee).unwrap();
+            (amida, absentee, redraws)
+        };
+
+        let (amida, absentee, redraws) = play();
+        assert_eq!(redraws.len(), 1);
+        assert_eq!(redraws[0].prize, 1);
+        assert_ne!(redraws[0].winner, absentee);
+        assert_eq!(amida.absentees, vec![absentee]);
+
+        // 景品を持っていない参加者が優先される
+        let original = amida.get_result().unwrap();
+        let winner_pair = original
+            .pairs
+            .iter()
+            .find(|(_, g)| *g == Some(redraws[0].winner))
+            .unwrap();
+        assert_eq!(winner_pair.0, BLANK_PRIZE_LABEL);
+
+        // 元の結果は変わらず、保持者のみ更新される
+        assert!(original.pairs.contains(&("1".to_string(), Some(absentee))));
+        let holders = amida.current_holders().unwrap();
+        assert!(holders.contains(&(1, Some(redraws[0].winner))));
+
+        let (_, _, again) = play();
+        assert_eq!(redraws, again);
+    }
+
+    #[test]
+    fn test_amida_absent_requires_revealed_result() {
+        let mut amida = AmidaGame::new(2, Box::new(XorShift::new(123)));
+        amida.update(guests(2)).unwrap();
+        assert_eq!(amida.mark_absent(0), Err(AmidaRedrawError::NotRevealed));
+        assert_eq!(amida.mark_absent(9), Err(AmidaRedrawError::UnknownGuest));
+        assert!(amida.absentees.is_empty());
+
+        amida.reveal(1);
+        amida.reveal(2);
+        amida.mark_absent(0).unwrap();
+        assert_eq!(amida.mark_absent(0), Err(AmidaRedrawError::AlreadyAbsent));
+    }
+
+    #[test]
+    fn test_amida_involves_seated_and_constrained_players() {
+        let mut amida = AmidaGame::new(2, Box::new(XorShift::new(123)));
+        amida.set_rule(AmidaRule::Leftover);
+        amida.set_constraints(AmidaConstraints {
+            exclusions: vec![AmidaExclusion {
+                player: 5,
+                prize: 1,
+            }],
+            spreads: Vec::new(),
+        });
+        amida.update(vec![Some(0), None]).unwrap();
+        assert!(amida.involves(0));
+        assert!(amida.involves(5));
+        assert!(!amida.involves(1));
+
+        // 空の枠は結果に参加者のいない枠として残る
+        let result = amida.get_result().unwrap();
+        assert_eq!(result.pairs.len(), 2);
+        assert!(result.pairs.iter().any(|(_, gest)| gest.is_none()));
     }
 }
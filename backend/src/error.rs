@@ -0,0 +1,464 @@
+use crate::autodraw::AutoDrawError;
+use crate::card::CardError;
+use crate::domain::AmidaRedrawError;
+use crate::import::ImportError;
+use crate::lifecycle::GamePhaseError;
+use crate::player::{PlayerError, PlayerId};
+use crate::qr::QrError;
+use crate::winner::WinnerError;
+use axum::{
+    extract::rejection::{BytesRejection, JsonRejection, PathRejection, QueryRejection},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use std::fmt;
+use utoipa::ToSchema;
+
+/// APIのエラーコード
+///
+/// クライアントが分岐に使う機械可読な値です。一度公開したコードの意味は変更しません。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// 管理者トークンが必要
+    Unauthorized,
+    /// 管理者トークンが正しくない
+    Forbidden,
+    /// 管理者トークンを続けて間違えたため、しばらく受け付けない
+    TooManyAttempts,
+    /// 存在しないパス
+    NotFound,
+    /// リクエストの形式が正しくない
+    InvalidRequest,
+    /// 指定されたIDのゲームがない
+    GameNotFound,
+    /// 全ての数字が出尽くしている
+    GameOver,
+    /// 指定された番号のラウンドがない
+    RoundNotFound,
+    /// 現在のフェーズでは行えない操作
+    InvalidPhase,
+    /// すでに自動抽選中
+    AutoDrawActive,
+    /// 自動抽選中ではない
+    AutoDrawInactive,
+    /// 指定された枠番号に割り当てがない
+    PrizeNotFound,
+    /// あみだくじの結果が確定していない
+    AmidaNotReady,
+    /// 参加者リストに存在しない参加者
+    UnknownGuest,
+    /// すでに欠席として記録されている
+    AlreadyAbsent,
+    /// 結果がまだ公開されていない
+    NotRevealed,
+    /// 指定されたIDの参加者がいない
+    PlayerNotFound,
+    /// 参加者の情報が正しくない (空の表示名・絵文字ではないアバター)
+    InvalidPlayer,
+    /// 同じ表示名の参加者がすでにいる
+    DuplicatePlayer,
+    /// 申告・当選者・カード・あみだくじの枠で参照されている参加者
+    PlayerInUse,
+    /// 参加コードに対応するカードがない
+    CardNotFound,
+    /// カードの形式がないビンゴの種類 (75ボールのみ対応)
+    UnsupportedVariant,
+    /// カードの当たりの形が揃っていない
+    NotBingo,
+    /// QRコードの内容が正しくない (偽造・改変されたカード)
+    InvalidCard,
+    /// スマートフォン用のカードIDを使い切った
+    CardsExhausted,
+    /// 画像を読めない、または読み取れるQRコードがない
+    UnreadableImage,
+    /// すでに当選している参加者 (景品は1人1つまで)
+    AlreadyWon,
+    /// 進行中のラウンドに参加者の申告がない
+    ClaimNotFound,
+    /// 景品を分け合っている当選者がいない
+    NoTie,
+    /// 景品を受け取れる出席者がいない
+    NoEligibleGuest,
+    /// ファイルの文字コードが正しくない
+    InvalidEncoding,
+    /// ファイルにヘッダー行がない
+    MissingHeader,
+    /// ファイルに名前列がない
+    MissingNameColumn,
+    /// 指定されたヘッダー名の列がない
+    UnknownColumn,
+    /// 当選確率のシミュレーションが混み合っている
+    SimulationBusy,
+}
+
+/// APIのエラー
+///
+/// 全てのハンドラはこの型をエラーとして返し、
+/// `{"code": "...", "message": "..."}` 形式のJSONと適切なHTTPステータスに変換されます。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiError {
+    /// 管理者トークンがない (401)
+    Unauthorized,
+    /// 管理者トークンが正しくない (403)
+    Forbidden,
+    /// 管理者トークンを続けて間違えたため、指定した秒数の間は受け付けない (429)
+    TooManyAttempts(u64),
+    /// 存在しないパス (404)
+    NotFound,
+    /// リクエストボディやクエリパラメータを解析できない
+    InvalidRequest(StatusCode, String),
+    /// 指定されたIDのゲームがない (404)
+    GameNotFound(String),
+    /// 全ての数字が出尽くしている (409)
+    GameOver,
+    /// 指定された番号のラウンドがない (404)
+    RoundNotFound(u32),
+    /// 現在のフェーズでは行えない操作 (409)
+    Phase(GamePhaseError),
+    /// 自動抽選を操作できない (409)
+    AutoDraw(AutoDrawError),
+    /// 指定された枠番号に割り当てがない (404)
+    PrizeNotFound(u8),
+    /// あみだくじの結果が確定していない (409)
+    AmidaNotReady,
+    /// 欠席者の再抽選に失敗した
+    Redraw(AmidaRedrawError),
+    /// 参加者リストのファイルを取り込めない (422)
+    Import(ImportError),
+    /// 参加者名簿を操作できない
+    Player(PlayerError),
+    /// 参照されている参加者は削除できない (409)
+    PlayerInUse(PlayerId),
+    /// ビンゴカードを操作できない
+    Card(CardError),
+    /// QRコードの画像を読み取れない (422)
+    Qr(QrError),
+    /// 当選者を記録できない
+    Winner(WinnerError),
+    /// 当選確率のシミュレーションが混み合っているため、指定した秒数後に再試行してほしい (503)
+    SimulationBusy(u64),
+}
+
+/// エラーレスポンスのボディ
+#[derive(Serialize, ToSchema)]
+pub struct ErrorResponse {
+    /// エラーコード
+    pub code: ErrorCode,
+    /// 人が読むためのエラーの説明 (内容は変わる可能性がある)
+    pub message: String,
+}
+
+/// ハンドラの戻り値
+pub type ApiResult<T> = Result<T, ApiError>;
+
+impl ApiError {
+    /// エラーコード
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::Unauthorized => ErrorCode::Unauthorized,
+            Self::Forbidden => ErrorCode::Forbidden,
+            Self::TooManyAttempts(_) => ErrorCode::TooManyAttempts,
+            Self::NotFound => ErrorCode::NotFound,
+            Self::InvalidRequest(..) => ErrorCode::InvalidRequest,
+            Self::GameNotFound(_) => ErrorCode::GameNotFound,
+            Self::GameOver => ErrorCode::GameOver,
+            Self::RoundNotFound(_) => ErrorCode::RoundNotFound,
+            Self::Phase(_) | Self::AutoDraw(AutoDrawError::Phase(_)) => ErrorCode::InvalidPhase,
+            Self::AutoDraw(AutoDrawError::AlreadyActive) => ErrorCode::AutoDrawActive,
+            Self::AutoDraw(AutoDrawError::NotActive) => ErrorCode::AutoDrawInactive,
+            Self::PrizeNotFound(_) => ErrorCode::PrizeNotFound,
+            Self::AmidaNotReady | Self::Redraw(AmidaRedrawError::NotReady) => {
+                ErrorCode::AmidaNotReady
+            }
+            Self::Redraw(AmidaRedrawError::UnknownGuest) => ErrorCode::UnknownGuest,
+            Self::Redraw(AmidaRedrawError::AlreadyAbsent) => ErrorCode::AlreadyAbsent,
+            Self::Redraw(AmidaRedrawError::NotRevealed) => ErrorCode::NotRevealed,
+            Self::Redraw(AmidaRedrawError::NoEligibleGuest) => ErrorCode::NoEligibleGuest,
+            Self::Import(ImportError::InvalidEncoding(_)) => ErrorCode::InvalidEncoding,
+            Self::Import(ImportError::MissingHeader) => ErrorCode::MissingHeader,
+            Self::Import(ImportError::MissingNameColumn) => ErrorCode::MissingNameColumn,
+            Self::Import(ImportError::UnknownColumn(_)) => ErrorCode::UnknownColumn,
+            Self::Player(PlayerError::NotFound(_)) => ErrorCode::PlayerNotFound,
+            Self::Player(PlayerError::EmptyName | PlayerError::InvalidAvatar) => {
+                ErrorCode::InvalidPlayer
+            }
+            Self::Player(PlayerError::DuplicateName(_)) => ErrorCode::DuplicatePlayer,
+            Self::PlayerInUse(_) => ErrorCode::PlayerInUse,
+            Self::Card(CardError::NotSetup(_)) => ErrorCode::InvalidPhase,
+            Self::Card(CardError::UnsupportedVariant(_)) => ErrorCode::UnsupportedVariant,
+            Self::Card(CardError::NotFound) => ErrorCode::CardNotFound,
+            Self::Card(CardError::NotBingo) => ErrorCode::NotBingo,
+            Self::Card(CardError::InvalidCard) => ErrorCode::InvalidCard,
+            Self::Card(CardError::Exhausted) => ErrorCode::CardsExhausted,
+            Self::Qr(_) => ErrorCode::UnreadableImage,
+            Self::Winner(WinnerError::AlreadyWon(_)) => ErrorCode::AlreadyWon,
+            Self::Winner(WinnerError::ClaimNotFound(_)) => ErrorCode::ClaimNotFound,
+            Self::Winner(WinnerError::NoTie) => ErrorCode::NoTie,
+            Self::SimulationBusy(_) => ErrorCode::SimulationBusy,
+        }
+    }
+
+    /// HTTPステータスコード
+    pub fn status(&self) -> StatusCode {
+        match self.code() {
+            ErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+            ErrorCode::Forbidden => StatusCode::FORBIDDEN,
+            ErrorCode::TooManyAttempts => StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::NotFound
+            | ErrorCode::GameNotFound
+            | ErrorCode::RoundNotFound
+            | ErrorCode::PrizeNotFound
+            | ErrorCode::UnknownGuest
+            | ErrorCode::PlayerNotFound
+            | ErrorCode::CardNotFound
+            | ErrorCode::ClaimNotFound => StatusCode::NOT_FOUND,
+            ErrorCode::InvalidRequest => match self {
+                Self::InvalidRequest(status, _) => *status,
+                _ => StatusCode::BAD_REQUEST,
+            },
+            ErrorCode::GameOver
+            | ErrorCode::InvalidPhase
+            | ErrorCode::AutoDrawActive
+            | ErrorCode::AutoDrawInactive
+            | ErrorCode::AmidaNotReady
+            | ErrorCode::AlreadyAbsent
+            | ErrorCode::NotRevealed
+            | ErrorCode::DuplicatePlayer
+            | ErrorCode::PlayerInUse
+            | ErrorCode::UnsupportedVariant
+            | ErrorCode::CardsExhausted
+            | ErrorCode::NotBingo
+            | ErrorCode::AlreadyWon
+            | ErrorCode::NoTie => StatusCode::CONFLICT,
+            ErrorCode::NoEligibleGuest
+            | ErrorCode::InvalidPlayer
+            | ErrorCode::InvalidCard
+            | ErrorCode::UnreadableImage
+            | ErrorCode::InvalidEncoding
+            | ErrorCode::MissingHeader
+            | ErrorCode::MissingNameColumn
+            | ErrorCode::UnknownColumn => StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorCode::SimulationBusy => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unauthorized => write!(f, "admin token is required"),
+            Self::Forbidden => write!(f, "admin token is invalid"),
+            Self::TooManyAttempts(seconds) => write!(
+                f,
+                "too many invalid admin tokens; retry after {} seconds",
+                seconds
+            ),
+            Self::NotFound => write!(f, "not found"),
+            Self::InvalidRequest(_, message) => write!(f, "{}", message),
+            Self::GameNotFound(id) => write!(f, "game {:?} does not exist", id),
+            Self::GameOver => write!(f, "all numbers have been drawn"),
+            Self::RoundNotFound(number) => write!(f, "round {} does not exist", number),
+            Self::Phase(e) => write!(f, "{}", e),
+            Self::AutoDraw(e) => write!(f, "{}", e),
+            Self::PrizeNotFound(prize) => write!(f, "prize {} has no assignment", prize),
+            Self::AmidaNotReady => write!(f, "amida result is not ready"),
+            Self::Redraw(e) => write!(f, "{}", e),
+            Self::Import(e) => write!(f, "{}", e),
+            Self::Player(e) => write!(f, "{}", e),
+            Self::PlayerInUse(id) => write!(
+                f,
+                "player {} is referenced by a claim, a winner, a card or an amida slot",
+                id
+            ),
+            Self::Card(e) => write!(f, "{}", e),
+            Self::Qr(e) => write!(f, "{}", e),
+            Self::Winner(e) => write!(f, "{}", e),
+            Self::SimulationBusy(seconds) => write!(
+                f,
+                "too many winner probability simulations are running; retry after {} seconds",
+                seconds
+            ),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let body = Json(ErrorResponse {
+            code: self.code(),
+            message: self.to_string(),
+        });
+        if status == StatusCode::UNAUTHORIZED {
+            let challenge = [(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"))];
+            return (status, challenge, body).into_response();
+        }
+        if let Self::TooManyAttempts(seconds) | Self::SimulationBusy(seconds) = self {
+            let retry = [(header::RETRY_AFTER, HeaderValue::from(seconds))];
+            return (status, retry, body).into_response();
+        }
+        (status, body).into_response()
+    }
+}
+
+impl From<AmidaRedrawError> for ApiError {
+    fn from(e: AmidaRedrawError) -> Self {
+        Self::Redraw(e)
+    }
+}
+
+impl From<GamePhaseError> for ApiError {
+    fn from(e: GamePhaseError) -> Self {
+        Self::Phase(e)
+    }
+}
+
+impl From<AutoDrawError> for ApiError {
+    fn from(e: AutoDrawError) -> Self {
+        Self::AutoDraw(e)
+    }
+}
+
+impl From<ImportError> for ApiError {
+    fn from(e: ImportError) -> Self {
+        Self::Import(e)
+    }
+}
+
+impl From<PlayerError> for ApiError {
+    fn from(e: PlayerError) -> Self {
+        Self::Player(e)
+    }
+}
+
+impl From<CardError> for ApiError {
+    fn from(e: CardError) -> Self {
+        Self::Card(e)
+    }
+}
+
+impl From<QrError> for ApiError {
+    fn from(e: QrError) -> Self {
+        Self::Qr(e)
+    }
+}
+
+impl From<WinnerError> for ApiError {
+    fn from(e: WinnerError) -> Self {
+        Self::Winner(e)
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        Self::InvalidRequest(rejection.status(), rejection.body_text())
+    }
+}
+
+impl From<BytesRejection> for ApiError {
+    fn from(rejection: BytesRejection) -> Self {
+        Self::InvalidRequest(rejection.status(), rejection.body_text())
+    }
+}
+
+impl From<QueryRejection> for ApiError {
+    fn from(rejection: QueryRejection) -> Self {
+        Self::InvalidRequest(rejection.status(), rejection.body_text())
+    }
+}
+
+impl From<PathRejection> for ApiError {
+    fn from(rejection: PathRejection) -> Self {
+        Self::InvalidRequest(rejection.status(), rejection.body_text())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::TextEncoding;
+    use crate::lifecycle::GamePhase;
+
+    #[test]
+    fn test_status_codes() {
+        assert_eq!(ApiError::Unauthorized.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            ApiError::TooManyAttempts(30).status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(ApiError::PrizeNotFound(3).status(), StatusCode::NOT_FOUND);
+        assert_eq!(ApiError::RoundNotFound(3).status(), StatusCode::NOT_FOUND);
+        assert_eq!(ApiError::GameOver.status(), StatusCode::CONFLICT);
+        assert_eq!(
+            ApiError::Player(PlayerError::NotFound(3)).status(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            ApiError::Player(PlayerError::DuplicateName("Alice".to_string())).status(),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            ApiError::Player(PlayerError::InvalidAvatar).status(),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+        assert_eq!(
+            ApiError::Card(CardError::NotFound).status(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            ApiError::Card(CardError::NotSetup(GamePhase::Running)).code(),
+            ErrorCode::InvalidPhase
+        );
+        assert_eq!(
+            ApiError::Card(CardError::InvalidCard).status(),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+        assert_eq!(
+            ApiError::Winner(WinnerError::AlreadyWon(1)).status(),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            ApiError::Winner(WinnerError::ClaimNotFound(1)).status(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            ApiError::Winner(WinnerError::NoTie).status(),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            ApiError::Phase(GamePhaseError::CannotDraw(GamePhase::Paused)).status(),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            ApiError::Redraw(AmidaRedrawError::NotRevealed).status(),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            ApiError::Import(ImportError::InvalidEncoding(TextEncoding::Utf8)).status(),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+        assert_eq!(
+            ApiError::InvalidRequest(StatusCode::UNSUPPORTED_MEDIA_TYPE, String::new()).status(),
+            StatusCode::UNSUPPORTED_MEDIA_TYPE
+        );
+    }
+
+    #[test]
+    fn test_codes_are_snake_case() {
+        assert_eq!(
+            serde_json::to_value(ApiError::Redraw(AmidaRedrawError::NotReady).code()).unwrap(),
+            "amida_not_ready"
+        );
+        assert_eq!(
+            serde_json::to_value(ErrorCode::MissingNameColumn).unwrap(),
+            "missing_name_column"
+        );
+        assert_eq!(
+            serde_json::to_value(ApiError::AutoDraw(AutoDrawError::NotActive).code()).unwrap(),
+            "auto_draw_inactive"
+        );
+    }
+}
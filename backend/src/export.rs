@@ -0,0 +1,706 @@
+use crate::domain::{AmidaGame, AmidaRedraw, AmidaRule, BingoGame};
+use crate::player::{PlayerId, PlayerRegistry};
+use crate::session::{Round, Session, WinPattern};
+use crate::winner::{BingoPrize, Winner, WinnerRegistry};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fmt::Write;
+use utoipa::ToSchema;
+
+/// UTF-8 の BOM (Excel で文字化けせずに開くために付与する)
+const UTF8_BOM: &str = "\u{FEFF}";
+
+/// 日時の表示形式
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// 結果の出力形式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// JSON
+    #[default]
+    Json,
+    /// CSV (UTF-8 BOM付き)
+    Csv,
+    /// 印刷用のHTMLレポート
+    Html,
+}
+
+impl ExportFormat {
+    /// Content-Type ヘッダーの値
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json; charset=utf-8",
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Html => "text/html; charset=utf-8",
+        }
+    }
+
+    /// ダウンロード時のファイル拡張子
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+            Self::Html => "html",
+        }
+    }
+}
+
+/// ビンゴの抽選記録
+#[derive(Debug, Clone, Serialize)]
+pub struct DrawRecord {
+    /// 抽選順 (1始まり)
+    pub order: usize,
+    /// 抽選された数字
+    pub number: u8,
+    /// 抽選日時
+    pub drawn_at: DateTime<Local>,
+}
+
+/// ビンゴの当選記録
+#[derive(Debug, Clone, Serialize)]
+pub struct BingoWinner {
+    /// 当選者の記録
+    #[serde(flatten)]
+    pub winner: Winner,
+    /// 当選者の表示名
+    pub name: String,
+}
+
+/// ビンゴの1ラウンドの結果
+#[derive(Debug, Clone, Serialize)]
+pub struct RoundExport {
+    /// ラウンド番号
+    pub number: u32,
+    /// 当たりの形
+    pub pattern: WinPattern,
+    /// このラウンドの景品
+    pub prize: Option<String>,
+    /// このラウンドの抽選に使ったシード値
+    pub seed: u32,
+    /// 抽選記録 (抽選順)
+    pub draws: Vec<DrawRecord>,
+    /// 当選者 (記録順)
+    pub winners: Vec<BingoWinner>,
+}
+
+impl RoundExport {
+    /// ラウンドの記録と当選者の一覧から結果を作る
+    fn new(round: &Round, winners: &WinnerRegistry, players: &PlayerRegistry) -> Self {
+        let draws = round
+            .history
+            .iter()
+            .zip(&round.drawn_at)
+            .enumerate()
+            .map(|(i, (&number, &drawn_at))| DrawRecord {
+                order: i + 1,
+                number,
+                drawn_at,
+            })
+            .collect();
+        Self {
+            number: round.settings.number,
+            pattern: round.settings.pattern,
+            prize: round.settings.prize.clone(),
+            seed: round.settings.seed,
+            draws,
+            winners: winners
+                .winners()
+                .iter()
+                .filter(|winner| winner.round == round.settings.number)
+                .map(|winner| BingoWinner {
+                    winner: winner.clone(),
+                    name: players.display_name(winner.player),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// ビンゴの結果
+#[derive(Debug, Clone, Serialize)]
+pub struct BingoExport {
+    /// 乱数生成アルゴリズム
+    pub algorithm: String,
+    /// 全てのラウンドの結果 (古い順、最後が進行中のラウンド)
+    pub rounds: Vec<RoundExport>,
+    /// 進行中のラウンドの残りの数字の個数
+    pub remaining: usize,
+    /// まだ渡していない景品 (渡す順)
+    pub remaining_prizes: Vec<BingoPrize>,
+}
+
+/// あみだくじの当選記録
+#[derive(Debug, Clone, Serialize)]
+pub struct AmidaWinner {
+    /// 景品番号 (ハズレ枠は「ハズレ」)
+    pub prize: String,
+    /// あみだくじで当たった参加者
+    pub guest: String,
+    /// 最終的に景品を受け取った参加者 (欠席による再抽選を反映)
+    pub winner: String,
+}
+
+/// あみだくじの欠席者の景品の再抽選記録
+#[derive(Debug, Clone, Serialize)]
+pub struct AmidaRedrawExport {
+    /// 再抽選した景品番号
+    pub prize: u8,
+    /// 欠席した元の当選者
+    pub absentee: String,
+    /// 新しい当選者
+    pub winner: String,
+    /// 再抽選に使用したサブストリーム番号
+    pub stream: u32,
+}
+
+impl AmidaRedrawExport {
+    /// 再抽選の記録の参加者IDを名簿の表示名にする
+    fn new(redraw: &AmidaRedraw, players: &PlayerRegistry) -> Self {
+        Self {
+            prize: redraw.prize,
+            absentee: players.display_name(redraw.absentee),
+            winner: players.display_name(redraw.winner),
+            stream: redraw.stream,
+        }
+    }
+}
+
+/// あみだくじの結果
+#[derive(Debug, Clone, Serialize)]
+pub struct AmidaExport {
+    /// 乱数生成アルゴリズム
+    pub algorithm: String,
+    /// シード値 (割り当てのある枠を全て公開するまではNone)
+    pub seed: Option<u32>,
+    /// 割り当てルール
+    pub rule: AmidaRule,
+    /// 公開済みの当選記録 (公開順)
+    pub winners: Vec<AmidaWinner>,
+    /// 誰にも割り当てられなかった景品番号
+    pub leftover: Vec<u8>,
+    /// 欠席者
+    pub absentees: Vec<String>,
+    /// 欠席者の景品の再抽選記録
+    pub redraws: Vec<AmidaRedrawExport>,
+    /// 未公開の枠の数
+    pub unrevealed: usize,
+}
+
+/// 結果一式
+///
+/// ビンゴの全てのラウンドの抽選履歴とあみだくじの結果を、シード値や乱数生成アルゴリズムとともにまとめたもの。
+/// ビンゴの各ラウンドは、そのラウンドの抽選に使ったシード値を含む。
+/// あみだくじは公開済みの結果のみを含む。
+#[derive(Debug, Clone, Serialize)]
+pub struct ResultsBundle {
+    /// 出力日時
+    pub generated_at: DateTime<Local>,
+    /// セッションのシード値 (各ラウンドのシード値はここから求める)
+    pub seed: u32,
+    /// ビンゴの結果
+    pub bingo: BingoExport,
+    /// あみだくじの結果
+    pub amida: AmidaExport,
+}
+
+impl ResultsBundle {
+    /// 現在のゲーム状態から結果一式を作成する
+    ///
+    /// # Arguments
+    /// * `session` - ビンゴのセッション (終了したラウンドの記録)
+    /// * `bingo` - 進行中のラウンドのビンゴゲーム
+    /// * `winners` - ビンゴの景品と当選者の一覧
+    /// * `amida` - あみだくじゲーム
+    /// * `players` - 参加者名簿 (当選者・あみだくじの参加者の表示名)
+    /// * `seed` - シード値
+    /// * `amida_seed` - あみだくじのシード値 (全ての枠を公開するまではNone)
+    pub fn collect(
+        session: &Session,
+        bingo: &BingoGame,
+        winners: &WinnerRegistry,
+        amida: &AmidaGame,
+        players: &PlayerRegistry,
+        seed: u32,
+        amida_seed: Option<u32>,
+    ) -> Self {
+        let current = Round::snapshot(session.current(), bingo);
+        let rounds = session
+            .rounds()
+            .iter()
+            .chain([&current])
+            .map(|round| RoundExport::new(round, winners, players))
+            .collect();
+
+        let revealed = amida.get_revealed_result();
+        let holders = amida.current_holders().unwrap_or_default();
+        let name =
+            |gest: Option<PlayerId>| gest.map(|id| players.display_name(id)).unwrap_or_default();
+        let amida_winners = revealed
+            .as_ref()
+            .map(|result| {
+                result
+                    .pairs
+                    .iter()
+                    .map(|&(ref prize, guest)| {
+                        let winner = holders
+                            .iter()
+                            .find(|(p, _)| p.to_string() == *prize)
+                            .map_or(guest, |&(_, holder)| holder);
+                        AmidaWinner {
+                            prize: prize.clone(),
+                            guest: name(guest),
+                            winner: name(winner),
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            generated_at: Local::now(),
+            seed,
+            bingo: BingoExport {
+                algorithm: bingo.algorithm().to_string(),
+                rounds,
+                remaining: bingo.remaining_numbers.len(),
+                remaining_prizes: winners.remaining().to_vec(),
+            },
+            amida: AmidaExport {
+                algorithm: amida.algorithm().to_string(),
+                seed: amida_seed,
+                rule: amida.rule,
+                winners: amida_winners,
+                leftover: revealed.map(|r| r.leftover).unwrap_or_default(),
+                absentees: amida
+                    .absentees
+                    .iter()
+                    .map(|&id| players.display_name(id))
+                    .collect(),
+                redraws: amida
+                    .redraws
+                    .iter()
+                    .map(|redraw| AmidaRedrawExport::new(redraw, players))
+                    .collect(),
+                unrevealed: amida.assigned_count().saturating_sub(amida.revealed.len()),
+            },
+        }
+    }
+
+    /// ダウンロード時のファイル名
+    pub fn file_name(&self, format: ExportFormat) -> String {
+        format!(
+            "bingo-results-{}.{}",
+            self.generated_at.format("%Y%m%d-%H%M%S"),
+            format.extension()
+        )
+    }
+
+    /// 指定した形式で出力する
+    pub fn render(&self, format: ExportFormat) -> String {
+        match format {
+            ExportFormat::Json => self.to_json(),
+            ExportFormat::Csv => self.to_csv(),
+            ExportFormat::Html => self.to_html(),
+        }
+    }
+
+    /// JSON形式で出力する
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// CSV形式で出力する
+    ///
+    /// 1行1レコードで、`section` 列がレコードの種類 (meta / bingo / winner / prize / amida / redraw) を表す。
+    /// `meta` の行は `key` 列と `value` 列を使い、ラウンドごとの項目は `round` 列にラウンド番号を入れる。
+    /// Excel で開けるよう UTF-8 の BOM を付与し、数式として解釈されないようにセルを無害化する。
+    pub fn to_csv(&self) -> String {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        let mut row = |fields: [&str; 10]| {
+            // Vec への書き込みは失敗しない
+            let cells = fields.map(csv_cell);
+            let _ = writer.write_record(cells.iter().map(|cell| cell.as_bytes()));
+        };
+
+        row([
+            "section", "round", "order", "number", "prize", "guest", "winner", "time", "key",
+            "value",
+        ]);
+        let generated_at = self.generated_at.format(TIME_FORMAT).to_string();
+        let seed = self.seed.to_string();
+        let amida_seed = self.amida.seed.map(|seed| seed.to_string());
+        let mut meta = vec![
+            ("generated_at", generated_at.as_str()),
+            ("seed", &seed),
+            ("bingo_algorithm", &self.bingo.algorithm),
+            ("amida_algorithm", &self.amida.algorithm),
+        ];
+        if let Some(amida_seed) = &amida_seed {
+            meta.push(("amida_seed", amida_seed));
+        }
+        for (key, value) in meta {
+            row(["meta", "", "", "", "", "", "", "", key, value]);
+        }
+
+        for round in &self.bingo.rounds {
+            let number = round.number.to_string();
+            let seed = round.seed.to_string();
+            let mut meta = vec![("seed", seed.as_str()), ("pattern", round.pattern.name())];
+            if let Some(prize) = &round.prize {
+                meta.push(("prize", prize));
+            }
+            for (key, value) in meta {
+                row(["meta", &number, "", "", "", "", "", "", key, value]);
+            }
+            for draw in &round.draws {
+                row([
+                    "bingo",
+                    &number,
+                    &draw.order.to_string(),
+                    &draw.number.to_string(),
+                    "",
+                    "",
+                    "",
+                    &draw.drawn_at.format(TIME_FORMAT).to_string(),
+                    "",
+                    "",
+                ]);
+            }
+            for record in &round.winners {
+                let winner = &record.winner;
+                row([
+                    "winner",
+                    &number,
+                    &winner.draw_index.to_string(),
+                    &winner.number.map(|n| n.to_string()).unwrap_or_default(),
+                    winner
+                        .prize
+                        .as_ref()
+                        .map_or("", |prize| prize.name.as_str()),
+                    "",
+                    &record.name,
+                    &winner.at.format(TIME_FORMAT).to_string(),
+                    "share",
+                    &winner.share.to_string(),
+                ]);
+            }
+        }
+        for (i, prize) in self.bingo.remaining_prizes.iter().enumerate() {
+            row([
+                "prize",
+                "",
+                &(i + 1).to_string(),
+                "",
+                &prize.name,
+                "",
+                "",
+                "",
+                "",
+                "",
+            ]);
+        }
+        for (i, winner) in self.amida.winners.iter().enumerate() {
+            row([
+                "amida",
+                "",
+                &(i + 1).to_string(),
+                "",
+                &winner.prize,
+                &winner.guest,
+                &winner.winner,
+                "",
+                "",
+                "",
+            ]);
+        }
+        for redraw in &self.amida.redraws {
+            row([
+                "redraw",
+                "",
+                &redraw.stream.to_string(),
+                "",
+                &redraw.prize.to_string(),
+                &redraw.absentee,
+                &redraw.winner,
+                "",
+                "",
+                "",
+            ]);
+        }
+
+        let bytes = writer.into_inner().unwrap_or_default();
+        format!("{}{}", UTF8_BOM, String::from_utf8_lossy(&bytes))
+    }
+
+    /// 印刷用のHTMLレポートとして出力する
+    ///
+    /// 外部ファイルを参照しない単体のHTMLとして出力する。
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        let generated_at = self.generated_at.format(TIME_FORMAT);
+        // String への書き込みは失敗しない
+        let _ = write!(
+            html,
+            r#"<!DOCTYPE html>
+<html lang="ja">
+<head>
+<meta charset="utf-8">
+<title>抽選結果 {generated_at}</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; color: #222; }}
+h1 {{ color: #1a472a; border-bottom: 3px solid #d4af37; }}
+h2 {{ color: #1a472a; margin-top: 1.5em; }}
+table {{ border-collapse: collapse; margin: 0.5em 0; }}
+th, td {{ border: 1px solid #999; padding: 4px 12px; text-align: left; }}
+th {{ background: #eee; }}
+.numbers {{ display: flex; flex-wrap: wrap; gap: 6px; }}
+.numbers span {{ border: 1px solid #1a472a; border-radius: 50%; width: 2.2em; height: 2.2em; line-height: 2.2em; text-align: center; }}
+@media print {{ body {{ margin: 0; }} h2 {{ page-break-after: avoid; }} }}
+</style>
+</head>
+<body>
+<h1>🎄 抽選結果</h1>
+<table>
+<tr><th>出力日時</th><td>{generated_at}</td></tr>
+<tr><th>シード値 (セッション)</th><td>{seed}</td></tr>
+<tr><th>乱数生成 (ビンゴ)</th><td>{bingo_algorithm}</td></tr>
+<tr><th>乱数生成 (あみだくじ)</th><td>{amida_algorithm}</td></tr>
+<tr><th>シード値 (あみだくじ)</th><td>{amida_seed}</td></tr>
+</table>
+"#,
+            seed = self.seed,
+            amida_seed = self.amida.seed.map_or_else(
+                || "全ての枠の公開後に表示".to_string(),
+                |seed| seed.to_string()
+            ),
+            bingo_algorithm = escape_html(&self.bingo.algorithm),
+            amida_algorithm = escape_html(&self.amida.algorithm),
+        );
+
+        let current = self.bingo.rounds.last().map(|round| round.number);
+        for round in &self.bingo.rounds {
+            let _ = write!(
+                html,
+                "<h2>ビンゴ ラウンド{} ({}個抽選",
+                round.number,
+                round.draws.len()
+            );
+            if Some(round.number) == current {
+                let _ = write!(html, " / 残り{}個", self.bingo.remaining);
+            }
+            let _ = writeln!(
+                html,
+                ")</h2>\n<table>\n<tr><th>当たりの形</th><td>{}</td></tr>\n<tr><th>景品</th><td>{}</td></tr>\n<tr><th>シード値</th><td>{}</td></tr>\n</table>",
+                round.pattern.name(),
+                escape_html(round.prize.as_deref().unwrap_or("")),
+                round.seed
+            );
+            html.push_str("<div class=\"numbers\">");
+            for draw in &round.draws {
+                let _ = write!(html, "<span>{}</span>", draw.number);
+            }
+            html.push_str("</div>\n<table>\n<tr><th>順番</th><th>数字</th><th>日時</th></tr>\n");
+            for draw in &round.draws {
+                let _ = writeln!(
+                    html,
+                    "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                    draw.order,
+                    draw.number,
+                    draw.drawn_at.format(TIME_FORMAT)
+                );
+            }
+            html.push_str("</table>\n");
+            if !round.winners.is_empty() {
+                html.push_str("<table>\n<tr><th>当選者</th><th>揃った数字</th><th>抽選順</th><th>景品</th><th>分け合う人数</th></tr>\n");
+                for record in &round.winners {
+                    let winner = &record.winner;
+                    let _ = writeln!(
+                        html,
+                        "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                        escape_html(&record.name),
+                        winner.number.map(|n| n.to_string()).unwrap_or_default(),
+                        winner.draw_index,
+                        escape_html(
+                            winner
+                                .prize
+                                .as_ref()
+                                .map_or("", |prize| prize.name.as_str())
+                        ),
+                        winner.share
+                    );
+                }
+                html.push_str("</table>\n");
+            }
+        }
+        if !self.bingo.remaining_prizes.is_empty() {
+            let names: Vec<String> = self
+                .bingo
+                .remaining_prizes
+                .iter()
+                .map(|prize| escape_html(&prize.name))
+                .collect();
+            let _ = writeln!(html, "<p>まだ渡していない景品: {}</p>", names.join(", "));
+        }
+
+        html.push_str(
+            "<h2>あみだくじ</h2>\n<table>\n<tr><th>景品</th><th>当選者</th><th>受取者</th></tr>\n",
+        );
+        for winner in &self.amida.winners {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape_html(&winner.prize),
+                escape_html(&winner.guest),
+                escape_html(&winner.winner)
+            );
+        }
+        html.push_str("</table>\n");
+        if !self.amida.leftover.is_empty() {
+            let leftover: Vec<String> = self.amida.leftover.iter().map(u8::to_string).collect();
+            let _ = writeln!(html, "<p>未割り当ての景品: {}</p>", leftover.join(", "));
+        }
+        if self.amida.unrevealed > 0 {
+            let _ = writeln!(html, "<p>未公開の枠: {}</p>", self.amida.unrevealed);
+        }
+        if !self.amida.redraws.is_empty() {
+            html.push_str("<h2>欠席による再抽選</h2>\n<table>\n<tr><th>景品</th><th>欠席者</th><th>再抽選の当選者</th></tr>\n");
+            for redraw in &self.amida.redraws {
+                let _ = writeln!(
+                    html,
+                    "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                    redraw.prize,
+                    escape_html(&redraw.absentee),
+                    escape_html(&redraw.winner)
+                );
+            }
+            html.push_str("</table>\n");
+        }
+
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
+
+/// 表計算ソフトで数式として解釈される文字で始まるセルの先頭に `'` を付ける
+///
+/// 参加者名などの利用者が入力した値で、数式を埋め込まれないようにする (CSVインジェクション対策)。
+fn csv_cell(field: &str) -> Cow<'_, str> {
+    if field.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        Cow::Owned(format!("'{}", field))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+/// HTMLの特殊文字をエスケープする
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lifecycle::PhaseAction;
+    use crate::rng::{MersenneTwister, XorShift};
+    use crate::session::round_seed;
+
+    fn sample(revealed: &[u8]) -> ResultsBundle {
+        let mut session = Session::new(1);
+        let mut bingo = BingoGame::new(75, Box::new(XorShift::new(1)));
+        bingo.draw().unwrap();
+        bingo.draw().unwrap();
+        let mut players = PlayerRegistry::new();
+        let alice = players.ensure("=Alice").unwrap();
+        let mut winners = WinnerRegistry::new();
+        winners.set_prizes(vec!["Cake".to_string(), "Candy".to_string()]);
+        let claim = bingo.claim(alice, None, None);
+        winners.record(1, WinPattern::SingleLine, &claim).unwrap();
+        bingo.transition(PhaseAction::Finish).unwrap();
+        session
+            .start_next_round(&mut bingo, WinPattern::Cross, Some("Turkey".to_string()))
+            .unwrap();
+        bingo.draw().unwrap();
+
+        let mut amida = AmidaGame::new(2, Box::new(MersenneTwister::new(1)));
+        let guests = ["<Alice>", "=Bob, Jr."].map(|name| players.ensure(name).ok());
+        amida.update(guests.to_vec()).unwrap();
+        for &prize in revealed {
+            amida.reveal(prize);
+        }
+
+        let amida_seed = amida.is_fully_revealed().then_some(1);
+        ResultsBundle::collect(&session, &bingo, &winners, &amida, &players, 1, amida_seed)
+    }
+
+    #[test]
+    fn test_collect_bundle() {
+        let bundle = sample(&[1]);
+        assert_eq!(bundle.bingo.algorithm, "xorshift32");
+        assert_eq!(bundle.amida.algorithm, "mt19937");
+        // 各ラウンドはそのラウンドのシード値とともに出力する
+        let rounds = &bundle.bingo.rounds;
+        assert_eq!(rounds.len(), 2);
+        assert_eq!(rounds[0].seed, 1);
+        assert_eq!(rounds[0].draws.len(), 2);
+        assert_eq!(rounds[0].draws[1].order, 2);
+        assert_eq!(rounds[1].seed, round_seed(1, 2));
+        assert_eq!(rounds[1].pattern, WinPattern::Cross);
+        assert_eq!(rounds[1].draws.len(), 1);
+        assert_eq!(bundle.bingo.remaining, 74);
+        // ビンゴの当選者はラウンドごとに、まだ渡していない景品とともに出力する
+        assert_eq!(rounds[0].winners.len(), 1);
+        assert_eq!(rounds[0].winners[0].name, "=Alice");
+        assert_eq!(rounds[0].winners[0].winner.draw_index, 2);
+        assert!(rounds[1].winners.is_empty());
+        assert_eq!(bundle.bingo.remaining_prizes.len(), 1);
+        assert!(bundle.to_json().contains("\"prize\": {"));
+        assert_eq!(bundle.amida.winners.len(), 1);
+        assert_eq!(bundle.amida.unrevealed, 1);
+        // 未公開の枠がある間はあみだくじのシード値を出力しない
+        assert_eq!(bundle.amida.seed, None);
+        assert!(!bundle.to_csv().contains("amida_seed"));
+    }
+
+    #[test]
+    fn test_csv_is_quoted_and_has_bom() {
+        let csv = sample(&[1, 2]).to_csv();
+        assert!(csv.starts_with(UTF8_BOM));
+        assert_eq!(csv.lines().filter(|l| l.starts_with("bingo,1,")).count(), 2);
+        assert_eq!(csv.lines().filter(|l| l.starts_with("bingo,2,")).count(), 1);
+        let round_seed = format!("meta,2,,,,,,,seed,{}", round_seed(1, 2));
+        assert!(csv.lines().any(|l| l == round_seed));
+        let winner = csv.lines().find(|l| l.starts_with("winner,1,2,")).unwrap();
+        assert!(winner.contains(",Cake,,'=Alice,"));
+        assert!(winner.ends_with(",share,1"));
+        assert!(csv.lines().any(|l| l == "prize,,1,,Candy,,,,,"));
+        assert_eq!(csv.lines().filter(|l| l.starts_with("amida,")).count(), 2);
+        // 数式として解釈されないように先頭に ' を付ける
+        assert!(csv.contains("\"'=Bob, Jr.\""));
+        assert!(!csv.contains(",=Bob"));
+        assert!(csv
+            .lines()
+            .any(|l| l.ends_with(",seed,1") && l.starts_with("meta,")));
+        assert!(csv.lines().any(|l| l == "meta,,,,,,,,amida_seed,1"));
+    }
+
+    #[test]
+    fn test_html_escapes_names() {
+        let html = sample(&[1, 2]).to_html();
+        assert!(!html.contains("<Alice>"));
+        assert!(html.contains("&lt;Alice&gt;"));
+        assert!(html.contains("</html>"));
+    }
+}
@@ -0,0 +1,114 @@
+use crate::state::AppState;
+use axum::Router;
+use std::fmt;
+use std::path::PathBuf;
+use tower_http::services::{ServeDir, ServeFile};
+
+/// SPAのエントリーポイント
+const INDEX_HTML: &str = "index.html";
+
+/// フロントエンド (ビルド済みのSPA) の配信元
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrontendSource {
+    /// 指定したディレクトリ (`frontend/dist` など) から配信する
+    Dir(PathBuf),
+    /// ビルド時にバイナリへ埋め込んだファイルを配信する
+    Embedded,
+    /// 配信しない (APIのみ)
+    Disabled,
+}
+
+impl FrontendSource {
+    /// フロントエンドを配信するか
+    pub fn is_served(&self) -> bool {
+        *self != Self::Disabled
+    }
+
+    /// 配信するディレクトリが正しいか確認する
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::Dir(dir) if !dir.join(INDEX_HTML).is_file() => {
+                Err(format!("{} does not contain {}", dir.display(), INDEX_HTML))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// APIのルーターにフロントエンドの配信を追加する
+    ///
+    /// 存在しないパスには `index.html` を返し、Vue Router の history モードに対応する。
+    pub fn serve(&self, router: Router<AppState>) -> Router<AppState> {
+        match self {
+            Self::Dir(dir) => router.fallback_service(
+                ServeDir::new(dir).fallback(ServeFile::new(dir.join(INDEX_HTML))),
+            ),
+            #[cfg(feature = "embed-frontend")]
+            Self::Embedded => router.fallback(embedded::serve),
+            #[cfg(not(feature = "embed-frontend"))]
+            Self::Embedded => router,
+            Self::Disabled => router,
+        }
+    }
+}
+
+impl fmt::Display for FrontendSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Dir(dir) => write!(f, "{}", dir.display()),
+            Self::Embedded => write!(f, "(embedded)"),
+            Self::Disabled => write!(f, "(disabled)"),
+        }
+    }
+}
+
+/// バイナリにフロントエンドが埋め込まれているか
+///
+/// `embed-frontend` フィーチャーが無効な場合や、ビルド時に `frontend/dist` がなかった場合は false
+pub fn embedded_available() -> bool {
+    #[cfg(feature = "embed-frontend")]
+    {
+        embedded::Assets::get(INDEX_HTML).is_some()
+    }
+    #[cfg(not(feature = "embed-frontend"))]
+    {
+        false
+    }
+}
+
+#[cfg(feature = "embed-frontend")]
+mod embedded {
+    use super::INDEX_HTML;
+    use axum::{
+        http::{header, StatusCode, Uri},
+        response::{IntoResponse, Response},
+    };
+    use rust_embed::RustEmbed;
+
+    /// ビルド時に埋め込んだ `frontend/dist` の内容
+    #[derive(RustEmbed)]
+    #[folder = "../frontend/dist"]
+    #[allow_missing = true]
+    pub struct Assets;
+
+    /// 埋め込んだファイルを返す
+    ///
+    /// ファイルが見つからず、拡張子のないパス (画面のURL) の場合は `index.html` を返す。
+    pub async fn serve(uri: Uri) -> Response {
+        let path = uri.path().trim_start_matches('/');
+        let path = if path.is_empty() { INDEX_HTML } else { path };
+
+        let (path, file) = match Assets::get(path) {
+            Some(file) => (path, file),
+            None if !path.rsplit('/').next().unwrap_or_default().contains('.') => {
+                match Assets::get(INDEX_HTML) {
+                    Some(file) => (INDEX_HTML, file),
+                    None => return StatusCode::NOT_FOUND.into_response(),
+                }
+            }
+            None => return StatusCode::NOT_FOUND.into_response(),
+        };
+
+        let mime = mime_guess::from_path(path).first_or_octet_stream();
+        ([(header::CONTENT_TYPE, mime.to_string())], file.data).into_response()
+    }
+}
@@ -1,158 +1,2914 @@
+use crate::autodraw::{self, AutoDrawStatus, AutoDrawStopReason};
+use crate::card::{self, BingoCard, CardGrid, CardId};
+use crate::domain::{
+    AmidaConstraintError, AmidaConstraints, AmidaExclusion, AmidaGame, AmidaRedraw,
+    AmidaRedrawError, AmidaRule, AmidaSpread, BingoClaim, BingoVariant, MAX_AMIDA_SLOTS,
+};
+use crate::error::{ApiError, ApiResult, ErrorResponse};
+use crate::export::{ExportFormat, ResultsBundle};
+use crate::import::{
+    import_players, ColumnMapping, ImportFormat, ImportOptions, ImportRowError, TextEncoding,
+};
+use crate::lifecycle::{GamePhase, PhaseAction, PhaseTransition};
+use crate::player::{Player, PlayerChanges, PlayerId, PlayerProfile, PlayerRegistry};
+use crate::qr;
+use crate::session::{Round, RoundChanges, WinPattern};
+use crate::sheet::{self, PrintedCard, SheetFormat};
 use crate::state::AppState;
-use axum::{extract::State, Json};
+use crate::stats::{
+    self, DrawStatistics, DEFAULT_SIMULATIONS, MAX_SIMULATED_CARDS, MAX_SIMULATED_CARD_GAMES,
+    MAX_SIMULATIONS,
+};
+use crate::tiebreak::{TieBreak, TieBreakMethod};
+use crate::winner::{BingoPrize, Winner, WinnerError};
+use axum::{
+    body::Bytes,
+    extract::{
+        rejection::{JsonRejection, PathRejection, QueryRejection},
+        FromRequest, Path, Query, Request, State,
+    },
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::time::Duration;
+use utoipa::{IntoParams, ToSchema};
+
+/// ビンゴのゲームID
+///
+/// 現在は1つのゲームのみを扱います。
+pub const DEFAULT_GAME_ID: &str = "default";
+
+/// ビンゴの抽選状況
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DrawStatus {
+    /// 数字を抽選した
+    Drawn,
+    /// 全ての数字が出尽くした
+    GameOver,
+    /// ゲームをリセットした
+    Reset,
+}
+
+/// あみだくじの設定の状況
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AmidaSettingsStatus {
+    /// 現在の設定を返した
+    Current,
+    /// 設定を更新し、結果が確定した
+    Updated,
+    /// 設定を更新したが、割り当て制約を満たせず結果が確定していない
+    Unsatisfied,
+}
+
+/// あみだくじの結果の公開状況
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AmidaResultStatus {
+    /// 結果が確定していない
+    NotReady,
+    /// 公開中 (未公開の枠がある)
+    InProgress,
+    /// 全ての枠を公開した
+    Complete,
+}
 
 /// ビンゴの抽選結果レスポンス
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct NumberResponse {
     /// 抽選された数字 (Noneの場合は終了)
     pub number: Option<u8>,
     /// これまでの抽選履歴
     pub history: Vec<u8>,
-    /// ステータスメッセージ
-    pub message: String,
+    /// 抽選状況
+    pub status: DrawStatus,
+    /// 使用されているシード値
+    pub seed: u32,
+}
+
+/// ビンゴのゲームの状態レスポンス
+#[derive(Serialize, ToSchema)]
+pub struct GameResponse {
+    /// ゲームID
+    pub id: String,
+    /// 進行中のラウンド番号
+    pub round: u32,
+    /// 数字の最大値
+    pub max_number: usize,
+    /// これまでの抽選履歴
+    pub history: Vec<u8>,
+    /// 残りの数字の個数
+    pub remaining: usize,
+    /// 全ての数字が出尽くしたかどうか
+    pub finished: bool,
+    /// 現在のフェーズ
+    pub phase: GamePhase,
+    /// 現在のフェーズで許可される操作
+    pub actions: Vec<PhaseAction>,
+    /// これまでのフェーズの遷移 (古い順)
+    pub transitions: Vec<PhaseTransition>,
+    /// 進行中のラウンドのシード値
+    pub seed: u32,
+}
+
+/// ビンゴのゲームの表示用の状態レスポンス
+#[derive(Serialize, ToSchema)]
+pub struct GameStatusResponse {
+    /// ゲームID
+    pub id: String,
+    /// 進行中のラウンド番号
+    pub round: u32,
+    /// 状態の版数 (抽選やリセットのたびに増える)
+    pub version: u64,
+    /// ビンゴの種類
+    pub variant: BingoVariant,
+    /// 現在のフェーズ
+    pub phase: GamePhase,
+    /// 現在のフェーズで許可される操作
+    pub actions: Vec<PhaseAction>,
+    /// 最後に抽選された数字
+    pub last: Option<u8>,
+    /// これまでの抽選履歴
+    pub history: Vec<u8>,
+    /// 残りの数字の個数
+    pub remaining: usize,
     /// 使用されているシード値
     pub seed: u32,
 }
 
+/// フェーズ遷移リクエスト
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct PhaseTransitionRequest {
+    /// 行う操作
+    pub action: PhaseAction,
+}
+
+/// 自動抽選の開始リクエスト
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct AutoDrawRequest {
+    /// 抽選間隔 (秒、省略時は前回の間隔または設定の値)
+    #[serde(default)]
+    pub interval_secs: Option<u64>,
+}
+
+/// 自動抽選の変更リクエスト
+///
+/// 省略した項目は現在の値を維持します。
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct AutoDrawPatchRequest {
+    /// 抽選間隔 (秒)
+    #[serde(default)]
+    pub interval_secs: Option<u64>,
+    /// 一時停止するか (`false` で再開)
+    #[serde(default)]
+    pub paused: Option<bool>,
+}
+
+/// ビンゴの申告リクエスト
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ClaimRequest {
+    /// 申告した参加者のID
+    pub player: PlayerId,
+}
+
+/// ビンゴの申告レスポンス
+#[derive(Serialize, ToSchema)]
+pub struct ClaimResponse {
+    /// 記録した申告
+    pub claim: BingoClaim,
+    /// 申告後の自動抽選の状況 (申告があると停止する)
+    pub auto_draw: AutoDrawStatus,
+    /// 記録した当選者 (当たりの形を確認していない申告や、すでに当選している参加者の申告はnull)
+    pub winner: Option<Winner>,
+}
+
+/// 当選者の記録リクエスト
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct WinnerRequest {
+    /// 司会者が当たりの形を確認した参加者のID
+    pub player: PlayerId,
+}
+
+/// ビンゴの景品の登録リクエスト
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct PrizesRequest {
+    /// まだ渡していない景品の名前 (渡す順)
+    pub prizes: Vec<String>,
+}
+
+/// ビンゴの景品と当選者のレスポンス
+#[derive(Serialize, ToSchema)]
+pub struct WinnersResponse {
+    /// まだ渡していない景品 (渡す順)
+    pub remaining: Vec<BingoPrize>,
+    /// これまでの当選者 (記録順)
+    pub winners: Vec<Winner>,
+}
+
+/// 同じ数字で揃った当選者のくじ引きリクエスト
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct TieBreakRequest {
+    /// 順位の決め方 (省略時は `random_order`)
+    #[serde(default)]
+    pub method: TieBreakMethod,
+    /// 当たりの形が揃った数字の抽選順 (省略時は進行中のラウンドで最後に揃ったもの)
+    #[serde(default)]
+    pub draw_index: Option<usize>,
+}
+
+/// くじ引きのレスポンス
+#[derive(Serialize, ToSchema)]
+pub struct TieBreakResponse {
+    /// くじ引きの記録
+    pub tie_break: TieBreak,
+    /// 景品を分け直した当選者 (順位順)
+    pub winners: Vec<Winner>,
+}
+
+/// くじ引きの記録の一覧レスポンス
+#[derive(Serialize, ToSchema)]
+pub struct TieBreaksResponse {
+    /// 全てのラウンドのくじ引きの記録 (ラウンド順・くじ引きをした順)
+    pub tie_breaks: Vec<TieBreak>,
+}
+
+/// ビンゴのセッションの概要レスポンス
+#[derive(Serialize, ToSchema)]
+pub struct SessionResponse {
+    /// セッションのシード値 (各ラウンドのシード値はここから求める)
+    pub seed: u32,
+    /// 進行中のラウンド番号
+    pub current_round: u32,
+    /// 全てのラウンドの概要 (古い順、最後が進行中のラウンド)
+    pub rounds: Vec<RoundSummary>,
+}
+
+/// ラウンドの概要
+#[derive(Serialize, ToSchema)]
+pub struct RoundSummary {
+    /// ラウンド番号
+    pub number: u32,
+    /// 当たりの形
+    pub pattern: WinPattern,
+    /// 景品
+    pub prize: Option<String>,
+    /// シード値
+    pub seed: u32,
+    /// フェーズ
+    pub phase: GamePhase,
+    /// 抽選した数字の個数
+    pub draws: usize,
+    /// 当選者の参加者ID
+    pub winners: Vec<PlayerId>,
+    /// 開始した日時
+    #[schema(value_type = String, format = DateTime)]
+    pub started_at: chrono::DateTime<chrono::Local>,
+    /// 終えた日時 (進行中のラウンドはNone)
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub ended_at: Option<chrono::DateTime<chrono::Local>>,
+}
+
+impl RoundSummary {
+    /// ラウンドの記録と当選者から概要を作る
+    fn new(round: &Round, winners: Vec<PlayerId>) -> Self {
+        Self {
+            number: round.settings.number,
+            pattern: round.settings.pattern,
+            prize: round.settings.prize.clone(),
+            seed: round.settings.seed,
+            phase: round.phase,
+            draws: round.history.len(),
+            winners,
+            started_at: round.settings.started_at,
+            ended_at: round.ended_at,
+        }
+    }
+}
+
+/// ラウンドの記録レスポンス
+#[derive(Serialize, ToSchema)]
+pub struct RoundResponse {
+    /// ラウンドの記録
+    #[serde(flatten)]
+    pub round: Round,
+    /// 当選者の参加者ID (当選者の一覧から求める、記録順)
+    pub winners: Vec<PlayerId>,
+}
+
+/// 新しいラウンドの開始リクエスト
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct RoundRequest {
+    /// 当たりの形 (省略時は `single_line`)
+    #[serde(default)]
+    pub pattern: WinPattern,
+    /// 景品
+    #[serde(default)]
+    pub prize: Option<String>,
+}
+
+/// ラウンドの設定の変更リクエスト
+///
+/// 省略した項目は現在の値を維持します。
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct RoundPatchRequest {
+    /// 当たりの形
+    #[serde(default)]
+    pub pattern: Option<WinPattern>,
+    /// 景品 (空文字列で消去)
+    #[serde(default)]
+    pub prize: Option<String>,
+    /// 当選者の参加者ID (ラウンドの申告から当選者の一覧に記録し、含まれない当選者の記録は取り消す)
+    #[serde(default)]
+    pub winners: Option<Vec<PlayerId>>,
+}
+
+/// 参加者名簿レスポンス
+#[derive(Serialize, ToSchema)]
+pub struct PlayersResponse {
+    /// 登録されている参加者 (登録順)
+    pub players: Vec<Player>,
+}
+
+/// 参加者の情報の変更リクエスト
+///
+/// 省略した項目は現在の値を維持します。表示名以外は空文字列で消去します。
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct PlayerPatchRequest {
+    /// 表示名
+    #[serde(default)]
+    pub name: Option<String>,
+    /// ふりがな
+    #[serde(default)]
+    pub furigana: Option<String>,
+    /// 所属チーム
+    #[serde(default)]
+    pub team: Option<String>,
+    /// アバターの絵文字
+    #[serde(default)]
+    pub avatar: Option<String>,
+}
+
+/// ビンゴカードの一覧レスポンス
+#[derive(Serialize, ToSchema)]
+pub struct CardsResponse {
+    /// 発行したカード (発行順)
+    pub cards: Vec<BingoCard>,
+}
+
+/// ビンゴカードの発行リクエスト
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct CardRequest {
+    /// カードを渡す参加者のID
+    pub player: PlayerId,
+}
+
+/// 印刷用シートのクエリパラメータ
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CardSheetQuery {
+    /// 出力形式 (`pdf` / `svg`、省略時は `pdf`)
+    #[serde(default)]
+    pub format: SheetFormat,
+    /// SVGで出力するページ (1から、省略時は1ページ目)
+    pub page: Option<usize>,
+}
+
+/// 抽選の統計のクエリパラメータ
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StatisticsQuery {
+    /// 当選確率のシミュレーションの回数 (1〜10000、省略時は1000。仮想カードの枚数との積は100000まで)
+    pub simulations: Option<usize>,
+    /// 当選確率のシミュレーションの仮想カードの枚数 (0〜1000、省略時は発行済みのカードの枚数)
+    pub cards: Option<usize>,
+}
+
+/// 印刷したカードの確認リクエスト
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct CardVerificationRequest {
+    /// カードのQRコードから読み取った内容 (`XMASBINGO:<カードID>:<署名>`)
+    pub payload: String,
+}
+
+/// 印刷したカードの確認結果
+///
+/// カードの数字に抽選履歴を重ね、当たりの形を作るマスを示します。
+#[derive(Serialize, ToSchema)]
+pub struct CardVerificationResponse {
+    /// カードID
+    pub card: CardId,
+    /// マスの数字 (`[行][列]`、中央のフリーマスはnull)
+    #[schema(value_type = Vec<Vec<Option<u8>>>)]
+    pub numbers: CardGrid<Option<u8>>,
+    /// カードを発行した参加者 (サーバーを起動せずに印刷したカードはnull)
+    pub player: Option<Player>,
+    /// 進行中のラウンド番号
+    pub round: u32,
+    /// 進行中のラウンドの当たりの形
+    pub pattern: WinPattern,
+    /// ゲームのフェーズ
+    pub phase: GamePhase,
+    /// 最後に抽選された数字
+    pub last: Option<u8>,
+    /// 抽選済みの数字の個数
+    pub draw_count: usize,
+    /// 印の付いたマス (`[行][列]`、フリーマスは常にtrue)
+    #[schema(value_type = Vec<Vec<bool>>)]
+    pub marks: CardGrid<bool>,
+    /// 当たりの形を作るマス (揃っていない場合はnull)
+    #[schema(value_type = Option<Vec<Vec<bool>>>)]
+    pub winning: Option<CardGrid<bool>>,
+    /// このカードで申告済みかどうか
+    pub claimed: bool,
+}
+
+/// 参加者のスマートフォンに表示するビンゴカードの状態
+///
+/// 抽選済みの数字の印はサーバーが付けます。
+#[derive(Serialize, ToSchema)]
+pub struct PlayerCardResponse {
+    /// ビンゴカード
+    pub card: BingoCard,
+    /// カードを持つ参加者
+    pub player: Player,
+    /// 進行中のラウンド番号
+    pub round: u32,
+    /// 進行中のラウンドの当たりの形
+    pub pattern: WinPattern,
+    /// ゲームのフェーズ
+    pub phase: GamePhase,
+    /// ゲームの状態の版数
+    pub version: u64,
+    /// 最後に抽選された数字
+    pub last: Option<u8>,
+    /// 抽選済みの数字の個数
+    pub draw_count: usize,
+    /// 印の付いたマス (`[行][列]`、フリーマスは常にtrue)
+    #[schema(value_type = Vec<Vec<bool>>)]
+    pub marks: CardGrid<bool>,
+    /// 当たりの形を作るマス (揃っていない場合はnull)
+    #[schema(value_type = Option<Vec<Vec<bool>>>)]
+    pub winning: Option<CardGrid<bool>>,
+    /// このカードで申告済みかどうか
+    pub claimed: bool,
+}
+
 /// あみだくじ設定リクエスト
-#[derive(Serialize, Deserialize)]
+///
+/// `PUT` では省略した割り当てルール・制約はデフォルト値になり、
+/// 旧API (`POST /amida`) では現在の値を維持します。
+#[derive(Serialize, Deserialize, ToSchema)]
 pub struct AmidaRequest {
-    /// 参加者名のリスト
-    pub items: Vec<String>,
+    /// 参加者名のリスト (名簿にない参加者は名簿に登録する。`players` を指定しない場合は必須)
+    #[serde(default)]
+    pub items: Option<Vec<String>>,
+    /// 参加者IDのリスト (指定した場合は `items` の代わりに使う)
+    #[serde(default)]
+    pub players: Option<Vec<PlayerId>>,
+    /// 割り当てルール
+    #[serde(default)]
+    pub rule: Option<AmidaRule>,
+    /// 割り当て制約
+    #[serde(default)]
+    pub constraints: Option<AmidaConstraintsSpec>,
+}
+
+/// あみだくじ設定の部分更新リクエスト
+///
+/// 省略した項目は現在の値を維持します。
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct AmidaPatchRequest {
+    /// 参加者名のリスト (名簿にない参加者は名簿に登録する)
+    #[serde(default)]
+    pub items: Option<Vec<String>>,
+    /// 参加者IDのリスト (指定した場合は `items` の代わりに使う)
+    #[serde(default)]
+    pub players: Option<Vec<PlayerId>>,
+    /// 割り当てルール
+    #[serde(default)]
+    pub rule: Option<AmidaRule>,
+    /// 割り当て制約
+    #[serde(default)]
+    pub constraints: Option<AmidaConstraintsSpec>,
+}
+
+/// 特定の参加者に特定の景品を割り当てない制約
+///
+/// 例: 景品の提供者が自分の景品を受け取らないようにする
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct AmidaExclusionSpec {
+    /// 参加者名 (名簿にない参加者は名簿に登録する。`player` を指定しない場合は必須)
+    #[serde(default)]
+    pub guest: Option<String>,
+    /// 参加者ID (指定した場合は `guest` の代わりに使う)
+    #[serde(default)]
+    pub player: Option<PlayerId>,
+    /// 割り当てない景品番号
+    pub prize: u8,
+}
+
+/// グループ内で特定の景品が偏らないようにする制約
+///
+/// 例: 同じチームのメンバーが上位賞を独占しないようにする
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct AmidaSpreadSpec {
+    /// グループに属する参加者名 (名簿にない参加者は名簿に登録する)
+    #[serde(default)]
+    pub members: Vec<String>,
+    /// グループに属する参加者のID (指定した場合は `members` の代わりに使う)
+    #[serde(default)]
+    pub players: Option<Vec<PlayerId>>,
+    /// 対象の景品番号
+    pub prizes: Vec<u8>,
+    /// グループ全体で獲得できる対象景品の最大数
+    pub max: usize,
+}
+
+/// あみだくじの割り当て制約
+///
+/// 参加者は参加者名または参加者IDで指定し、レスポンスには両方を返します。
+#[derive(Default, Serialize, Deserialize, ToSchema)]
+pub struct AmidaConstraintsSpec {
+    /// 除外ペアのリスト
+    #[serde(default)]
+    pub exclusions: Vec<AmidaExclusionSpec>,
+    /// グループ分散ルールのリスト
+    #[serde(default)]
+    pub spreads: Vec<AmidaSpreadSpec>,
+}
+
+/// 欠席者の景品を再抽選した記録
+#[derive(Serialize, ToSchema)]
+pub struct AmidaRedrawRecord {
+    /// 再抽選した景品番号
+    pub prize: u8,
+    /// 欠席した元の当選者の参加者名
+    pub absentee: String,
+    /// 欠席した元の当選者のID
+    pub absentee_player: PlayerId,
+    /// 新しい当選者の参加者名
+    pub winner: String,
+    /// 新しい当選者のID
+    pub winner_player: PlayerId,
+    /// 再抽選に使用したサブストリーム番号
+    pub stream: u32,
 }
 
 /// あみだくじ設定レスポンス
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct AmidaResponse {
     /// 現在の参加者名リスト
     pub items: Vec<String>,
+    /// 各枠の参加者ID (`items` と同じ順、名簿にない参加者はNone)
+    pub players: Vec<Option<PlayerId>>,
     /// 景品の数
     pub prize_count: usize,
-    /// ステータスメッセージ
-    pub message: String,
-    /// 使用されているシード値
-    pub seed: u32,
+    /// 割り当てルール
+    pub rule: AmidaRule,
+    /// 割り当て制約
+    pub constraints: AmidaConstraintsSpec,
+    /// 割り当て制約を満たせない場合の理由
+    pub constraint_error: Option<AmidaConstraintError>,
+    /// 設定の状況
+    pub status: AmidaSettingsStatus,
+    /// あみだくじのシード値 (割り当てのある枠を全て公開するまではNone)
+    pub seed: Option<u32>,
+}
+
+/// あみだくじ結果公開リクエスト
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct AmidaRevealRequest {
+    /// 公開する枠番号
+    pub prize: u8,
+}
+
+/// あみだくじ結果公開レスポンス
+#[derive(Serialize, ToSchema)]
+pub struct AmidaRevealResponse {
+    /// 公開された (景品番号, 参加者名) のペア
+    #[schema(value_type = Vec<String>, min_items = 2, max_items = 2, example = json!(["3", "Guest C"]))]
+    pub item: (String, String),
+    /// 公開された参加者のID (名簿にない参加者はNone)
+    pub player: Option<PlayerId>,
+    /// 公開済みの枠数
+    pub revealed_count: usize,
+    /// 割り当てのある枠の総数
+    pub total: usize,
+    /// 公開状況
+    pub status: AmidaResultStatus,
+    /// あみだくじのシード値 (割り当てのある枠を全て公開するまではNone)
+    pub seed: Option<u32>,
+}
+
+/// あみだくじ欠席登録リクエスト
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct AmidaAbsentRequest {
+    /// 欠席した参加者名
+    #[serde(default)]
+    pub guest: String,
+    /// 欠席した参加者のID (指定した場合は `guest` の代わりに使う)
+    #[serde(default)]
+    pub player: Option<PlayerId>,
+}
+
+/// あみだくじ欠席登録レスポンス
+#[derive(Serialize, ToSchema)]
+pub struct AmidaAbsentResponse {
+    /// 今回行った再抽選の記録
+    pub redraws: Vec<AmidaRedrawRecord>,
+    /// 欠席者の参加者名リスト
+    pub absentees: Vec<String>,
+    /// 欠席者の参加者ID (`absentees` と同じ順)
+    pub absentee_players: Vec<PlayerId>,
+    /// あみだくじのシード値 (割り当てのある枠を全て公開するまではNone)
+    pub seed: Option<u32>,
+}
+
+/// 参加者リスト取り込みのクエリパラメータ
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ImportQuery {
+    /// 区切り文字 (`csv` / `tsv`、省略時は自動判定)
+    pub format: Option<ImportFormat>,
+    /// 文字コード (`utf8` / `shift_jis`、省略時は自動判定)
+    pub encoding: Option<TextEncoding>,
+    /// 名前列のヘッダー名
+    pub name: Option<String>,
+    /// チーム列のヘッダー名
+    pub team: Option<String>,
+    /// ふりがな列のヘッダー名
+    pub furigana: Option<String>,
+}
+
+/// 参加者リスト取り込みレスポンス
+#[derive(Serialize, ToSchema)]
+pub struct ImportResponse {
+    /// 取り込んだ参加者名リスト
+    pub items: Vec<String>,
+    /// 取り込めなかった行
+    pub errors: Vec<ImportRowError>,
+    /// 使用した区切り文字
+    pub format: ImportFormat,
+    /// 使用した文字コード
+    pub encoding: TextEncoding,
+    /// 景品の数
+    pub prize_count: usize,
+    /// 割り当て制約を満たせない場合の理由
+    pub constraint_error: Option<AmidaConstraintError>,
+    /// あみだくじの設定の状況
+    pub status: AmidaSettingsStatus,
+    /// あみだくじのシード値 (割り当てのある枠を全て公開するまではNone)
+    pub seed: Option<u32>,
+}
+
+/// 結果一式ダウンロードのクエリパラメータ
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportQuery {
+    /// 出力形式 (`json` / `csv` / `html`、省略時は `json`)
+    #[serde(default)]
+    pub format: ExportFormat,
 }
 
 /// あみだくじ結果レスポンス
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct AmidaResultResponse {
-    /// (参加者名, 景品番号) のペアリスト
+    /// 公開済みの (景品番号, 参加者名) のペアリスト (公開順)
+    #[schema(value_type = Vec<Vec<String>>, example = json!([["3", "Guest C"], ["1", "Guest A"]]))]
     pub items: Vec<(String, String)>,
-    /// ステータスメッセージ
-    pub message: String,
-    /// 使用されているシード値
-    pub seed: u32,
+    /// 公開済みのペアの参加者ID (`items` と同じ順、名簿にない参加者はNone)
+    pub players: Vec<Option<PlayerId>>,
+    /// 結果が確定しているかどうか (参加者の設定が完了しているか)
+    pub ready: bool,
+    /// 割り当てのある枠の総数
+    pub total: usize,
+    /// 誰にも割り当てられなかった景品番号
+    pub leftover: Vec<u8>,
+    /// 適用された割り当てルール
+    pub rule: AmidaRule,
+    /// 欠席者の参加者名リスト
+    pub absentees: Vec<String>,
+    /// 欠席者の参加者ID (`absentees` と同じ順)
+    pub absentee_players: Vec<PlayerId>,
+    /// 欠席者の景品の再抽選記録 (元の結果は `items` に残る)
+    pub redraws: Vec<AmidaRedrawRecord>,
+    /// 公開状況
+    pub status: AmidaResultStatus,
+    /// あみだくじのシード値 (割り当てのある枠を全て公開するまではNone)
+    pub seed: Option<u32>,
 }
 
-/// 次のビンゴ番号を抽選する
+/// ビンゴのゲームの状態を取得する
 ///
 /// # Arguments
 /// * `state` - アプリケーション状態
+/// * `id` - ゲームID
 ///
 /// # Returns
-/// * `Json<NumberResponse>` - 抽選結果
-pub async fn get_next_number(State(state): State<AppState>) -> Json<NumberResponse> {
-    let mut game = state.game.lock().unwrap();
+/// * `ApiResult<Json<GameResponse>>` - ゲームの状態
+#[utoipa::path(
+    get,
+    path = "/v1/games/{id}",
+    tag = "bingo",
+    params(("id" = String, Path, description = "ゲームID (現在は `default` のみ)")),
+    responses(
+        (status = 200, description = "ゲームの状態", body = GameResponse),
+        (status = 404, description = "存在しないゲーム (`game_not_found`)", body = ErrorResponse),
+    )
+)]
+pub async fn get_game(
+    State(state): State<AppState>,
+    id: Result<Path<String>, PathRejection>,
+) -> ApiResult<Json<GameResponse>> {
+    let id = game_id(id)?;
+    Ok(Json(game_response(&state, id)))
+}
 
-    if let Some(num) = game.get_next_number() {
-        Json(NumberResponse {
-            number: Some(num),
+/// ビンゴのゲームの表示用の状態を取得する
+///
+/// 状態を変更せずに、抽選履歴・最後の数字・残りの個数・進行状況を返します。
+/// 会場のモニターなどが途中から表示を再開したり、定期的に状態を確認するために使います。
+/// レスポンスの `ETag` を `If-None-Match` に指定すると、変化がない場合は `304 Not Modified` を返します。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+/// * `id` - ゲームID
+/// * `headers` - リクエストヘッダー (`If-None-Match`)
+///
+/// # Returns
+/// * `ApiResult<Response>` - ゲームの状態 (変化がない場合は `304`)
+#[utoipa::path(
+    get,
+    path = "/v1/games/{id}/status",
+    tag = "bingo",
+    params(
+        ("id" = String, Path, description = "ゲームID (現在は `default` のみ)"),
+        ("If-None-Match" = Option<String>, Header, description = "前回のレスポンスの `ETag`"),
+    ),
+    responses(
+        (status = 200, description = "ゲームの状態", body = GameStatusResponse,
+            headers(("ETag" = String, description = "状態を識別する値"))),
+        (status = 304, description = "前回から変化していない",
+            headers(("ETag" = String, description = "状態を識別する値"))),
+        (status = 404, description = "存在しないゲーム (`game_not_found`)", body = ErrorResponse),
+    )
+)]
+pub async fn get_game_status(
+    State(state): State<AppState>,
+    id: Result<Path<String>, PathRejection>,
+    headers: HeaderMap,
+) -> ApiResult<Response> {
+    let id = game_id(id)?;
+    let status = {
+        let session = state.session();
+        let game = state.game();
+        GameStatusResponse {
+            id,
+            round: session.current().number,
+            version: game.version(),
+            variant: game.variant(),
+            phase: game.phase(),
+            actions: game.phase().actions(),
+            last: game.history.last().copied(),
             history: game.history.clone(),
-            message: "Success".to_string(),
-            seed: state.seed,
-        })
-    } else {
+            remaining: game.remaining_numbers.len(),
+            seed: session.current().seed,
+        }
+    };
+
+    let body = serde_json::to_vec(&status).expect("status is always serializable");
+    Ok(conditional_json(&headers, body))
+}
+
+/// 次のビンゴ番号を抽選する
+///
+/// 抽選した数字を抽選履歴 (`draws`) に追加します。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+/// * `id` - ゲームID
+///
+/// # Returns
+/// * `ApiResult<(StatusCode, Json<NumberResponse>)>` - 抽選結果 (`201 Created`)
+///   (全ての数字が出尽くしている場合は `game_over`、抽選できないフェーズの場合は `invalid_phase`)
+#[utoipa::path(
+    post,
+    path = "/v1/games/{id}/draws",
+    tag = "bingo",
+    security(("admin_token" = []), ("bearer" = [])),
+    params(("id" = String, Path, description = "ゲームID (現在は `default` のみ)")),
+    responses(
+        (status = 201, description = "抽選結果", body = NumberResponse),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+        (status = 404, description = "存在しないゲーム (`game_not_found`)", body = ErrorResponse),
+        (status = 409, description = "全ての数字が出尽くしている (`game_over`)、または抽選できないフェーズ (`invalid_phase`)", body = ErrorResponse),
+    )
+)]
+pub async fn create_draw(
+    State(state): State<AppState>,
+    id: Result<Path<String>, PathRejection>,
+) -> ApiResult<(StatusCode, Json<NumberResponse>)> {
+    game_id(id)?;
+    let seed = state.round_seed();
+    let mut game = state.game();
+    let number = game.draw()?.ok_or(ApiError::GameOver)?;
+    Ok((
+        StatusCode::CREATED,
         Json(NumberResponse {
-            number: None,
+            number: Some(number),
             history: game.history.clone(),
-            message: "Game Over".to_string(),
-            seed: state.seed,
-        })
+            status: DrawStatus::Drawn,
+            seed,
+        }),
+    ))
+}
+
+/// ビンゴの抽選履歴を消去し、ゲームをリセットする
+///
+/// フェーズは準備中 (`setup`) に戻ります。保管済みのゲームはリセットできません。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+/// * `id` - ゲームID
+///
+/// # Returns
+/// * `ApiResult<Json<GameResponse>>` - リセット後のゲームの状態
+#[utoipa::path(
+    delete,
+    path = "/v1/games/{id}/draws",
+    tag = "bingo",
+    security(("admin_token" = []), ("bearer" = [])),
+    params(("id" = String, Path, description = "ゲームID (現在は `default` のみ)")),
+    responses(
+        (status = 200, description = "リセット後のゲームの状態", body = GameResponse),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+        (status = 404, description = "存在しないゲーム (`game_not_found`)", body = ErrorResponse),
+        (status = 409, description = "保管済みのゲーム (`invalid_phase`)", body = ErrorResponse),
+    )
+)]
+pub async fn delete_draws(
+    State(state): State<AppState>,
+    id: Result<Path<String>, PathRejection>,
+) -> ApiResult<Json<GameResponse>> {
+    let id = game_id(id)?;
+    state.reset_game()?;
+    Ok(Json(game_response(&state, id)))
+}
+
+/// ゲームのフェーズを遷移する
+///
+/// 遷移は日時とともに記録され、ゲームの状態の `transitions` で確認できます。
+/// 現在のフェーズで許可される操作は `actions` で確認できます。
+/// 終了 (`finish`)・リセット (`reset`) した場合、自動抽選は停止します。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+/// * `id` - ゲームID
+/// * `payload` - 行う操作
+///
+/// # Returns
+/// * `ApiResult<Json<GameResponse>>` - 遷移後のゲームの状態
+///   (現在のフェーズで許可されない操作の場合は `invalid_phase`)
+#[utoipa::path(
+    post,
+    path = "/v1/games/{id}/transitions",
+    tag = "bingo",
+    security(("admin_token" = []), ("bearer" = [])),
+    params(("id" = String, Path, description = "ゲームID (現在は `default` のみ)")),
+    request_body = PhaseTransitionRequest,
+    responses(
+        (status = 200, description = "遷移後のゲームの状態", body = GameResponse),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+        (status = 404, description = "存在しないゲーム (`game_not_found`)", body = ErrorResponse),
+        (status = 409, description = "許可されない操作 (`invalid_phase`)", body = ErrorResponse),
+    )
+)]
+pub async fn create_transition(
+    State(state): State<AppState>,
+    id: Result<Path<String>, PathRejection>,
+    payload: Result<Json<PhaseTransitionRequest>, JsonRejection>,
+) -> ApiResult<Json<GameResponse>> {
+    let id = game_id(id)?;
+    let Json(payload) = payload?;
+    if payload.action == PhaseAction::Reset {
+        state.reset_game()?;
+    } else {
+        let phase = state.game().transition(payload.action)?;
+        if !phase.can_draw() && phase != GamePhase::Paused {
+            state.auto_draw.stop(AutoDrawStopReason::PhaseChanged);
+        }
     }
+    Ok(Json(game_response(&state, id)))
 }
 
-/// ゲームをリセットする
+/// ビンゴの申告を記録する
+///
+/// 「ビンゴ!」の声が上がったことを記録し、司会者が確認できるように自動抽選を停止します。
 ///
 /// # Arguments
 /// * `state` - アプリケーション状態
+/// * `id` - ゲームID
+/// * `payload` - 申告した参加者
 ///
 /// # Returns
-/// * `Json<NumberResponse>` - リセット後の状態
-pub async fn reset_game(State(state): State<AppState>) -> Json<NumberResponse> {
-    let mut game = state.game.lock().unwrap();
-    game.reset();
+/// * `ApiResult<(StatusCode, Json<ClaimResponse>)>` - 記録した申告 (`201 Created`)
+#[utoipa::path(
+    post,
+    path = "/v1/games/{id}/claims",
+    tag = "bingo",
+    security(("admin_token" = []), ("bearer" = [])),
+    params(("id" = String, Path, description = "ゲームID (現在は `default` のみ)")),
+    request_body = ClaimRequest,
+    responses(
+        (status = 201, description = "記録した申告", body = ClaimResponse),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+        (status = 404, description = "存在しないゲーム・参加者 (`game_not_found` / `player_not_found`)", body = ErrorResponse),
+    )
+)]
+pub async fn create_claim(
+    State(state): State<AppState>,
+    id: Result<Path<String>, PathRejection>,
+    payload: Result<Json<ClaimRequest>, JsonRejection>,
+) -> ApiResult<(StatusCode, Json<ClaimResponse>)> {
+    game_id(id)?;
+    let Json(payload) = payload?;
+    state.players().get(payload.player)?;
+    let claim = state.game().claim(payload.player, None, None);
+    let auto_draw = state.auto_draw.stop(AutoDrawStopReason::Claimed);
+    Ok((
+        StatusCode::CREATED,
+        Json(ClaimResponse {
+            claim,
+            auto_draw,
+            winner: None,
+        }),
+    ))
+}
 
-    Json(NumberResponse {
-        number: None,
-        history: Vec::new(),
-        message: "Game Reset".to_string(),
-        seed: state.seed,
-    })
+/// 自動抽選の状況を取得する
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+/// * `id` - ゲームID
+///
+/// # Returns
+/// * `ApiResult<Json<AutoDrawStatus>>` - 自動抽選の状況
+#[utoipa::path(
+    get,
+    path = "/v1/games/{id}/auto-draw",
+    tag = "bingo",
+    params(("id" = String, Path, description = "ゲームID (現在は `default` のみ)")),
+    responses(
+        (status = 200, description = "自動抽選の状況", body = AutoDrawStatus),
+        (status = 404, description = "存在しないゲーム (`game_not_found`)", body = ErrorResponse),
+    )
+)]
+pub async fn get_auto_draw(
+    State(state): State<AppState>,
+    id: Result<Path<String>, PathRejection>,
+) -> ApiResult<Json<AutoDrawStatus>> {
+    game_id(id)?;
+    Ok(Json(state.auto_draw.status()))
 }
 
-/// あみだくじの設定を取得する
+/// 自動抽選を開始する
 ///
-/// 現在設定されている参加者名リストを返します。
+/// サーバーが一定間隔で次の数字を抽選します。
 ///
 /// # Arguments
 /// * `state` - アプリケーション状態
+/// * `id` - ゲームID
+/// * `payload` - 抽選間隔
 ///
 /// # Returns
-/// * `Json<AmidaResponse>` - 現在の設定
-pub async fn get_amida(State(state): State<AppState>) -> Json<AmidaResponse> {
-    let amida = state.amida.lock().unwrap();
-    Json(AmidaResponse {
-        items: amida.gests.clone(),
-        prize_count: amida.count,
-        message: "Success".to_string(),
-        seed: state.seed,
-    })
+/// * `ApiResult<(StatusCode, Json<AutoDrawStatus>)>` - 開始後の状況 (`201 Created`)
+///   (すでに自動抽選中の場合は `auto_draw_active`、抽選できないフェーズの場合は `invalid_phase`)
+#[utoipa::path(
+    post,
+    path = "/v1/games/{id}/auto-draw",
+    tag = "bingo",
+    security(("admin_token" = []), ("bearer" = [])),
+    params(("id" = String, Path, description = "ゲームID (現在は `default` のみ)")),
+    request_body = AutoDrawRequest,
+    responses(
+        (status = 201, description = "開始後の状況", body = AutoDrawStatus),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+        (status = 404, description = "存在しないゲーム (`game_not_found`)", body = ErrorResponse),
+        (status = 409, description = "すでに自動抽選中 (`auto_draw_active`)、または抽選できないフェーズ (`invalid_phase`)", body = ErrorResponse),
+        (status = 422, description = "抽選間隔が範囲外 (`invalid_request`)", body = ErrorResponse),
+    )
+)]
+pub async fn start_auto_draw(
+    State(state): State<AppState>,
+    id: Result<Path<String>, PathRejection>,
+    payload: Result<Json<AutoDrawRequest>, JsonRejection>,
+) -> ApiResult<(StatusCode, Json<AutoDrawStatus>)> {
+    game_id(id)?;
+    let Json(payload) = payload?;
+    let interval = interval(payload.interval_secs)?;
+    let status = state.auto_draw.start(state.game.clone(), interval)?;
+    Ok((StatusCode::CREATED, Json(status)))
 }
 
-/// あみだくじの設定を更新する
+/// 自動抽選の間隔を変更する・一時停止する・再開する
 ///
-/// クライアントから送信された参加者名リストでサーバーの状態を更新します。
+/// 間隔を変更した場合や再開した場合は、その時点から間隔を計測し直します。
 ///
 /// # Arguments
 /// * `state` - アプリケーション状態
-/// * `payload` - 更新する参加者リスト
+/// * `id` - ゲームID
+/// * `payload` - 変更内容
 ///
 /// # Returns
-/// * `Json<AmidaResponse>` - 更新後の設定
-pub async fn set_amida(
+/// * `ApiResult<Json<AutoDrawStatus>>` - 変更後の状況 (自動抽選中でない場合は `auto_draw_inactive`)
+#[utoipa::path(
+    patch,
+    path = "/v1/games/{id}/auto-draw",
+    tag = "bingo",
+    security(("admin_token" = []), ("bearer" = [])),
+    params(("id" = String, Path, description = "ゲームID (現在は `default` のみ)")),
+    request_body = AutoDrawPatchRequest,
+    responses(
+        (status = 200, description = "変更後の状況", body = AutoDrawStatus),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+        (status = 404, description = "存在しないゲーム (`game_not_found`)", body = ErrorResponse),
+        (status = 409, description = "自動抽選中でない (`auto_draw_inactive`)", body = ErrorResponse),
+        (status = 422, description = "抽選間隔が範囲外 (`invalid_request`)", body = ErrorResponse),
+    )
+)]
+pub async fn update_auto_draw(
     State(state): State<AppState>,
-    Json(payload): Json<AmidaRequest>,
-) -> Json<AmidaResponse> {
-    let mut amida = state.amida.lock().unwrap();
-    amida.update(payload.items);
-    Json(AmidaResponse {
-        items: amida.gests.clone(),
-        prize_count: amida.count,
-        message: "Updated".to_string(),
-        seed: state.seed,
+    id: Result<Path<String>, PathRejection>,
+    payload: Result<Json<AutoDrawPatchRequest>, JsonRejection>,
+) -> ApiResult<Json<AutoDrawStatus>> {
+    game_id(id)?;
+    let Json(payload) = payload?;
+    let interval = interval(payload.interval_secs)?;
+    Ok(Json(state.auto_draw.update(interval, payload.paused)?))
+}
+
+/// 自動抽選を停止する
+///
+/// 自動抽選中でない場合は何もせず、現在の状況を返します。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+/// * `id` - ゲームID
+///
+/// # Returns
+/// * `ApiResult<Json<AutoDrawStatus>>` - 停止後の状況
+#[utoipa::path(
+    delete,
+    path = "/v1/games/{id}/auto-draw",
+    tag = "bingo",
+    security(("admin_token" = []), ("bearer" = [])),
+    params(("id" = String, Path, description = "ゲームID (現在は `default` のみ)")),
+    responses(
+        (status = 200, description = "停止後の状況", body = AutoDrawStatus),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+        (status = 404, description = "存在しないゲーム (`game_not_found`)", body = ErrorResponse),
+    )
+)]
+pub async fn stop_auto_draw(
+    State(state): State<AppState>,
+    id: Result<Path<String>, PathRejection>,
+) -> ApiResult<Json<AutoDrawStatus>> {
+    game_id(id)?;
+    Ok(Json(state.auto_draw.stop(AutoDrawStopReason::Manual)))
+}
+
+/// ビンゴのセッションの概要を取得する
+///
+/// 終了したラウンドと進行中のラウンドの概要を返します。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+///
+/// # Returns
+/// * `Json<SessionResponse>` - セッションの概要
+#[utoipa::path(
+    get,
+    path = "/v1/session",
+    tag = "bingo",
+    responses((status = 200, description = "セッションの概要", body = SessionResponse))
+)]
+pub async fn get_session(State(state): State<AppState>) -> Json<SessionResponse> {
+    let session = state.session();
+    let current = Round::snapshot(session.current(), &state.game());
+    let winners = state.winners();
+    let rounds = session
+        .rounds()
+        .iter()
+        .chain([&current])
+        .map(|round| RoundSummary::new(round, winners.round_winners(round.settings.number)))
+        .collect();
+    Json(SessionResponse {
+        seed: session.seed(),
+        current_round: current.settings.number,
+        rounds,
     })
 }
 
-/// あみだくじの結果を取得する
+/// 次のラウンドを開始する
 ///
-/// サーバー側で決定された「参加者」と「景品番号」のペアを返します。
-/// あみだくじの線の形はクライアント側でランダムに生成されますが、
-/// 最終的な結果はこのAPIのレスポンスに従います。
+/// 進行中のラウンドを保管済み (`archived`) にして記録し、
+/// ラウンド番号から求めたシード値の新しいゲームを準備中 (`setup`) のフェーズで開始します。
+/// 進行中のラウンドが終了 (`finished`) または保管済みでない場合は `invalid_phase` になります。
 ///
 /// # Arguments
 /// * `state` - アプリケーション状態
+/// * `payload` - 次のラウンドの設定
 ///
 /// # Returns
-/// * `Json<AmidaResultResponse>` - 抽選結果ペア
-pub async fn get_amida_result(State(state): State<AppState>) -> Json<AmidaResultResponse> {
-    let amida = state.amida.lock().unwrap();
-    let result = amida.get_result();
-    // ゲストと景品の組み合わせを返す
-    Json(AmidaResultResponse {
-        items: result.unwrap_or_default(),
-        message: "Success".to_string(),
-        seed: state.seed,
+/// * `ApiResult<(StatusCode, Json<RoundResponse>)>` - 開始したラウンド (`201 Created`)
+#[utoipa::path(
+    post,
+    path = "/v1/session/rounds",
+    tag = "bingo",
+    security(("admin_token" = []), ("bearer" = [])),
+    request_body = RoundRequest,
+    responses(
+        (status = 201, description = "開始したラウンド", body = RoundResponse),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+        (status = 409, description = "進行中のラウンドが終了していない (`invalid_phase`)", body = ErrorResponse),
+    )
+)]
+pub async fn create_round(
+    State(state): State<AppState>,
+    payload: Result<Json<RoundRequest>, JsonRejection>,
+) -> ApiResult<(StatusCode, Json<RoundResponse>)> {
+    let Json(payload) = payload?;
+    let round = {
+        let mut session = state.session();
+        let mut game = state.game();
+        let settings = session
+            .start_next_round(&mut game, payload.pattern, non_empty(payload.prize))?
+            .clone();
+        Round::snapshot(&settings, &game)
+    };
+    state.auto_draw.stop(AutoDrawStopReason::PhaseChanged);
+    Ok((
+        StatusCode::CREATED,
+        Json(RoundResponse {
+            round,
+            winners: Vec::new(),
+        }),
+    ))
+}
+
+/// ラウンドの記録を取得する
+///
+/// 終了したラウンドは抽選履歴・申告・フェーズの遷移を含む記録を、
+/// 進行中のラウンドは現在の状態を返します。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+/// * `number` - ラウンド番号
+///
+/// # Returns
+/// * `ApiResult<Json<RoundResponse>>` - ラウンドの記録
+#[utoipa::path(
+    get,
+    path = "/v1/session/rounds/{number}",
+    tag = "bingo",
+    params(("number" = u32, Path, description = "ラウンド番号")),
+    responses(
+        (status = 200, description = "ラウンドの記録", body = RoundResponse),
+        (status = 404, description = "存在しないラウンド (`round_not_found`)", body = ErrorResponse),
+    )
+)]
+pub async fn get_round(
+    State(state): State<AppState>,
+    number: Result<Path<u32>, PathRejection>,
+) -> ApiResult<Json<RoundResponse>> {
+    let Path(number) = number?;
+    Ok(Json(round(&state, number)?))
+}
+
+/// ラウンドの設定を変更する
+///
+/// 当たりの形・景品・当選者を変更します。終了したラウンドも変更できます。
+/// 当選者は当選者の一覧に記録します。新しい当選者はそのラウンドの最後の申告から記録し、
+/// 含まれなくなった当選者の記録は取り消して景品を戻します。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+/// * `number` - ラウンド番号
+/// * `payload` - 変更内容
+///
+/// # Returns
+/// * `ApiResult<Json<RoundResponse>>` - 変更後のラウンドの記録
+#[utoipa::path(
+    patch,
+    path = "/v1/session/rounds/{number}",
+    tag = "bingo",
+    security(("admin_token" = []), ("bearer" = [])),
+    params(("number" = u32, Path, description = "ラウンド番号")),
+    request_body = RoundPatchRequest,
+    responses(
+        (status = 200, description = "変更後のラウンドの記録", body = RoundResponse),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+        (status = 404, description = "存在しないラウンド・参加者・申告がない (`round_not_found` / `player_not_found` / `claim_not_found`)", body = ErrorResponse),
+        (status = 409, description = "ほかのラウンドですでに当選している (`already_won`)", body = ErrorResponse),
+    )
+)]
+pub async fn update_round(
+    State(state): State<AppState>,
+    number: Result<Path<u32>, PathRejection>,
+    payload: Result<Json<RoundPatchRequest>, JsonRejection>,
+) -> ApiResult<Json<RoundResponse>> {
+    let Path(number) = number?;
+    let Json(payload) = payload?;
+    if let Some(winners) = &payload.winners {
+        let players = state.players();
+        for &winner in winners {
+            players.get(winner)?;
+        }
+    }
+    {
+        let mut session = state.session();
+        let game = state.game();
+        let (pattern, claims) = if session.current().number == number {
+            (session.current().pattern, game.claims.clone())
+        } else {
+            let round = session
+                .round(number)
+                .ok_or(ApiError::RoundNotFound(number))?;
+            (round.settings.pattern, round.claims.clone())
+        };
+        let pattern = payload.pattern.unwrap_or(pattern);
+        if let Some(winners) = payload.winners {
+            let mut registry = state.winners();
+            let current = registry.round_winners(number);
+            // 記録を変更する前に、新しい当選者が全員記録できることを確かめる
+            let mut added: Vec<&BingoClaim> = Vec::new();
+            for &player in winners.iter().filter(|player| !current.contains(player)) {
+                if added.iter().any(|claim| claim.player == player) {
+                    continue;
+                }
+                if registry.has_won(player) {
+                    return Err(WinnerError::AlreadyWon(player).into());
+                }
+                let claim = claims
+                    .iter()
+                    .rev()
+                    .find(|claim| claim.player == player)
+                    .ok_or(WinnerError::ClaimNotFound(player))?;
+                added.push(claim);
+            }
+            let removed: Vec<PlayerId> = current
+                .into_iter()
+                .filter(|player| !winners.contains(player))
+                .collect();
+            for &player in &removed {
+                registry.revoke(number, player);
+            }
+            session.remove_tie_breaks(number, &removed);
+            for claim in added {
+                registry.record(number, pattern, claim)?;
+            }
+        }
+        session.update_round(
+            number,
+            RoundChanges {
+                pattern: payload.pattern,
+                prize: payload.prize.map(|prize| non_empty(Some(prize))),
+            },
+        );
+    }
+    Ok(Json(round(&state, number)?))
+}
+
+/// 参加者名簿を取得する
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+///
+/// # Returns
+/// * `Json<PlayersResponse>` - 登録されている参加者
+#[utoipa::path(
+    get,
+    path = "/v1/players",
+    tag = "players",
+    responses((status = 200, description = "登録されている参加者", body = PlayersResponse))
+)]
+pub async fn list_players(State(state): State<AppState>) -> Json<PlayersResponse> {
+    Json(PlayersResponse {
+        players: state.players().players().to_vec(),
     })
 }
+
+/// 参加者を登録する
+///
+/// 参加者IDはサーバーが振ります。表示名が空の場合や、絵文字ではないアバターは `invalid_player`、
+/// 同じ表示名の参加者がすでにいる場合は `duplicate_player` になります。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+/// * `payload` - 登録内容
+///
+/// # Returns
+/// * `ApiResult<(StatusCode, Json<Player>)>` - 登録した参加者 (`201 Created`)
+#[utoipa::path(
+    post,
+    path = "/v1/players",
+    tag = "players",
+    security(("admin_token" = []), ("bearer" = [])),
+    request_body = PlayerProfile,
+    responses(
+        (status = 201, description = "登録した参加者", body = Player),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+        (status = 409, description = "同じ表示名の参加者がいる (`duplicate_player`)", body = ErrorResponse),
+        (status = 422, description = "登録内容が正しくない (`invalid_player`)", body = ErrorResponse),
+    )
+)]
+pub async fn create_player(
+    State(state): State<AppState>,
+    payload: Result<Json<PlayerProfile>, JsonRejection>,
+) -> ApiResult<(StatusCode, Json<Player>)> {
+    let Json(payload) = payload?;
+    let player = state.players().add(payload)?.clone();
+    Ok((StatusCode::CREATED, Json(player)))
+}
+
+/// 参加者を取得する
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+/// * `player_id` - 参加者ID
+///
+/// # Returns
+/// * `ApiResult<Json<Player>>` - 参加者
+#[utoipa::path(
+    get,
+    path = "/v1/players/{player_id}",
+    tag = "players",
+    params(("player_id" = u32, Path, description = "参加者ID")),
+    responses(
+        (status = 200, description = "参加者", body = Player),
+        (status = 404, description = "存在しない参加者 (`player_not_found`)", body = ErrorResponse),
+    )
+)]
+pub async fn get_player(
+    State(state): State<AppState>,
+    player_id: Result<Path<PlayerId>, PathRejection>,
+) -> ApiResult<Json<Player>> {
+    let Path(player_id) = player_id?;
+    Ok(Json(state.players().get(player_id)?.clone()))
+}
+
+/// 参加者の情報を変更する
+///
+/// 表示名を変更すると、あみだくじの枠・制約・欠席の記録も新しい表示名に置き換えます。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+/// * `player_id` - 参加者ID
+/// * `payload` - 変更内容
+///
+/// # Returns
+/// * `ApiResult<Json<Player>>` - 変更後の参加者
+#[utoipa::path(
+    patch,
+    path = "/v1/players/{player_id}",
+    tag = "players",
+    security(("admin_token" = []), ("bearer" = [])),
+    params(("player_id" = u32, Path, description = "参加者ID")),
+    request_body = PlayerPatchRequest,
+    responses(
+        (status = 200, description = "変更後の参加者", body = Player),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+        (status = 404, description = "存在しない参加者 (`player_not_found`)", body = ErrorResponse),
+        (status = 409, description = "同じ表示名の参加者がいる (`duplicate_player`)", body = ErrorResponse),
+        (status = 422, description = "変更内容が正しくない (`invalid_player`)", body = ErrorResponse),
+    )
+)]
+pub async fn update_player(
+    State(state): State<AppState>,
+    player_id: Result<Path<PlayerId>, PathRejection>,
+    payload: Result<Json<PlayerPatchRequest>, JsonRejection>,
+) -> ApiResult<Json<Player>> {
+    let Path(player_id) = player_id?;
+    let Json(payload) = payload?;
+    let changes = PlayerChanges {
+        name: payload.name,
+        furigana: payload.furigana.map(Some),
+        team: payload.team.map(Some),
+        avatar: payload.avatar.map(Some),
+    };
+
+    let mut players = state.players();
+    Ok(Json(players.update(player_id, changes)?.clone()))
+}
+
+/// 参加者を名簿から削除する
+///
+/// ビンゴの申告 (終了したラウンドを含む)・当選者・ビンゴカード・あみだくじの枠と割り当て制約で参照されている参加者は削除できず、
+/// `player_in_use` になります。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+/// * `player_id` - 参加者ID
+///
+/// # Returns
+/// * `ApiResult<Json<Player>>` - 削除した参加者
+#[utoipa::path(
+    delete,
+    path = "/v1/players/{player_id}",
+    tag = "players",
+    security(("admin_token" = []), ("bearer" = [])),
+    params(("player_id" = u32, Path, description = "参加者ID")),
+    responses(
+        (status = 200, description = "削除した参加者", body = Player),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+        (status = 404, description = "存在しない参加者 (`player_not_found`)", body = ErrorResponse),
+        (status = 409, description = "参照されている参加者 (`player_in_use`)", body = ErrorResponse),
+    )
+)]
+pub async fn delete_player(
+    State(state): State<AppState>,
+    player_id: Result<Path<PlayerId>, PathRejection>,
+) -> ApiResult<Json<Player>> {
+    let Path(player_id) = player_id?;
+    let referenced = referenced_players(&state).contains(&player_id);
+    let seated = state.amida().involves(player_id);
+
+    let mut players = state.players();
+    players.get(player_id)?;
+    if referenced || seated {
+        return Err(ApiError::PlayerInUse(player_id));
+    }
+    Ok(Json(players.remove(player_id)?))
+}
+
+/// 発行したビンゴカードの一覧を取得する
+///
+/// 参加コードを含むため、管理者のみ取得できます。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+///
+/// # Returns
+/// * `Json<CardsResponse>` - 発行したカード
+#[utoipa::path(
+    get,
+    path = "/v1/cards",
+    tag = "cards",
+    security(("admin_token" = []), ("bearer" = [])),
+    responses(
+        (status = 200, description = "発行したカード", body = CardsResponse),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+    )
+)]
+pub async fn list_cards(State(state): State<AppState>) -> Json<CardsResponse> {
+    Json(CardsResponse {
+        cards: state.cards().cards().to_vec(),
+    })
+}
+
+/// 発行したビンゴカードを印刷用のシートとして取得する
+///
+/// A4の1ページに6枚のカードを、カードIDと確認用のQRコード付きで並べます。
+/// PDFは全ページを1ファイルで、SVGは `page` で指定した1ページを返します。
+/// レスポンスは添付ファイルとしてダウンロードされます。
+/// QRコードの鍵 (`--card-secret`) が設定されていない場合は、再起動すると検証できなくなるため印刷できません。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+/// * `query` - 出力形式とページ
+///
+/// # Returns
+/// * `ApiResult<impl IntoResponse>` - 印刷用のシート
+#[utoipa::path(
+    get,
+    path = "/v1/cards/sheet",
+    tag = "cards",
+    security(("admin_token" = []), ("bearer" = [])),
+    params(CardSheetQuery),
+    responses(
+        (status = 200, description = "印刷用のシート", content(
+            (Vec<u8> = "application/pdf"),
+            (String = "image/svg+xml"),
+        )),
+        (status = 400, description = "リクエストの形式が正しくない (`invalid_request`)", body = ErrorResponse),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+        (status = 422, description = "QRコードの鍵が設定されていない、発行したカードがない、またはページが範囲外 (`invalid_request`)", body = ErrorResponse),
+    )
+)]
+pub async fn get_card_sheet(
+    State(state): State<AppState>,
+    query: Result<Query<CardSheetQuery>, QueryRejection>,
+) -> ApiResult<impl IntoResponse> {
+    let Query(query) = query?;
+    if !state.card_key.is_configured() {
+        return Err(ApiError::InvalidRequest(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "printed cards need a card secret (--card-secret)".to_string(),
+        ));
+    }
+    let cards: Vec<PrintedCard> = state
+        .cards()
+        .cards()
+        .iter()
+        .map(|card| PrintedCard::issued(&state.card_key, card))
+        .collect();
+    if cards.is_empty() {
+        return Err(ApiError::InvalidRequest(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "no cards have been issued".to_string(),
+        ));
+    }
+
+    let (body, file_name) = match query.format {
+        SheetFormat::Pdf => (sheet::render_pdf(&cards), "cards.pdf".to_string()),
+        SheetFormat::Svg => {
+            let pages = sheet::render_svg(&cards);
+            let page = query.page.unwrap_or(1);
+            let svg = page
+                .checked_sub(1)
+                .and_then(|i| pages.get(i))
+                .ok_or_else(|| {
+                    ApiError::InvalidRequest(
+                        StatusCode::UNPROCESSABLE_ENTITY,
+                        format!("page must be between 1 and {}", pages.len()),
+                    )
+                })?;
+            (svg.clone().into_bytes(), format!("cards-{}.svg", page))
+        }
+    };
+
+    let disposition = format!("attachment; filename=\"{}\"", file_name);
+    Ok((
+        [
+            (
+                header::CONTENT_TYPE,
+                query.format.content_type().to_string(),
+            ),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    ))
+}
+
+/// 印刷したカードのQRコードを確認する
+///
+/// 読み取ったQRコードの署名を検証し、カードの数字に進行中のラウンドの抽選履歴を重ねて返します。
+/// QRコードの内容をJSONで送る代わりに、カードを撮影したPNG画像 (`Content-Type: image/png`) を送ると、
+/// サーバーで画像からQRコードを読み取ります。読み取れない場合は `unreadable_image` になります。
+/// 偽造されたカード、カードIDや署名を書き換えたカード、別のシード値で作られたカードは `invalid_card` になります。
+/// 当たりの形が揃っている場合は `winning` で揃ったマスを示します。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+/// * `request` - QRコードの内容 (JSON) またはカードの画像 (PNG)
+///
+/// # Returns
+/// * `ApiResult<Json<CardVerificationResponse>>` - 確認結果
+#[utoipa::path(
+    post,
+    path = "/v1/cards/verifications",
+    tag = "cards",
+    security(("admin_token" = []), ("bearer" = [])),
+    request_body(
+        description = "QRコードの内容、またはカードを撮影したPNG画像",
+        content(
+            (CardVerificationRequest = "application/json"),
+            (Vec<u8> = "image/png"),
+        ),
+    ),
+    responses(
+        (status = 200, description = "確認結果", body = CardVerificationResponse),
+        (status = 400, description = "リクエストの形式が正しくない (`invalid_request`)", body = ErrorResponse),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+        (status = 409, description = "カードの形式がないビンゴの種類 (`unsupported_variant`)", body = ErrorResponse),
+        (status = 422, description = "偽造・改変されたカード (`invalid_card`)、または画像からQRコードを読み取れない (`unreadable_image`)", body = ErrorResponse),
+    )
+)]
+pub async fn verify_card(
+    State(state): State<AppState>,
+    request: Request,
+) -> ApiResult<Json<CardVerificationResponse>> {
+    let payload = if is_png(request.headers()) {
+        let image = Bytes::from_request(request, &()).await?;
+        // 画像の読み取りは時間がかかるため、非同期のワーカーを止めないよう別スレッドで行う
+        tokio::task::spawn_blocking(move || qr::decode_png(&image))
+            .await
+            .expect("QR code decoding panicked")?
+    } else {
+        let Json(request) = Json::<CardVerificationRequest>::from_request(request, &()).await?;
+        request.payload
+    };
+    let session = state.session();
+    let game = state.game();
+    let variant = game.variant();
+    if variant != BingoVariant::Ball75 {
+        return Err(card::CardError::UnsupportedVariant(variant).into());
+    }
+    let (id, numbers) = state.card_key.verify(&payload, session.seed())?;
+    let holder = state
+        .cards()
+        .cards()
+        .iter()
+        .find(|card| card.id == id)
+        .map(|card| card.player);
+    let player = holder.and_then(|player| state.players().get(player).ok().cloned());
+
+    let marks = card::marks(&numbers, &game.history);
+    let pattern = session.current().pattern;
+    Ok(Json(CardVerificationResponse {
+        card: id,
+        numbers,
+        player,
+        round: session.current().number,
+        pattern,
+        phase: game.phase(),
+        last: game.history.last().copied(),
+        draw_count: game.history.len(),
+        marks,
+        winning: card::winning_cells(pattern, &marks),
+        claimed: game.claims.iter().any(|claim| claim.card == Some(id)),
+    }))
+}
+
+/// 参加者にビンゴカードを発行する
+///
+/// カードの数字はセッションのシード値とカードIDから決まります。
+/// 新しいカードは準備中 (`setup`) のフェーズでのみ発行でき、それ以外は `invalid_phase` になります。
+/// すでにカードを持つ参加者には、フェーズに関わらず同じカードを `200 OK` で返します。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+/// * `payload` - カードを渡す参加者
+///
+/// # Returns
+/// * `ApiResult<(StatusCode, Json<BingoCard>)>` - 発行したカード (`201 Created`)
+#[utoipa::path(
+    post,
+    path = "/v1/cards",
+    tag = "cards",
+    security(("admin_token" = []), ("bearer" = [])),
+    request_body = CardRequest,
+    responses(
+        (status = 201, description = "発行したカード", body = BingoCard),
+        (status = 200, description = "参加者が持っているカード", body = BingoCard),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+        (status = 404, description = "存在しない参加者 (`player_not_found`)", body = ErrorResponse),
+        (status = 409, description = "準備中ではない (`invalid_phase`)、カードのないビンゴの種類 (`unsupported_variant`)、またはカードIDを使い切った (`cards_exhausted`)", body = ErrorResponse),
+    )
+)]
+pub async fn create_card(
+    State(state): State<AppState>,
+    payload: Result<Json<CardRequest>, JsonRejection>,
+) -> ApiResult<(StatusCode, Json<BingoCard>)> {
+    let Json(payload) = payload?;
+    state.players().get(payload.player)?;
+
+    let session = state.session();
+    let game = state.game();
+    let mut cards = state.cards();
+    let (card, created) =
+        cards.issue(payload.player, session.seed(), game.phase(), game.variant())?;
+    let status = if created {
+        StatusCode::CREATED
+    } else {
+        StatusCode::OK
+    };
+    Ok((status, Json(card.clone())))
+}
+
+/// 参加コードでビンゴカードの状態を取得する
+///
+/// 抽選済みの数字に印を付けたカードと、進行中のラウンドの当たりの形が揃っているかを返します。
+/// 参加コードを知っている人なら誰でも取得できます。
+/// レスポンスの `ETag` を `If-None-Match` に指定すると、変化がない場合は `304 Not Modified` を返します。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+/// * `code` - 参加コード
+/// * `headers` - リクエストヘッダー (`If-None-Match`)
+///
+/// # Returns
+/// * `ApiResult<Response>` - カードの状態 (変化がない場合は `304`)
+#[utoipa::path(
+    get,
+    path = "/v1/join/{code}",
+    tag = "cards",
+    params(
+        ("code" = String, Path, description = "参加コード (大文字・小文字は区別しない)"),
+        ("If-None-Match" = Option<String>, Header, description = "前回のレスポンスの `ETag`"),
+    ),
+    responses(
+        (status = 200, description = "カードの状態", body = PlayerCardResponse,
+            headers(("ETag" = String, description = "状態を識別する値"))),
+        (status = 304, description = "前回から変化していない",
+            headers(("ETag" = String, description = "状態を識別する値"))),
+        (status = 404, description = "存在しない参加コード (`card_not_found`)", body = ErrorResponse),
+    )
+)]
+pub async fn get_player_card(
+    State(state): State<AppState>,
+    code: Result<Path<String>, PathRejection>,
+    headers: HeaderMap,
+) -> ApiResult<Response> {
+    let Path(code) = code?;
+    let card = player_card(&state, &code)?;
+    let body = serde_json::to_vec(&card).expect("card is always serializable");
+    Ok(conditional_json(&headers, body))
+}
+
+/// 参加コードのビンゴカードで申告する
+///
+/// 進行中のラウンドの当たりの形が揃っている場合だけ申告を記録し、
+/// 司会者が確認できるように自動抽選を停止します。揃っていない場合は `not_bingo` になります。
+/// 揃っていることを確認した申告は当選者として記録します (すでに当選している参加者を除く)。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+/// * `code` - 参加コード
+///
+/// # Returns
+/// * `ApiResult<(StatusCode, Json<ClaimResponse>)>` - 記録した申告 (`201 Created`)
+#[utoipa::path(
+    post,
+    path = "/v1/join/{code}/claims",
+    tag = "cards",
+    params(("code" = String, Path, description = "参加コード (大文字・小文字は区別しない)")),
+    responses(
+        (status = 201, description = "記録した申告", body = ClaimResponse),
+        (status = 404, description = "存在しない参加コード (`card_not_found`)", body = ErrorResponse),
+        (status = 409, description = "当たりの形が揃っていない (`not_bingo`)", body = ErrorResponse),
+    )
+)]
+pub async fn create_card_claim(
+    State(state): State<AppState>,
+    code: Result<Path<String>, PathRejection>,
+) -> ApiResult<(StatusCode, Json<ClaimResponse>)> {
+    let Path(code) = code?;
+    let (claim, winner) = {
+        let session = state.session();
+        let mut game = state.game();
+        let cards = state.cards();
+        let card = cards.find_by_code(&code)?;
+        let marks = card.marks(&game.history);
+        let (round, pattern) = (session.current().number, session.current().pattern);
+        card::winning_cells(pattern, &marks).ok_or(card::CardError::NotBingo)?;
+        let draw_index = card::completing_draw(pattern, &card.numbers, &game.history);
+        let claim = game.claim(card.player, Some(card.id), draw_index);
+        let winner = state.winners().record(round, pattern, &claim).ok().cloned();
+        (claim, winner)
+    };
+    let auto_draw = state.auto_draw.stop(AutoDrawStopReason::Claimed);
+    Ok((
+        StatusCode::CREATED,
+        Json(ClaimResponse {
+            claim,
+            auto_draw,
+            winner,
+        }),
+    ))
+}
+
+/// ビンゴの景品と当選者を取得する
+///
+/// まだ渡していない景品と、これまでの当選者 (ラウンド・当たりの形・揃った数字の抽選順・景品) を返します。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+///
+/// # Returns
+/// * `Json<WinnersResponse>` - 景品と当選者
+#[utoipa::path(
+    get,
+    path = "/v1/winners",
+    tag = "winners",
+    responses((status = 200, description = "景品と当選者", body = WinnersResponse))
+)]
+pub async fn get_winners(State(state): State<AppState>) -> Json<WinnersResponse> {
+    Json(winners_response(&state))
+}
+
+/// 司会者が確認した申告を当選者として記録する
+///
+/// 進行中のラウンドの参加者の最後の申告を当選者として記録し、次の景品を渡します。
+/// 同じ数字で揃った当選者がすでにいる場合は、その景品を分け合います。
+/// 景品は1人1つまでのため、すでに当選している参加者は `already_won` になります。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+/// * `payload` - 当たりの形を確認した参加者
+///
+/// # Returns
+/// * `ApiResult<(StatusCode, Json<Winner>)>` - 記録した当選者 (`201 Created`)
+#[utoipa::path(
+    post,
+    path = "/v1/winners",
+    tag = "winners",
+    security(("admin_token" = []), ("bearer" = [])),
+    request_body = WinnerRequest,
+    responses(
+        (status = 201, description = "記録した当選者", body = Winner),
+        (status = 400, description = "リクエストの形式が正しくない (`invalid_request`)", body = ErrorResponse),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+        (status = 404, description = "存在しない参加者・申告がない (`player_not_found` / `claim_not_found`)", body = ErrorResponse),
+        (status = 409, description = "すでに当選している (`already_won`)", body = ErrorResponse),
+    )
+)]
+pub async fn create_winner(
+    State(state): State<AppState>,
+    payload: Result<Json<WinnerRequest>, JsonRejection>,
+) -> ApiResult<(StatusCode, Json<Winner>)> {
+    let Json(payload) = payload?;
+    state.players().get(payload.player)?;
+    let session = state.session();
+    let game = state.game();
+    let claim = game
+        .claims
+        .iter()
+        .rev()
+        .find(|claim| claim.player == payload.player)
+        .ok_or(WinnerError::ClaimNotFound(payload.player))?;
+    let (round, pattern) = (session.current().number, session.current().pattern);
+    let winner = state.winners().record(round, pattern, claim)?.clone();
+    Ok((StatusCode::CREATED, Json(winner)))
+}
+
+/// まだ渡していないビンゴの景品を置き換える
+///
+/// 景品は指定した順に当選者に渡します。渡した景品は当選者の記録に残ります。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+/// * `payload` - 景品の名前
+///
+/// # Returns
+/// * `ApiResult<Json<WinnersResponse>>` - 置き換えた後の景品と当選者
+#[utoipa::path(
+    put,
+    path = "/v1/winners/prizes",
+    tag = "winners",
+    security(("admin_token" = []), ("bearer" = [])),
+    request_body = PrizesRequest,
+    responses(
+        (status = 200, description = "置き換えた後の景品と当選者", body = WinnersResponse),
+        (status = 400, description = "リクエストの形式が正しくない (`invalid_request`)", body = ErrorResponse),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+        (status = 422, description = "空の景品名がある (`invalid_request`)", body = ErrorResponse),
+    )
+)]
+pub async fn replace_prizes(
+    State(state): State<AppState>,
+    payload: Result<Json<PrizesRequest>, JsonRejection>,
+) -> ApiResult<Json<WinnersResponse>> {
+    let Json(payload) = payload?;
+    let prizes: Vec<String> = payload
+        .prizes
+        .iter()
+        .map(|name| name.trim().to_string())
+        .collect();
+    if prizes.iter().any(String::is_empty) {
+        return Err(ApiError::InvalidRequest(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "prize name must not be empty".to_string(),
+        ));
+    }
+    state.winners().set_prizes(prizes);
+    Ok(Json(winners_response(&state)))
+}
+
+/// 終了したラウンドの抽選の統計を取得する
+///
+/// 数字ごとの抽選回数と順番、最初の当選者が出るまでの抽選回数、抽選された順番に偏りがないかどうかのカイ二乗検定に加え、
+/// 75ボールのゲームでは進行中のラウンドの当たりの形で、N番目の抽選までに当選者が出る確率をシミュレーションで求めます。
+/// シミュレーションの乱数はセッションのシード値から求めるため、同じ条件なら同じ結果になります。
+/// 認証不要のAPIのため、仮想カードの枚数×回数に上限を設け、同じ条件の結果は計算し直さずに返します。
+/// 同時に行うシミュレーションの数にも上限があり、超えた場合は `Retry-After` を付けて503を返します。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+/// * `query` - シミュレーションの回数と仮想カードの枚数
+///
+/// # Returns
+/// * `ApiResult<Json<DrawStatistics>>` - 抽選の統計
+#[utoipa::path(
+    get,
+    path = "/v1/statistics",
+    tag = "statistics",
+    params(StatisticsQuery),
+    responses(
+        (status = 200, description = "抽選の統計", body = DrawStatistics),
+        (status = 400, description = "クエリパラメータの形式が正しくない (`invalid_request`)", body = ErrorResponse),
+        (status = 422, description = "シミュレーションの回数・仮想カードの枚数・その積が範囲外 (`invalid_request`)", body = ErrorResponse),
+        (status = 503, description = "シミュレーションが混み合っている (`simulation_busy`)", body = ErrorResponse,
+            headers(("Retry-After" = u64, description = "再試行までの秒数"))),
+    )
+)]
+pub async fn get_statistics(
+    State(state): State<AppState>,
+    query: Result<Query<StatisticsQuery>, QueryRejection>,
+) -> ApiResult<Json<DrawStatistics>> {
+    let Query(query) = query?;
+    if query
+        .simulations
+        .is_some_and(|simulations| !(1..=MAX_SIMULATIONS).contains(&simulations))
+    {
+        return Err(ApiError::InvalidRequest(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("simulations must be between 1 and {}", MAX_SIMULATIONS),
+        ));
+    }
+    if query.cards.is_some_and(|cards| cards > MAX_SIMULATED_CARDS) {
+        return Err(ApiError::InvalidRequest(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("cards must be at most {}", MAX_SIMULATED_CARDS),
+        ));
+    }
+
+    let (mut statistics, seed, pattern, variant) = {
+        let session = state.session();
+        let game = state.game();
+        let winners = state.winners();
+        (
+            DrawStatistics::collect(session.rounds(), winners.winners(), game.max_number()),
+            session.seed(),
+            session.current().pattern,
+            game.variant(),
+        )
+    };
+    if variant == BingoVariant::Ball75 {
+        let cards = query
+            .cards
+            .unwrap_or_else(|| state.cards().cards().len().min(MAX_SIMULATED_CARDS));
+        let simulations = simulation_count(query.simulations, cards)?;
+        let cached = state.simulations().get(seed, cards, pattern, simulations);
+        let probability = match cached {
+            Some(probability) => probability,
+            None => {
+                // 同時に走るシミュレーションが増えてブロッキング用のスレッドを使い切らないよう、数を制限する
+                let Ok(permit) = state.simulation_permits.clone().try_acquire_owned() else {
+                    return Err(ApiError::SimulationBusy(stats::SIMULATION_RETRY_SECONDS));
+                };
+                // シミュレーションは時間がかかるため、非同期のワーカーを止めないよう別スレッドで行う
+                let probability = tokio::task::spawn_blocking(move || {
+                    let _permit = permit;
+                    stats::simulate_winner_probability(seed, cards, pattern, simulations)
+                })
+                .await
+                .expect("winner probability simulation panicked");
+                state.simulations().insert(seed, probability.clone());
+                probability
+            }
+        };
+        statistics.winner_probability = Some(probability);
+    }
+    Ok(Json(statistics))
+}
+
+/// くじ引きの記録を取得する
+///
+/// 会場の画面がくじ引きの様子を再生できるよう、全てのラウンドのくじ引きの記録を返します。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+///
+/// # Returns
+/// * `Json<TieBreaksResponse>` - くじ引きの記録
+#[utoipa::path(
+    get,
+    path = "/v1/winners/tie-breaks",
+    tag = "winners",
+    responses((status = 200, description = "くじ引きの記録", body = TieBreaksResponse))
+)]
+pub async fn get_tie_breaks(State(state): State<AppState>) -> Json<TieBreaksResponse> {
+    let session = state.session();
+    Json(TieBreaksResponse {
+        tie_breaks: session.tie_breaks().into_iter().cloned().collect(),
+    })
+}
+
+/// 同じ数字で揃った当選者の順位をくじ引きで決める
+///
+/// 進行中のラウンドで景品を分け合っている当選者の順位を、ラウンドのシード値から求めた乱数で決めます。
+/// 1位の当選者が分け合っていた景品を受け取り、2位以降には順にまだ渡していない景品を渡します。
+/// くじ引きの記録はラウンドに残り、同じ同点は何度やり直しても同じ結果になります。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+/// * `payload` - 順位の決め方と、揃った数字の抽選順
+///
+/// # Returns
+/// * `ApiResult<(StatusCode, Json<TieBreakResponse>)>` - くじ引きの記録と当選者 (`201 Created`)
+#[utoipa::path(
+    post,
+    path = "/v1/winners/tie-breaks",
+    tag = "winners",
+    security(("admin_token" = []), ("bearer" = [])),
+    request_body = TieBreakRequest,
+    responses(
+        (status = 201, description = "くじ引きの記録と当選者", body = TieBreakResponse),
+        (status = 400, description = "リクエストの形式が正しくない (`invalid_request`)", body = ErrorResponse),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+        (status = 409, description = "景品を分け合っている当選者がいない (`no_tie`)", body = ErrorResponse),
+    )
+)]
+pub async fn create_tie_break(
+    State(state): State<AppState>,
+    payload: Result<Json<TieBreakRequest>, JsonRejection>,
+) -> ApiResult<(StatusCode, Json<TieBreakResponse>)> {
+    let Json(payload) = payload?;
+    let mut session = state.session();
+    let (round, seed) = (session.current().number, session.current().seed);
+    let mut winners = state.winners();
+    let (draw_index, entrants) = winners.tie(round, payload.draw_index)?;
+    let tie_break = TieBreak::run(round, seed, draw_index, payload.method, &entrants);
+    winners.apply_tie_break(round, draw_index, &tie_break.order);
+    let ranked = tie_break
+        .order
+        .iter()
+        .filter_map(|player| {
+            winners.winners().iter().find(|winner| {
+                winner.round == round && winner.draw_index == draw_index && winner.player == *player
+            })
+        })
+        .cloned()
+        .collect();
+    session.add_tie_break(tie_break.clone());
+    Ok((
+        StatusCode::CREATED,
+        Json(TieBreakResponse {
+            tie_break,
+            winners: ranked,
+        }),
+    ))
+}
+
+/// あみだくじの設定を取得する
+///
+/// 現在設定されている参加者名リストを返します。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+///
+/// # Returns
+/// * `Json<AmidaResponse>` - 現在の設定
+#[utoipa::path(
+    get,
+    path = "/v1/amida",
+    tag = "amida",
+    responses((status = 200, description = "現在の設定", body = AmidaResponse))
+)]
+pub async fn get_amida(State(state): State<AppState>) -> Json<AmidaResponse> {
+    Json(current_amida(&state))
+}
+
+/// あみだくじの設定を置き換える
+///
+/// 参加者名リストと割り当てルール・制約をまとめて置き換えます。
+/// 省略したルール・制約はデフォルト値になります。
+/// 割り当て制約を満たす結果が得られない場合は、設定を保存した上で結果を確定させず、
+/// `status` を `unsatisfied` として理由を返します。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+/// * `payload` - 新しい設定
+///
+/// # Returns
+/// * `ApiResult<Json<AmidaResponse>>` - 更新後の設定
+#[utoipa::path(
+    put,
+    path = "/v1/amida",
+    tag = "amida",
+    security(("admin_token" = []), ("bearer" = [])),
+    request_body = AmidaRequest,
+    responses(
+        (status = 200, description = "更新後の設定", body = AmidaResponse),
+        (status = 400, description = "リクエストの形式が正しくない (`invalid_request`)", body = ErrorResponse),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+    )
+)]
+pub async fn replace_amida(
+    State(state): State<AppState>,
+    payload: Result<Json<AmidaRequest>, JsonRejection>,
+) -> ApiResult<Json<AmidaResponse>> {
+    let Json(payload) = payload?;
+    require_guests(&payload)?;
+    Ok(Json(update_amida(
+        &state,
+        AmidaPatchRequest {
+            items: payload.items,
+            players: payload.players,
+            rule: Some(payload.rule.unwrap_or_default()),
+            constraints: Some(payload.constraints.unwrap_or_default()),
+        },
+    )?))
+}
+
+/// あみだくじの設定を部分的に更新する
+///
+/// 指定した項目だけを更新し、省略した項目は現在の値を維持します。
+/// ルール・制約だけを変更した場合も、現在の参加者名リストで結果を確定し直します。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+/// * `payload` - 更新する項目
+///
+/// # Returns
+/// * `ApiResult<Json<AmidaResponse>>` - 更新後の設定
+#[utoipa::path(
+    patch,
+    path = "/v1/amida",
+    tag = "amida",
+    security(("admin_token" = []), ("bearer" = [])),
+    request_body = AmidaPatchRequest,
+    responses(
+        (status = 200, description = "更新後の設定", body = AmidaResponse),
+        (status = 400, description = "リクエストの形式が正しくない (`invalid_request`)", body = ErrorResponse),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+    )
+)]
+pub async fn patch_amida(
+    State(state): State<AppState>,
+    payload: Result<Json<AmidaPatchRequest>, JsonRejection>,
+) -> ApiResult<Json<AmidaResponse>> {
+    let Json(payload) = payload?;
+    Ok(Json(update_amida(&state, payload)?))
+}
+
+/// あみだくじの結果を取得する
+///
+/// サーバー側で決定された「景品番号」と「参加者」のペアのうち、
+/// 公開済みのものだけを返します。未公開の結果は `reveal_amida` で1件ずつ公開します。
+/// これにより、クライアント側で事前に結果を覗き見ることはできません。
+/// あみだくじの線の形はクライアント側でランダムに生成されますが、
+/// 最終的な結果はこのAPIのレスポンスに従います。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+///
+/// # Returns
+/// * `Json<AmidaResultResponse>` - 公開済みの抽選結果ペア
+#[utoipa::path(
+    get,
+    path = "/v1/amida/result",
+    tag = "amida",
+    responses((status = 200, description = "公開済みの結果", body = AmidaResultResponse))
+)]
+pub async fn get_amida_result(State(state): State<AppState>) -> Json<AmidaResultResponse> {
+    let amida = state.amida();
+    let rule = amida.rule;
+    // 公開済みのゲストと景品の組み合わせを返す
+    let (pairs, leftover) = amida
+        .get_revealed_result()
+        .map(|result| (result.pairs, result.leftover))
+        .unwrap_or_default();
+    let ready = amida.get_result().is_some();
+    let registry = state.players();
+    let items = pairs
+        .iter()
+        .map(|(prize, guest)| (prize.clone(), guest_name(&registry, *guest)))
+        .collect();
+    Json(AmidaResultResponse {
+        items,
+        players: pairs.iter().map(|&(_, guest)| guest).collect(),
+        ready,
+        total: amida.assigned_count(),
+        leftover,
+        rule,
+        absentees: guest_names(&registry, amida.absentees.iter().copied().map(Some)),
+        absentee_players: amida.absentees.clone(),
+        redraws: redraw_records(&registry, &amida.redraws),
+        status: result_status(ready, amida.is_fully_revealed()),
+        seed: state.revealed_amida_seed(&amida),
+    })
+}
+
+/// あみだくじの結果を1件公開する
+///
+/// 指定された枠番号の結果をサーバー側で公開済みとして記録し、そのペアのみを返します。
+/// 公開済みの枠を再度指定した場合は、同じ結果を返します。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+/// * `payload` - 公開する枠番号
+///
+/// # Returns
+/// * `ApiResult<Json<AmidaRevealResponse>>` - 公開された結果ペア
+///   (結果が確定していない場合は `amida_not_ready`、割り当てのない枠は `prize_not_found`)
+#[utoipa::path(
+    post,
+    path = "/v1/amida/reveals",
+    tag = "amida",
+    security(("admin_token" = []), ("bearer" = [])),
+    request_body = AmidaRevealRequest,
+    responses(
+        (status = 200, description = "公開された結果", body = AmidaRevealResponse),
+        (status = 400, description = "リクエストの形式が正しくない (`invalid_request`)", body = ErrorResponse),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+        (status = 404, description = "割り当てのない番号 (`prize_not_found`)", body = ErrorResponse),
+        (status = 409, description = "結果が確定していない (`amida_not_ready`)", body = ErrorResponse),
+    )
+)]
+pub async fn reveal_amida(
+    State(state): State<AppState>,
+    payload: Result<Json<AmidaRevealRequest>, JsonRejection>,
+) -> ApiResult<Json<AmidaRevealResponse>> {
+    let Json(payload) = payload?;
+    let mut amida = state.amida();
+    if !amida.is_ready() {
+        return Err(ApiError::AmidaNotReady);
+    }
+    let (prize, player) = amida
+        .reveal(payload.prize)
+        .ok_or(ApiError::PrizeNotFound(payload.prize))?;
+    let guest = guest_name(&state.players(), player);
+    Ok(Json(AmidaRevealResponse {
+        item: (prize, guest),
+        player,
+        revealed_count: amida.revealed.len(),
+        total: amida.assigned_count(),
+        status: result_status(true, amida.is_fully_revealed()),
+        seed: state.revealed_amida_seed(&amida),
+    }))
+}
+
+/// あみだくじの参加者を欠席として記録し、景品を再抽選する
+///
+/// 結果が公開済みの参加者を欠席として記録し、その参加者の景品だけを
+/// 出席者の中から再抽選します。再抽選はシード値から導出したサブストリームで
+/// 行われ、記録として残るため再現可能です。元の結果も `GET /v1/amida/result` で引き続き確認できます。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+/// * `payload` - 欠席した参加者
+///
+/// # Returns
+/// * `ApiResult<Json<AmidaAbsentResponse>>` - 再抽選の記録
+#[utoipa::path(
+    post,
+    path = "/v1/amida/absences",
+    tag = "amida",
+    security(("admin_token" = []), ("bearer" = [])),
+    request_body = AmidaAbsentRequest,
+    responses(
+        (status = 200, description = "再抽選の記録", body = AmidaAbsentResponse),
+        (status = 400, description = "リクエストの形式が正しくない (`invalid_request`)", body = ErrorResponse),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+        (status = 404, description = "参加者リストにない (`unknown_guest`)", body = ErrorResponse),
+        (status = 409, description = "結果未確定・記録済み・結果未公開 (`amida_not_ready` / `already_absent` / `not_revealed`)", body = ErrorResponse),
+        (status = 422, description = "景品を受け取れる出席者がいない (`no_eligible_guest`)", body = ErrorResponse),
+    )
+)]
+pub async fn mark_amida_absent(
+    State(state): State<AppState>,
+    payload: Result<Json<AmidaAbsentRequest>, JsonRejection>,
+) -> ApiResult<Json<AmidaAbsentResponse>> {
+    let Json(payload) = payload?;
+    let guest = {
+        let players = state.players();
+        match payload.player {
+            Some(player) => players.get(player)?.id,
+            None => players
+                .find_by_name(payload.guest.trim())
+                .map(|player| player.id)
+                .ok_or(AmidaRedrawError::UnknownGuest)?,
+        }
+    };
+    let mut amida = state.amida();
+    let redraws = amida.mark_absent(guest)?;
+    let registry = state.players();
+    Ok(Json(AmidaAbsentResponse {
+        redraws: redraw_records(&registry, &redraws),
+        absentees: guest_names(&registry, amida.absentees.iter().copied().map(Some)),
+        absentee_players: amida.absentees.clone(),
+        seed: state.revealed_amida_seed(&amida),
+    }))
+}
+
+/// CSV/TSVファイルから参加者リストを取り込む
+///
+/// リクエストボディのファイル (UTF-8 (BOM付き可) または Shift_JIS) を解析し、
+/// 参加者名簿とあみだくじの参加者リストを置き換えます。
+/// 取り込めなかった行は行番号とともに `errors` に返し、それ以外の行は取り込みます。
+/// ファイル全体を解析できない場合や、ビンゴの申告・当選者・ビンゴカード・あみだくじの割り当て制約で
+/// 参照されている参加者がファイルに含まれない場合は、何も変更せずエラーを返します。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+/// * `query` - 区切り文字・文字コード・列の対応付け
+/// * `body` - ファイルの内容
+///
+/// # Returns
+/// * `ApiResult<Json<ImportResponse>>` - 取り込み結果
+#[utoipa::path(
+    post,
+    path = "/v1/amida/imports",
+    tag = "amida",
+    security(("admin_token" = []), ("bearer" = [])),
+    params(ImportQuery),
+    request_body(content = String, content_type = "text/csv", description = "CSV/TSVファイル (UTF-8 または Shift_JIS)"),
+    responses(
+        (status = 200, description = "取り込み結果", body = ImportResponse),
+        (status = 400, description = "リクエストの形式が正しくない (`invalid_request`)", body = ErrorResponse),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+        (status = 409, description = "参照されている参加者がファイルに含まれない (`player_in_use`)", body = ErrorResponse),
+        (status = 422, description = "ファイル全体を解析できない (`invalid_encoding` / `missing_header` / `missing_name_column` / `unknown_column`)", body = ErrorResponse),
+    )
+)]
+pub async fn import_amida(
+    State(state): State<AppState>,
+    query: Result<Query<ImportQuery>, QueryRejection>,
+    body: Bytes,
+) -> ApiResult<Json<ImportResponse>> {
+    let Query(query) = query?;
+    let options = ImportOptions {
+        format: query.format,
+        encoding: query.encoding,
+        columns: ColumnMapping {
+            name: query.name,
+            team: query.team,
+            furigana: query.furigana,
+        },
+    };
+
+    let report = import_players(&body, &options)?;
+    check_guest_count(report.players.len())?;
+
+    let referenced = referenced_players(&state);
+    let mut amida = state.amida();
+    let (items, seats) = {
+        let mut players = state.players();
+        // 取り込み直しても残るのは、同じ表示名の参加者だけ
+        // あみだくじの枠は取り込んだ参加者で置き換えるため、割り当て制約で参照されている参加者を確かめる
+        if let Some(dropped) = players.players().iter().find(|player| {
+            (referenced.contains(&player.id) || amida.constraints.involves(player.id))
+                && !report
+                    .players
+                    .iter()
+                    .any(|profile| profile.name.trim() == player.name)
+        }) {
+            return Err(ApiError::PlayerInUse(dropped.id));
+        }
+        players.replace_all(report.players);
+        let seats = players.players().iter().map(|player| Some(player.id));
+        (players.names(), seats.collect())
+    };
+
+    let constraint_error = amida.update(seats).err();
+    Ok(Json(ImportResponse {
+        items,
+        errors: report.errors,
+        format: report.format,
+        encoding: report.encoding,
+        prize_count: amida.count,
+        constraint_error,
+        status: settings_status(constraint_error),
+        seed: state.revealed_amida_seed(&amida),
+    }))
+}
+
+/// ビンゴとあみだくじの結果一式をダウンロードする
+///
+/// ビンゴの各ラウンドの抽選履歴 (抽選日時付き)・当選者・まだ渡していない景品と、
+/// あみだくじの公開済みの結果・再抽選の記録を、
+/// シード値と乱数生成アルゴリズムとともに指定の形式で返します。
+/// レスポンスは添付ファイルとしてダウンロードされます。
+///
+/// # Arguments
+/// * `state` - アプリケーション状態
+/// * `query` - 出力形式
+///
+/// # Returns
+/// * `ApiResult<impl IntoResponse>` - 結果一式のファイル
+#[utoipa::path(
+    get,
+    path = "/v1/export",
+    tag = "export",
+    params(ExportQuery),
+    responses(
+        (status = 200, description = "結果一式のファイル", content(
+            (String = "application/json"),
+            (String = "text/csv"),
+            (String = "text/html"),
+        )),
+        (status = 400, description = "リクエストの形式が正しくない (`invalid_request`)", body = ErrorResponse),
+    )
+)]
+pub async fn export_results(
+    State(state): State<AppState>,
+    query: Result<Query<ExportQuery>, QueryRejection>,
+) -> ApiResult<impl IntoResponse> {
+    let Query(query) = query?;
+    let bundle = {
+        let session = state.session();
+        let game = state.game();
+        let winners = state.winners();
+        let amida = state.amida();
+        let players = state.players();
+        let amida_seed = state.revealed_amida_seed(&amida);
+        ResultsBundle::collect(
+            &session, &game, &winners, &amida, &players, state.seed, amida_seed,
+        )
+    };
+
+    let disposition = format!(
+        "attachment; filename=\"{}\"",
+        bundle.file_name(query.format)
+    );
+    Ok((
+        [
+            (
+                header::CONTENT_TYPE,
+                query.format.content_type().to_string(),
+            ),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        bundle.render(query.format),
+    ))
+}
+
+/// 存在しないAPIのパスへのリクエストに 404 を返す
+///
+/// `/api` 以下のパスがフロントエンドの `index.html` にフォールバックしないようにします。
+pub async fn api_not_found() -> ApiError {
+    ApiError::NotFound
+}
+
+/// パスのゲームIDを検証する
+fn game_id(id: Result<Path<String>, PathRejection>) -> ApiResult<String> {
+    let Path(id) = id?;
+    if id != DEFAULT_GAME_ID {
+        return Err(ApiError::GameNotFound(id));
+    }
+    Ok(id)
+}
+
+/// ラウンドの記録を求める (進行中のラウンドは現在の状態)
+fn round(state: &AppState, number: u32) -> ApiResult<RoundResponse> {
+    let session = state.session();
+    let round = if session.current().number == number {
+        Round::snapshot(session.current(), &state.game())
+    } else {
+        session
+            .round(number)
+            .cloned()
+            .ok_or(ApiError::RoundNotFound(number))?
+    };
+    Ok(RoundResponse {
+        round,
+        winners: state.winners().round_winners(number),
+    })
+}
+
+/// 当選確率のシミュレーションの回数を求める
+///
+/// 省略時は既定の回数を、仮想カードの枚数×回数が上限に収まるように減らします。
+/// 指定された回数で上限を超える場合は `422 Unprocessable Entity` を返します。
+fn simulation_count(simulations: Option<usize>, cards: usize) -> ApiResult<usize> {
+    let Some(simulations) = simulations else {
+        return Ok(DEFAULT_SIMULATIONS.min(MAX_SIMULATED_CARD_GAMES / cards.max(1)));
+    };
+    if simulations.saturating_mul(cards) > MAX_SIMULATED_CARD_GAMES {
+        return Err(ApiError::InvalidRequest(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!(
+                "cards * simulations must be at most {}",
+                MAX_SIMULATED_CARD_GAMES
+            ),
+        ));
+    }
+    Ok(simulations)
+}
+
+/// リクエストのボディがPNG画像かどうか
+fn is_png(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|mime| mime.trim().eq_ignore_ascii_case("image/png"))
+}
+
+/// 空白だけの文字列をNoneにする
+fn non_empty(text: Option<String>) -> Option<String> {
+    text.map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty())
+}
+
+/// 自動抽選の間隔 (秒) を検証する
+fn interval(secs: Option<u64>) -> ApiResult<Option<Duration>> {
+    secs.map(|secs| {
+        autodraw::interval_from_secs(secs).map_err(|e| {
+            ApiError::InvalidRequest(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("invalid interval_secs: {}", e),
+            )
+        })
+    })
+    .transpose()
+}
+
+/// レスポンスボディから `ETag` を求める
+///
+/// ボディのSHA-256の先頭16バイトを使うため、同じボディにはビルドや実行をまたいで同じ値になります。
+/// ボディには再起動で0に戻る `version` が含まれるため、再起動後は状態が同じでも値が変わることがあります
+/// (クライアントは1度だけ取得し直します)。
+fn entity_tag(body: &[u8]) -> HeaderValue {
+    let digest = hmac_sha256::Hash::hash(body);
+    let hex: String = digest[..16].iter().map(|b| format!("{:02x}", b)).collect();
+    HeaderValue::from_str(&format!("\"{}\"", hex)).expect("hex digits are a valid header value")
+}
+
+/// `ETag` を付けたJSONのレスポンスを作る
+///
+/// `If-None-Match` が一致する場合はボディを省いた `304 Not Modified` を返します。
+fn conditional_json(headers: &HeaderMap, body: Vec<u8>) -> Response {
+    let etag = entity_tag(&body);
+    let cache = [
+        (header::ETAG, etag.clone()),
+        (header::CACHE_CONTROL, HeaderValue::from_static("no-cache")),
+    ];
+    if if_none_match(headers, &etag) {
+        return (StatusCode::NOT_MODIFIED, cache).into_response();
+    }
+    (
+        cache,
+        [(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        )],
+        body,
+    )
+        .into_response()
+}
+
+/// `If-None-Match` のいずれかが `etag` と一致するか
+///
+/// 弱い比較 (`W/` を無視) で判定し、`*` は常に一致とします。
+fn if_none_match(headers: &HeaderMap, etag: &HeaderValue) -> bool {
+    let Ok(etag) = etag.to_str() else {
+        return false;
+    };
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|tag| tag.trim().trim_start_matches("W/"))
+        .any(|tag| tag == "*" || tag == etag)
+}
+
+/// ビンゴのゲームの状態を求める
+fn game_response(state: &AppState, id: String) -> GameResponse {
+    let session = state.session();
+    let game = state.game();
+    GameResponse {
+        id,
+        round: session.current().number,
+        max_number: game.max_number(),
+        history: game.history.clone(),
+        remaining: game.remaining_numbers.len(),
+        finished: game.remaining_numbers.is_empty(),
+        phase: game.phase(),
+        actions: game.phase().actions(),
+        transitions: game.transitions().to_vec(),
+        seed: session.current().seed,
+    }
+}
+
+/// 参加コードのビンゴカードの状態を求める
+fn player_card(state: &AppState, code: &str) -> ApiResult<PlayerCardResponse> {
+    let session = state.session();
+    let game = state.game();
+    let cards = state.cards();
+    let card = cards.find_by_code(code)?;
+    let marks = card.marks(&game.history);
+    let pattern = session.current().pattern;
+    Ok(PlayerCardResponse {
+        card: card.clone(),
+        player: state.players().get(card.player)?.clone(),
+        round: session.current().number,
+        pattern,
+        phase: game.phase(),
+        version: game.version(),
+        last: game.history.last().copied(),
+        draw_count: game.history.len(),
+        marks,
+        winning: card::winning_cells(pattern, &marks),
+        claimed: game.claims.iter().any(|claim| claim.card == Some(card.id)),
+    })
+}
+
+/// ビンゴの景品と当選者を求める
+fn winners_response(state: &AppState) -> WinnersResponse {
+    let winners = state.winners();
+    WinnersResponse {
+        remaining: winners.remaining().to_vec(),
+        winners: winners.winners().to_vec(),
+    }
+}
+
+/// 現在のあみだくじの設定を求める
+fn current_amida(state: &AppState) -> AmidaResponse {
+    amida_response(state, &state.amida(), None, AmidaSettingsStatus::Current)
+}
+
+/// あみだくじの設定のレスポンスを作る
+///
+/// あみだくじの状態をロックしたまま呼び出せるように、名簿は最後にロックします。
+fn amida_response(
+    state: &AppState,
+    amida: &AmidaGame,
+    constraint_error: Option<AmidaConstraintError>,
+    status: AmidaSettingsStatus,
+) -> AmidaResponse {
+    let players = state.players();
+    AmidaResponse {
+        items: guest_names(&players, amida.gests.iter().copied()),
+        players: amida.gests.clone(),
+        prize_count: amida.count,
+        rule: amida.rule,
+        constraints: describe_constraints(&players, &amida.constraints),
+        constraint_error,
+        status,
+        seed: state.revealed_amida_seed(amida),
+    }
+}
+
+/// あみだくじの設定を更新する
+///
+/// 指定された項目だけを更新し、現在の参加者リストで結果を確定し直します。
+/// 何も指定されていない場合は、公開状態を保ったまま現在の設定を返します。
+/// 参加者名は名簿の参加者IDに置き換え、名簿にない参加者は名簿に登録します。
+pub(crate) fn update_amida(
+    state: &AppState,
+    payload: AmidaPatchRequest,
+) -> ApiResult<AmidaResponse> {
+    if payload.items.is_none()
+        && payload.players.is_none()
+        && payload.rule.is_none()
+        && payload.constraints.is_none()
+    {
+        return Ok(current_amida(state));
+    }
+    let seats = seat_guests(state, payload.items, payload.players)?;
+    let constraints = payload
+        .constraints
+        .map(|spec| resolve_constraints(state, spec))
+        .transpose()?;
+    let mut amida = state.amida();
+    if let Some(rule) = payload.rule {
+        amida.set_rule(rule);
+    }
+    if let Some(constraints) = constraints {
+        amida.set_constraints(constraints);
+    }
+    let seats = seats.unwrap_or_else(|| amida.gests.clone());
+    let constraint_error = amida.update(seats).err();
+    Ok(amida_response(
+        state,
+        &amida,
+        constraint_error,
+        settings_status(constraint_error),
+    ))
+}
+
+/// あみだくじ設定リクエストに参加者名または参加者IDのリストがあることを確認する
+pub(crate) fn require_guests(payload: &AmidaRequest) -> ApiResult<()> {
+    if payload.items.is_none() && payload.players.is_none() {
+        return Err(ApiError::InvalidRequest(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "either items or players is required".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// あみだくじの枠の参加者IDを求める
+///
+/// 参加者IDが指定された場合は名簿にあることを確認し、参加者名が指定された場合は
+/// 名簿にない参加者を名簿に登録します (空の名前の枠は名簿に結び付けません)。
+fn seat_guests(
+    state: &AppState,
+    items: Option<Vec<String>>,
+    players: Option<Vec<PlayerId>>,
+) -> ApiResult<Option<Vec<Option<PlayerId>>>> {
+    let count = players
+        .as_ref()
+        .map(Vec::len)
+        .or(items.as_ref().map(Vec::len));
+    check_guest_count(count.unwrap_or_default())?;
+    let mut registry = state.players();
+    if let Some(players) = players {
+        return players
+            .into_iter()
+            .map(|id| Ok(Some(registry.get(id)?.id)))
+            .collect::<ApiResult<_>>()
+            .map(Some);
+    }
+    let Some(items) = items else {
+        return Ok(None);
+    };
+    let mut seats = Vec::with_capacity(items.len());
+    for name in items {
+        let name = name.trim();
+        let seat = match name.is_empty() {
+            true => None,
+            false => Some(registry.ensure(name)?),
+        };
+        seats.push(seat);
+    }
+    Ok(Some(seats))
+}
+
+/// 参加者名または参加者IDで指定された割り当て制約を、参加者IDの制約にする
+///
+/// 参加者IDは名簿にあることを確認し、参加者名のうち名簿にない参加者は名簿に登録します。
+fn resolve_constraints(
+    state: &AppState,
+    spec: AmidaConstraintsSpec,
+) -> ApiResult<AmidaConstraints> {
+    let mut registry = state.players();
+    let mut resolve = |guest: Option<String>, player: Option<PlayerId>| match (player, guest) {
+        (Some(player), _) => Ok(registry.get(player)?.id),
+        (None, Some(guest)) => Ok(registry.ensure(&guest)?),
+        (None, None) => Err(ApiError::InvalidRequest(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "either guest or player is required in exclusions".to_string(),
+        )),
+    };
+    let exclusions = spec
+        .exclusions
+        .into_iter()
+        .map(|exclusion| {
+            Ok(AmidaExclusion {
+                player: resolve(exclusion.guest, exclusion.player)?,
+                prize: exclusion.prize,
+            })
+        })
+        .collect::<ApiResult<_>>()?;
+    let spreads = spec
+        .spreads
+        .into_iter()
+        .map(|spread| {
+            let members = match spread.players {
+                Some(players) => players
+                    .into_iter()
+                    .map(|player| resolve(None, Some(player)))
+                    .collect::<ApiResult<_>>()?,
+                None => spread
+                    .members
+                    .into_iter()
+                    .map(|member| resolve(Some(member), None))
+                    .collect::<ApiResult<_>>()?,
+            };
+            Ok(AmidaSpread {
+                members,
+                prizes: spread.prizes,
+                max: spread.max,
+            })
+        })
+        .collect::<ApiResult<_>>()?;
+    Ok(AmidaConstraints {
+        exclusions,
+        spreads,
+    })
+}
+
+/// 割り当て制約の参加者に名簿の表示名を添える
+fn describe_constraints(
+    players: &PlayerRegistry,
+    constraints: &AmidaConstraints,
+) -> AmidaConstraintsSpec {
+    AmidaConstraintsSpec {
+        exclusions: constraints
+            .exclusions
+            .iter()
+            .map(|exclusion| AmidaExclusionSpec {
+                guest: Some(players.display_name(exclusion.player)),
+                player: Some(exclusion.player),
+                prize: exclusion.prize,
+            })
+            .collect(),
+        spreads: constraints
+            .spreads
+            .iter()
+            .map(|spread| AmidaSpreadSpec {
+                members: guest_names(players, spread.members.iter().copied().map(Some)),
+                players: Some(spread.members.clone()),
+                prizes: spread.prizes.clone(),
+                max: spread.max,
+            })
+            .collect(),
+    }
+}
+
+/// 再抽選の記録に名簿の表示名を添える
+fn redraw_records(players: &PlayerRegistry, redraws: &[AmidaRedraw]) -> Vec<AmidaRedrawRecord> {
+    redraws
+        .iter()
+        .map(|redraw| AmidaRedrawRecord {
+            prize: redraw.prize,
+            absentee: players.display_name(redraw.absentee),
+            absentee_player: redraw.absentee,
+            winner: players.display_name(redraw.winner),
+            winner_player: redraw.winner,
+            stream: redraw.stream,
+        })
+        .collect()
+}
+
+/// ビンゴの申告 (終了したラウンドを含む)・当選者・ビンゴカードで参照されている参加者
+///
+/// 名簿から参加者を除く前に確認するため、名簿より先にロックする状態だけを参照します。
+fn referenced_players(state: &AppState) -> HashSet<PlayerId> {
+    let mut referenced = HashSet::new();
+    {
+        let session = state.session();
+        let game = state.game();
+        let claims = session
+            .rounds()
+            .iter()
+            .flat_map(|round| &round.claims)
+            .chain(&game.claims);
+        referenced.extend(claims.map(|claim| claim.player));
+    }
+    referenced.extend(state.cards().cards().iter().map(|card| card.player));
+    referenced.extend(state.winners().winners().iter().map(|winner| winner.player));
+    referenced
+}
+
+/// あみだくじの参加者数が枠の最大数以下であることを確認する
+///
+/// 枠番号は `u8` で表すため、超える場合は名簿を変更する前に `422 Unprocessable Entity` を返します。
+fn check_guest_count(count: usize) -> ApiResult<()> {
+    if count > MAX_AMIDA_SLOTS {
+        return Err(ApiError::InvalidRequest(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!(
+                "too many guests: {} (must be at most {})",
+                count, MAX_AMIDA_SLOTS
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// あみだくじの枠の参加者名を求める (空の枠は空の名前)
+fn guest_name(players: &PlayerRegistry, guest: Option<PlayerId>) -> String {
+    guest.map(|id| players.display_name(id)).unwrap_or_default()
+}
+
+/// あみだくじの枠の参加者名のリストを求める
+fn guest_names(
+    players: &PlayerRegistry,
+    guests: impl IntoIterator<Item = Option<PlayerId>>,
+) -> Vec<String> {
+    guests
+        .into_iter()
+        .map(|guest| guest_name(players, guest))
+        .collect()
+}
+
+/// 割り当て制約の結果から設定の状況を求める
+fn settings_status(constraint_error: Option<AmidaConstraintError>) -> AmidaSettingsStatus {
+    match constraint_error {
+        Some(_) => AmidaSettingsStatus::Unsatisfied,
+        None => AmidaSettingsStatus::Updated,
+    }
+}
+
+/// 結果の確定・公開状況から公開状況を求める
+fn result_status(ready: bool, fully_revealed: bool) -> AmidaResultStatus {
+    match (ready, fully_revealed) {
+        (false, _) => AmidaResultStatus::NotReady,
+        (true, false) => AmidaResultStatus::InProgress,
+        (true, true) => AmidaResultStatus::Complete,
+    }
+}
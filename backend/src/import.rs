@@ -0,0 +1,351 @@
+use crate::player::PlayerProfile;
+use encoding_rs::{SHIFT_JIS, UTF_8};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use utoipa::ToSchema;
+
+/// UTF-8 の BOM
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// 名前列として認識するヘッダー名
+const NAME_HEADERS: &[&str] = &["name", "名前", "氏名", "参加者", "参加者名"];
+/// チーム列として認識するヘッダー名
+const TEAM_HEADERS: &[&str] = &["team", "チーム", "所属", "部署", "グループ"];
+/// ふりがな列として認識するヘッダー名
+const FURIGANA_HEADERS: &[&str] = &["furigana", "kana", "ふりがな", "フリガナ", "よみがな"];
+
+/// 取り込むファイルの区切り文字
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportFormat {
+    /// カンマ区切り
+    Csv,
+    /// タブ区切り
+    Tsv,
+}
+
+impl ImportFormat {
+    /// 1行目にタブが含まれていればTSV、それ以外はCSVと判定する
+    fn detect(text: &str) -> Self {
+        match text.lines().next() {
+            Some(line) if line.contains('\t') => Self::Tsv,
+            _ => Self::Csv,
+        }
+    }
+
+    fn delimiter(self) -> u8 {
+        match self {
+            Self::Csv => b',',
+            Self::Tsv => b'\t',
+        }
+    }
+}
+
+/// 取り込むファイルの文字コード
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TextEncoding {
+    /// UTF-8 (BOMの有無は問わない)
+    Utf8,
+    /// Shift_JIS (日本語版Excelの既定の出力形式)
+    ShiftJis,
+}
+
+impl TextEncoding {
+    /// BOMがあるか、UTF-8として正しければUTF-8、それ以外はShift_JISと判定する
+    fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(UTF8_BOM) || std::str::from_utf8(bytes).is_ok() {
+            Self::Utf8
+        } else {
+            Self::ShiftJis
+        }
+    }
+
+    /// バイト列を文字列に変換する (UTF-8のBOMは取り除かれる)
+    fn decode(self, bytes: &[u8]) -> Result<String, ImportError> {
+        let encoding = match self {
+            Self::Utf8 => UTF_8,
+            Self::ShiftJis => SHIFT_JIS,
+        };
+        let (text, _, had_errors) = encoding.decode(bytes);
+        if had_errors {
+            return Err(ImportError::InvalidEncoding(self));
+        }
+        Ok(text.into_owned())
+    }
+}
+
+/// 列とヘッダー名の対応付け
+///
+/// 指定のない列は、既定のヘッダー名 (`name`, `名前`, `チーム` など) から自動で探す
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct ColumnMapping {
+    /// 名前列のヘッダー名
+    pub name: Option<String>,
+    /// チーム列のヘッダー名
+    pub team: Option<String>,
+    /// ふりがな列のヘッダー名
+    pub furigana: Option<String>,
+}
+
+/// 取り込みの設定
+#[derive(Debug, Clone, Default)]
+pub struct ImportOptions {
+    /// 区切り文字 (省略時は自動判定)
+    pub format: Option<ImportFormat>,
+    /// 文字コード (省略時は自動判定)
+    pub encoding: Option<TextEncoding>,
+    /// 列とヘッダー名の対応付け
+    pub columns: ColumnMapping,
+}
+
+/// 行単位の取り込みエラー
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct ImportRowError {
+    /// ファイル内の行番号 (1始まり、ヘッダー行を含む)
+    pub line: u64,
+    /// エラーの内容
+    pub message: String,
+}
+
+/// 取り込み結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportReport {
+    /// 取り込めた参加者 (ファイル内の順序)
+    pub players: Vec<PlayerProfile>,
+    /// 取り込めなかった行
+    pub errors: Vec<ImportRowError>,
+    /// 使用した区切り文字
+    pub format: ImportFormat,
+    /// 使用した文字コード
+    pub encoding: TextEncoding,
+}
+
+/// ファイル全体を取り込めない場合のエラー
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportError {
+    /// 指定 (または判定) した文字コードで読めない
+    InvalidEncoding(TextEncoding),
+    /// ヘッダー行がない
+    MissingHeader,
+    /// 名前列が見つからない
+    MissingNameColumn,
+    /// 指定されたヘッダー名の列が見つからない
+    UnknownColumn(String),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidEncoding(encoding) => write!(f, "file is not valid {:?} text", encoding),
+            Self::MissingHeader => write!(f, "header row is missing"),
+            Self::MissingNameColumn => write!(f, "name column was not found in the header"),
+            Self::UnknownColumn(header) => write!(f, "column '{}' was not found", header),
+        }
+    }
+}
+
+/// ヘッダー行から列の位置を探す
+///
+/// # Arguments
+/// * `headers` - ヘッダー行
+/// * `explicit` - 指定されたヘッダー名
+/// * `aliases` - 指定がない場合に探すヘッダー名
+fn find_column(
+    headers: &csv::StringRecord,
+    explicit: Option<&str>,
+    aliases: &[&str],
+) -> Result<Option<usize>, ImportError> {
+    let position = |name: &str| {
+        headers
+            .iter()
+            .position(|h| h.trim().eq_ignore_ascii_case(name.trim()))
+    };
+    match explicit {
+        Some(name) => position(name)
+            .map(Some)
+            .ok_or_else(|| ImportError::UnknownColumn(name.to_string())),
+        None => Ok(aliases.iter().find_map(|alias| position(alias))),
+    }
+}
+
+/// CSV/TSVファイルから参加者リストを取り込む
+///
+/// 1行目はヘッダー行として扱う。名前が空の行や重複した名前の行は
+/// 取り込まずに行単位のエラーとして報告し、それ以外の行の取り込みは続行する。
+/// 全ての列が空の行は読み飛ばす。
+///
+/// # Arguments
+/// * `bytes` - ファイルの内容
+/// * `options` - 取り込みの設定
+///
+/// # Returns
+/// * `Result<ImportReport, ImportError>` - 取り込み結果
+pub fn import_players(bytes: &[u8], options: &ImportOptions) -> Result<ImportReport, ImportError> {
+    let encoding = options
+        .encoding
+        .unwrap_or_else(|| TextEncoding::detect(bytes));
+    let text = encoding.decode(bytes)?;
+    let format = options
+        .format
+        .unwrap_or_else(|| ImportFormat::detect(&text));
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(format.delimiter())
+        .flexible(true)
+        .from_reader(text.as_bytes());
+
+    let headers = reader
+        .headers()
+        .map_err(|_| ImportError::MissingHeader)?
+        .clone();
+    if headers.iter().all(|h| h.trim().is_empty()) {
+        return Err(ImportError::MissingHeader);
+    }
+
+    let columns = &options.columns;
+    let name_column = find_column(&headers, columns.name.as_deref(), NAME_HEADERS)?
+        .ok_or(ImportError::MissingNameColumn)?;
+    let team_column = find_column(&headers, columns.team.as_deref(), TEAM_HEADERS)?;
+    let furigana_column = find_column(&headers, columns.furigana.as_deref(), FURIGANA_HEADERS)?;
+
+    // csv の行番号は空行を数えず、位置も読み飛ばした空行の先頭を指すため、
+    // 空行を除いたバイト位置からファイル上の行番号を求める
+    let line_of = |position: Option<&csv::Position>| {
+        let Some(position) = position else {
+            return 0;
+        };
+        let bytes = text.as_bytes();
+        let mut start = position.byte() as usize;
+        while start < bytes.len() && matches!(bytes[start], b'\r' | b'\n') {
+            start += 1;
+        }
+        bytes[..start].iter().filter(|&&b| b == b'\n').count() as u64 + 1
+    };
+
+    let mut players: Vec<PlayerProfile> = Vec::new();
+    let mut errors = Vec::new();
+    for record in reader.records() {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                errors.push(ImportRowError {
+                    line: line_of(e.position()),
+                    message: e.to_string(),
+                });
+                continue;
+            }
+        };
+        let line = line_of(record.position());
+        if record.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+
+        let field = |column: Option<usize>| {
+            column
+                .and_then(|c| record.get(c))
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let Some(name) = field(Some(name_column)) else {
+            errors.push(ImportRowError {
+                line,
+                message: "name is empty".to_string(),
+            });
+            continue;
+        };
+        if players.iter().any(|p| p.name == name) {
+            errors.push(ImportRowError {
+                line,
+                message: format!("duplicate name '{}'", name),
+            });
+            continue;
+        }
+
+        players.push(PlayerProfile {
+            name,
+            furigana: field(furigana_column),
+            team: field(team_column),
+            avatar: None,
+        });
+    }
+
+    Ok(ImportReport {
+        players,
+        errors,
+        format,
+        encoding,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_utf8_csv_with_bom() {
+        let mut bytes = UTF8_BOM.to_vec();
+        bytes.extend_from_slice(
+            "名前,チーム,ふりがな\n山田 太郎,営業,やまだ たろう\n鈴木 花子,,すずき はなこ\n"
+                .as_bytes(),
+        );
+
+        let report = import_players(&bytes, &ImportOptions::default()).unwrap();
+        assert_eq!(report.encoding, TextEncoding::Utf8);
+        assert_eq!(report.format, ImportFormat::Csv);
+        assert!(report.errors.is_empty());
+        assert_eq!(
+            report.players[0],
+            PlayerProfile {
+                name: "山田 太郎".to_string(),
+                furigana: Some("やまだ たろう".to_string()),
+                team: Some("営業".to_string()),
+                avatar: None,
+            }
+        );
+        assert_eq!(report.players[1].team, None);
+    }
+
+    #[test]
+    fn test_import_shift_jis_tsv() {
+        let (bytes, _, _) = SHIFT_JIS.encode("氏名\t所属\n佐藤\t開発\n");
+
+        let report = import_players(&bytes, &ImportOptions::default()).unwrap();
+        assert_eq!(report.encoding, TextEncoding::ShiftJis);
+        assert_eq!(report.format, ImportFormat::Tsv);
+        assert_eq!(report.players[0].name, "佐藤");
+        assert_eq!(report.players[0].team.as_deref(), Some("開発"));
+    }
+
+    #[test]
+    fn test_import_reports_row_errors() {
+        let bytes = "name,team\nAlice,A\n,B\n\nAlice,C\nBob,D\n".as_bytes();
+
+        let report = import_players(bytes, &ImportOptions::default()).unwrap();
+        let names: Vec<&str> = report.players.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["Alice", "Bob"]);
+        let lines: Vec<u64> = report.errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![3, 5]);
+    }
+
+    #[test]
+    fn test_import_with_explicit_columns() {
+        let bytes = "社員名,部\nAlice,A\n".as_bytes();
+        let options = ImportOptions {
+            columns: ColumnMapping {
+                name: Some("社員名".to_string()),
+                team: Some("部".to_string()),
+                furigana: None,
+            },
+            ..ImportOptions::default()
+        };
+
+        let report = import_players(bytes, &options).unwrap();
+        assert_eq!(report.players[0].team.as_deref(), Some("A"));
+
+        assert_eq!(
+            import_players(bytes, &ImportOptions::default()),
+            Err(ImportError::MissingNameColumn)
+        );
+    }
+}
@@ -0,0 +1,256 @@
+//! 旧API (バージョンなし) の互換ルート
+//!
+//! `/api/v1` より前のクライアントのために残しているルートです。
+//! レスポンスには `Deprecation` ヘッダーと移行先を示す `Link` ヘッダーが付きます。
+//! 新しい機能はこちらには追加しません。
+
+use crate::error::{ApiResult, ErrorResponse};
+use crate::handlers::{
+    self, require_guests, update_amida, AmidaAbsentRequest, AmidaAbsentResponse, AmidaPatchRequest,
+    AmidaRequest, AmidaResponse, AmidaResultResponse, AmidaRevealRequest, AmidaRevealResponse,
+    DrawStatus, ExportQuery, ImportQuery, ImportResponse, NumberResponse,
+};
+use crate::state::AppState;
+use axum::{
+    body::Bytes,
+    extract::{
+        rejection::{JsonRejection, QueryRejection},
+        Query, State,
+    },
+    response::IntoResponse,
+    Json,
+};
+
+/// 次のビンゴ番号を抽選する
+///
+/// 状態を変更する `GET` のため非推奨です。`POST /v1/games/default/draws` を使用してください。
+///
+/// # Returns
+/// * `ApiResult<Json<NumberResponse>>` - 抽選結果 (出尽くした場合は `number` が null で `status` が `game_over`)
+#[utoipa::path(
+    get,
+    path = "/next_number",
+    operation_id = "legacy_next_number",
+    tag = "legacy",
+    security(("admin_token" = []), ("bearer" = [])),
+    responses(
+        (status = 200, description = "抽選結果", body = NumberResponse),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+        (status = 409, description = "抽選できないフェーズ (`invalid_phase`)", body = ErrorResponse),
+    )
+)]
+#[deprecated]
+pub async fn get_next_number(State(state): State<AppState>) -> ApiResult<Json<NumberResponse>> {
+    let seed = state.round_seed();
+    let mut game = state.game();
+    let number = game.draw()?;
+    let status = if number.is_some() {
+        DrawStatus::Drawn
+    } else {
+        DrawStatus::GameOver
+    };
+
+    Ok(Json(NumberResponse {
+        number,
+        history: game.history.clone(),
+        status,
+        seed,
+    }))
+}
+
+/// ゲームをリセットする
+///
+/// `DELETE /v1/games/default/draws` を使用してください。
+///
+/// # Returns
+/// * `ApiResult<Json<NumberResponse>>` - リセット後の状態
+#[utoipa::path(
+    post,
+    path = "/reset",
+    operation_id = "legacy_reset",
+    tag = "legacy",
+    security(("admin_token" = []), ("bearer" = [])),
+    responses(
+        (status = 200, description = "リセット後の状態", body = NumberResponse),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+        (status = 409, description = "保管済みのゲーム (`invalid_phase`)", body = ErrorResponse),
+    )
+)]
+#[deprecated]
+pub async fn reset_game(State(state): State<AppState>) -> ApiResult<Json<NumberResponse>> {
+    state.reset_game()?;
+
+    Ok(Json(NumberResponse {
+        number: None,
+        history: Vec::new(),
+        status: DrawStatus::Reset,
+        seed: state.round_seed(),
+    }))
+}
+
+/// あみだくじの設定を取得する
+///
+/// `GET /v1/amida` を使用してください。
+#[utoipa::path(
+    get,
+    path = "/amida",
+    operation_id = "legacy_get_amida",
+    tag = "legacy",
+    responses((status = 200, description = "現在の設定", body = AmidaResponse))
+)]
+#[deprecated]
+pub async fn get_amida(state: State<AppState>) -> Json<AmidaResponse> {
+    handlers::get_amida(state).await
+}
+
+/// あみだくじの設定を更新する
+///
+/// 省略した割り当てルール・制約は現在の値を維持します。`PATCH /v1/amida` を使用してください。
+#[utoipa::path(
+    post,
+    path = "/amida",
+    operation_id = "legacy_set_amida",
+    tag = "legacy",
+    security(("admin_token" = []), ("bearer" = [])),
+    request_body = AmidaRequest,
+    responses(
+        (status = 200, description = "更新後の設定", body = AmidaResponse),
+        (status = 400, description = "リクエストの形式が正しくない (`invalid_request`)", body = ErrorResponse),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+    )
+)]
+#[deprecated]
+pub async fn set_amida(
+    State(state): State<AppState>,
+    payload: Result<Json<AmidaRequest>, JsonRejection>,
+) -> ApiResult<Json<AmidaResponse>> {
+    let Json(payload) = payload?;
+    require_guests(&payload)?;
+    Ok(Json(update_amida(
+        &state,
+        AmidaPatchRequest {
+            items: payload.items,
+            players: payload.players,
+            rule: payload.rule,
+            constraints: payload.constraints,
+        },
+    )?))
+}
+
+/// あみだくじの結果を取得する
+///
+/// `GET /v1/amida/result` を使用してください。
+#[utoipa::path(
+    get,
+    path = "/amida/result",
+    operation_id = "legacy_get_amida_result",
+    tag = "legacy",
+    responses((status = 200, description = "公開済みの結果", body = AmidaResultResponse))
+)]
+#[deprecated]
+pub async fn get_amida_result(state: State<AppState>) -> Json<AmidaResultResponse> {
+    handlers::get_amida_result(state).await
+}
+
+/// あみだくじの結果を1件公開する
+///
+/// エラーは `POST /v1/amida/reveals` と同じです。こちらを使用してください。
+#[utoipa::path(
+    post,
+    path = "/amida/reveal",
+    operation_id = "legacy_reveal_amida",
+    tag = "legacy",
+    security(("admin_token" = []), ("bearer" = [])),
+    request_body = AmidaRevealRequest,
+    responses(
+        (status = 200, description = "公開された結果", body = AmidaRevealResponse),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+    )
+)]
+#[deprecated]
+pub async fn reveal_amida(
+    state: State<AppState>,
+    payload: Result<Json<AmidaRevealRequest>, JsonRejection>,
+) -> ApiResult<Json<AmidaRevealResponse>> {
+    handlers::reveal_amida(state, payload).await
+}
+
+/// あみだくじの参加者を欠席として記録し、景品を再抽選する
+///
+/// エラーは `POST /v1/amida/absences` と同じです。こちらを使用してください。
+#[utoipa::path(
+    post,
+    path = "/amida/absent",
+    operation_id = "legacy_mark_amida_absent",
+    tag = "legacy",
+    security(("admin_token" = []), ("bearer" = [])),
+    request_body = AmidaAbsentRequest,
+    responses(
+        (status = 200, description = "再抽選の記録", body = AmidaAbsentResponse),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+    )
+)]
+#[deprecated]
+pub async fn mark_amida_absent(
+    state: State<AppState>,
+    payload: Result<Json<AmidaAbsentRequest>, JsonRejection>,
+) -> ApiResult<Json<AmidaAbsentResponse>> {
+    handlers::mark_amida_absent(state, payload).await
+}
+
+/// CSV/TSVファイルから参加者リストを取り込む
+///
+/// エラーは `POST /v1/amida/imports` と同じです。こちらを使用してください。
+#[utoipa::path(
+    post,
+    path = "/amida/import",
+    operation_id = "legacy_import_amida",
+    tag = "legacy",
+    security(("admin_token" = []), ("bearer" = [])),
+    params(ImportQuery),
+    request_body(content = String, content_type = "text/csv", description = "CSV/TSVファイル (UTF-8 または Shift_JIS)"),
+    responses(
+        (status = 200, description = "取り込み結果", body = ImportResponse),
+        (status = 401, description = "管理者トークンがない", body = ErrorResponse),
+        (status = 403, description = "管理者トークンが正しくない", body = ErrorResponse),
+    )
+)]
+#[deprecated]
+pub async fn import_amida(
+    state: State<AppState>,
+    query: Result<Query<ImportQuery>, QueryRejection>,
+    body: Bytes,
+) -> ApiResult<Json<ImportResponse>> {
+    handlers::import_amida(state, query, body).await
+}
+
+/// ビンゴとあみだくじの結果一式をダウンロードする
+///
+/// `GET /v1/export` を使用してください。
+#[utoipa::path(
+    get,
+    path = "/export",
+    operation_id = "legacy_export_results",
+    tag = "legacy",
+    params(ExportQuery),
+    responses(
+        (status = 200, description = "結果一式のファイル", content(
+            (String = "application/json"),
+            (String = "text/csv"),
+            (String = "text/html"),
+        )),
+        (status = 400, description = "リクエストの形式が正しくない (`invalid_request`)", body = ErrorResponse),
+    )
+)]
+#[deprecated]
+pub async fn export_results(
+    state: State<AppState>,
+    query: Result<Query<ExportQuery>, QueryRejection>,
+) -> ApiResult<impl IntoResponse> {
+    handlers::export_results(state, query).await
+}
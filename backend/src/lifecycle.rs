@@ -0,0 +1,262 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use utoipa::ToSchema;
+
+/// ビンゴゲームのフェーズ
+///
+/// ```text
+/// setup ──lock──> locked ──start──> running ──finish──> finished ──archive──> archived
+///   ^  <──unlock──         pause │ ^ resume  ^
+///   │                            v │         │
+///   │                           paused ──finish
+///   └──────── reset (archived 以外の全てのフェーズから) ────────
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GamePhase {
+    /// 準備中 (カードの発行が可能。シード値は確定していない)
+    Setup,
+    /// シード値とカードを確定した (抽選開始前)
+    Locked,
+    /// 抽選中
+    Running,
+    /// 一時停止中
+    Paused,
+    /// 終了した
+    Finished,
+    /// 記録として保管した (以降は変更できない)
+    Archived,
+}
+
+/// フェーズを変更する操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PhaseAction {
+    /// シード値とカードを確定する
+    Lock,
+    /// 確定を取り消し、準備中に戻す
+    Unlock,
+    /// 抽選を開始する
+    Start,
+    /// 抽選を一時停止する
+    Pause,
+    /// 抽選を再開する
+    Resume,
+    /// ゲームを終了する
+    Finish,
+    /// 結果を保管する
+    Archive,
+    /// 抽選履歴を消去し、準備中に戻す
+    Reset,
+}
+
+/// 全ての操作 (許可される操作の一覧を求めるために使う)
+const ALL_ACTIONS: [PhaseAction; 8] = [
+    PhaseAction::Lock,
+    PhaseAction::Unlock,
+    PhaseAction::Start,
+    PhaseAction::Pause,
+    PhaseAction::Resume,
+    PhaseAction::Finish,
+    PhaseAction::Archive,
+    PhaseAction::Reset,
+];
+
+impl GamePhase {
+    /// 操作を行った後のフェーズ
+    ///
+    /// # Returns
+    /// * `Option<GamePhase>` - 遷移先のフェーズ。現在のフェーズで許可されない操作の場合はNone
+    pub fn next(self, action: PhaseAction) -> Option<GamePhase> {
+        use GamePhase::*;
+        match (self, action) {
+            (Setup, PhaseAction::Lock) => Some(Locked),
+            (Locked, PhaseAction::Unlock) => Some(Setup),
+            (Locked, PhaseAction::Start) => Some(Running),
+            (Running, PhaseAction::Pause) => Some(Paused),
+            (Paused, PhaseAction::Resume) => Some(Running),
+            (Running | Paused, PhaseAction::Finish) => Some(Finished),
+            (Finished, PhaseAction::Archive) => Some(Archived),
+            (Setup | Locked | Running | Paused | Finished, PhaseAction::Reset) => Some(Setup),
+            _ => None,
+        }
+    }
+
+    /// 現在のフェーズで許可される操作
+    pub fn actions(self) -> Vec<PhaseAction> {
+        ALL_ACTIONS
+            .into_iter()
+            .filter(|&action| self.next(action).is_some())
+            .collect()
+    }
+
+    /// 抽選できるかどうか
+    ///
+    /// 準備中・確定済みのフェーズでは、最初の抽選で自動的に確定・開始します。
+    pub fn can_draw(self) -> bool {
+        matches!(self, Self::Setup | Self::Locked | Self::Running)
+    }
+}
+
+impl fmt::Display for GamePhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Setup => "setup",
+            Self::Locked => "locked",
+            Self::Running => "running",
+            Self::Paused => "paused",
+            Self::Finished => "finished",
+            Self::Archived => "archived",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// フェーズの遷移の記録
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct PhaseTransition {
+    /// 遷移前のフェーズ
+    pub from: GamePhase,
+    /// 遷移後のフェーズ
+    pub to: GamePhase,
+    /// 行った操作
+    pub action: PhaseAction,
+    /// 遷移した日時
+    #[schema(value_type = String, format = DateTime)]
+    pub at: DateTime<Local>,
+}
+
+/// 現在のフェーズでは行えない操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GamePhaseError {
+    /// 現在のフェーズからはその操作で遷移できない
+    IllegalTransition {
+        /// 現在のフェーズ
+        phase: GamePhase,
+        /// 要求された操作
+        action: PhaseAction,
+    },
+    /// 現在のフェーズでは抽選できない
+    CannotDraw(GamePhase),
+}
+
+impl fmt::Display for GamePhaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IllegalTransition { phase, action } => {
+                let action = serde_json::to_value(action).unwrap_or_default();
+                write!(
+                    f,
+                    "cannot {} the game while it is {}",
+                    action.as_str().unwrap_or_default(),
+                    phase
+                )
+            }
+            Self::CannotDraw(phase) => {
+                write!(f, "cannot draw a number while the game is {}", phase)
+            }
+        }
+    }
+}
+
+/// フェーズと遷移の記録を管理する
+#[derive(Debug, Clone)]
+pub struct Lifecycle {
+    /// 現在のフェーズ
+    phase: GamePhase,
+    /// これまでの遷移の記録 (古い順)
+    transitions: Vec<PhaseTransition>,
+}
+
+impl Default for Lifecycle {
+    fn default() -> Self {
+        Self {
+            phase: GamePhase::Setup,
+            transitions: Vec::new(),
+        }
+    }
+}
+
+impl Lifecycle {
+    /// 現在のフェーズ
+    pub fn phase(&self) -> GamePhase {
+        self.phase
+    }
+
+    /// これまでの遷移の記録
+    pub fn transitions(&self) -> &[PhaseTransition] {
+        &self.transitions
+    }
+
+    /// 操作を行い、遷移を記録する
+    ///
+    /// # Returns
+    /// * `Result<GamePhase, GamePhaseError>` - 遷移後のフェーズ
+    pub fn apply(&mut self, action: PhaseAction) -> Result<GamePhase, GamePhaseError> {
+        let from = self.phase;
+        let to = from.next(action).ok_or(GamePhaseError::IllegalTransition {
+            phase: from,
+            action,
+        })?;
+        self.phase = to;
+        self.transitions.push(PhaseTransition {
+            from,
+            to,
+            action,
+            at: Local::now(),
+        });
+        Ok(to)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legal_transitions() {
+        let mut lifecycle = Lifecycle::default();
+        for (action, phase) in [
+            (PhaseAction::Lock, GamePhase::Locked),
+            (PhaseAction::Unlock, GamePhase::Setup),
+            (PhaseAction::Lock, GamePhase::Locked),
+            (PhaseAction::Start, GamePhase::Running),
+            (PhaseAction::Pause, GamePhase::Paused),
+            (PhaseAction::Resume, GamePhase::Running),
+            (PhaseAction::Finish, GamePhase::Finished),
+            (PhaseAction::Archive, GamePhase::Archived),
+        ] {
+            assert_eq!(lifecycle.apply(action), Ok(phase));
+        }
+        assert_eq!(lifecycle.transitions().len(), 8);
+        assert_eq!(lifecycle.transitions()[3].from, GamePhase::Locked);
+        assert_eq!(lifecycle.transitions()[3].action, PhaseAction::Start);
+    }
+
+    #[test]
+    fn test_illegal_transitions_are_refused() {
+        let mut lifecycle = Lifecycle::default();
+        assert_eq!(
+            lifecycle.apply(PhaseAction::Start),
+            Err(GamePhaseError::IllegalTransition {
+                phase: GamePhase::Setup,
+                action: PhaseAction::Start
+            })
+        );
+        assert!(lifecycle.transitions().is_empty());
+
+        assert_eq!(GamePhase::Archived.actions(), Vec::new());
+        assert!(GamePhase::Paused.next(PhaseAction::Lock).is_none());
+        assert!(GamePhase::Running.next(PhaseAction::Unlock).is_none());
+        assert!(!GamePhase::Paused.can_draw());
+        assert_eq!(
+            GamePhaseError::IllegalTransition {
+                phase: GamePhase::Finished,
+                action: PhaseAction::Pause
+            }
+            .to_string(),
+            "cannot pause the game while it is finished"
+        );
+    }
+}
@@ -1,26 +1,42 @@
+mod auth;
+mod autodraw;
+mod card;
+mod config;
+mod cors;
 mod domain;
+mod error;
+mod export;
+mod frontend;
 mod handlers;
+mod import;
+mod legacy;
+mod lifecycle;
+mod openapi;
+mod player;
+mod qr;
 mod rng;
+mod routes;
+mod session;
+mod sheet;
+mod simulation;
 mod state;
+mod stats;
+mod tiebreak;
+mod winner;
 
-use axum::{
-    Router,
-    http::Method,
-    routing::{get, post},
-};
-use handlers::{get_amida, get_amida_result, get_next_number, reset_game, set_amida};
+use axum::Router;
+use card::CardKey;
+use config::{CardsArgs, Command, Config, SimulateArgs};
 use state::AppState;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{self, BufRead};
-use std::path::Path;
-use tower_http::cors::{Any, CorsLayer};
-
-
-const SEEDS_FILE_PATH:&str = "seeds.txt";
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process;
 
 /// シードファイルを読み込んでシード値を計算する
 ///
-/// `seeds.txt` ファイルから数値を読み込み、それらを掛け合わせてシード値を生成します。
+/// シードファイル（デフォルトは `seeds.txt`）から数値を読み込み、それらを掛け合わせてシード値を生成します。
 /// ファイルが存在しない場合やエラーが発生した場合は 0 を返します。
 ///
 /// # Arguments
@@ -28,28 +44,25 @@ const SEEDS_FILE_PATH:&str = "seeds.txt";
 ///
 /// # Returns
 /// * `u32` - 計算されたシード値
-fn calculate_seed_from_file(path: &str) -> u32 {
-    let path = Path::new(path);
+fn calculate_seed_from_file(path: &Path) -> u32 {
     if !path.exists() {
-        println!("Warning: seeds.txt not found. Using default seed.");
+        eprintln!("Warning: {} not found. Using default seed.", path.display());
         return 0;
     }
 
-    let file = match File::open(&path) {
+    let file = match File::open(path) {
         Ok(file) => file,
         Err(e) => {
-            eprintln!("Error opening seeds.txt: {}", e);
+            eprintln!("Error opening {}: {}", path.display(), e);
             return 0;
         }
     };
 
     let mut seed: u32 = 1;
-    for line in io::BufReader::new(file).lines() {
-        if let Ok(line) = line {
-            if let Ok(num) = line.trim().parse::<u32>() {
-                // オーバーフローしてもラップアラウンドするように wrapping_mul を使用
-                seed = seed.wrapping_mul(num);
-            }
+    for line in io::BufReader::new(file).lines().map_while(Result::ok) {
+        if let Ok(num) = line.trim().parse::<u32>() {
+            // オーバーフローしてもラップアラウンドするように wrapping_mul を使用
+            seed = seed.wrapping_mul(num);
         }
     }
 
@@ -57,38 +70,189 @@ fn calculate_seed_from_file(path: &str) -> u32 {
     seed
 }
 
+/// 印刷用のビンゴカードのシートを出力します。
+///
+/// カードIDが `first` から `first + count - 1` のカードを、シード値から生成して書き出します。
+/// SVGは1ページにつき1ファイルを出力します。
+///
+/// # Arguments
+/// * `key` - QRコードに署名する鍵
+/// * `seed` - シード値
+/// * `args` - `cards` サブコマンドの引数
+///
+/// # Returns
+/// * `io::Result<Vec<PathBuf>>` - 書き出したファイルのパス
+fn print_cards(key: &CardKey, seed: u32, args: &CardsArgs) -> io::Result<Vec<PathBuf>> {
+    let cards: Vec<_> = (args.first..args.first.saturating_add(args.count))
+        .map(|id| sheet::PrintedCard::generate(key, seed, id))
+        .collect();
+    let output = args
+        .output
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(format!("cards.{}", args.format.extension())));
+
+    match args.format {
+        sheet::SheetFormat::Pdf => {
+            fs::write(&output, sheet::render_pdf(&cards))?;
+            Ok(vec![output])
+        }
+        sheet::SheetFormat::Svg => {
+            let pages = sheet::render_svg(&cards);
+            if pages.len() == 1 {
+                fs::write(&output, &pages[0])?;
+                return Ok(vec![output]);
+            }
+            let stem = output
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "cards".to_string());
+            pages
+                .iter()
+                .enumerate()
+                .map(|(i, page)| {
+                    let path = output.with_file_name(format!(
+                        "{}-{}.{}",
+                        stem,
+                        i + 1,
+                        args.format.extension()
+                    ));
+                    fs::write(&path, page)?;
+                    Ok(path)
+                })
+                .collect()
+        }
+    }
+}
+
+/// 仮想カードでゲームを繰り返しシミュレーションし、結果を出力する
+///
+/// # Arguments
+/// * `config` - 設定 (抽選間隔を省略した場合は自動抽選の間隔を使う)
+/// * `seed` - シードファイルから求めたシード値 (シード値を省略した場合に使う)
+/// * `args` - `simulate` サブコマンドの引数
+fn print_simulation(config: &Config, seed: u32, args: &SimulateArgs) -> Result<(), String> {
+    let draw_interval = match args.draw_interval {
+        Some(secs) => autodraw::interval_from_secs(secs)
+            .map_err(|e| format!("invalid draw interval: {}", e))?,
+        None => config.auto_draw_interval,
+    };
+    let settings = simulation::SimulationSettings {
+        players: args.players as usize,
+        games: args.games as usize,
+        pattern: args.pattern,
+        seed: args.seed.unwrap_or(seed),
+        draw_interval_secs: draw_interval.as_secs(),
+        overhead_secs: args.overhead,
+        event_secs: args.event_minutes * 60,
+    };
+    let report = simulation::run(settings).ok_or("nothing to simulate")?;
+    if args.json {
+        let json = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+        println!("{}", json);
+    } else {
+        print!("{}", report);
+    }
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() {
+    // 設定の読み込み
+    // 設定ファイル (bingo.toml) < 環境変数 < コマンドライン引数 の順に上書きし、起動時に検証します。
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        }
+    };
+    match &config.command {
+        // JSONを標準出力に書き出す場合は、設定の一覧を標準エラー出力に回す
+        Some(Command::Simulate(args)) if args.json => eprintln!("{}", config),
+        _ => println!("{}", config),
+    }
+
     // シードの計算
-    // seeds.txt から数値を読み込み、それらを掛け合わせてシード値を生成します。
+    // シードファイルから数値を読み込み、それらを掛け合わせてシード値を生成します。
     // これにより、外部からシード値を制御し、再現性を確保します。
-    let seed = calculate_seed_from_file(SEEDS_FILE_PATH);
+    let seed = calculate_seed_from_file(&config.seeds_file);
+
+    // 印刷用カードの出力
+    // サーバーは起動せず、シード値から生成したカードをファイルに書き出して終了します。
+    if let Some(Command::Cards(args)) = &config.command {
+        let variant = domain::BingoVariant::from_max_number(config.bingo_max_number);
+        if variant != domain::BingoVariant::Ball75 {
+            eprintln!("Error: {}", card::CardError::UnsupportedVariant(variant));
+            process::exit(1);
+        }
+        // 起動ごとに生成する鍵で署名すると、サーバーで検証できないカードになる
+        let Some(secret) = config.card_key_secret() else {
+            eprintln!("Error: printed cards need a card secret (--card-secret)");
+            process::exit(1);
+        };
+        match print_cards(&CardKey::new(secret), seed, args) {
+            Ok(paths) => {
+                for path in paths {
+                    println!("wrote {}", path.display());
+                }
+            }
+            Err(e) => {
+                eprintln!("Error: cannot write cards: {}", e);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    // シミュレーション
+    // サーバーは起動せず、仮想カードでゲームを繰り返してラウンドの長さを見積もります。
+    if let Some(Command::Simulate(args)) = &config.command {
+        let variant = domain::BingoVariant::from_max_number(config.bingo_max_number);
+        if variant != domain::BingoVariant::Ball75 {
+            eprintln!("Error: {}", card::CardError::UnsupportedVariant(variant));
+            process::exit(1);
+        }
+        if let Err(e) = print_simulation(&config, seed, args) {
+            eprintln!("Error: cannot simulate: {}", e);
+            process::exit(1);
+        }
+        return;
+    }
 
     // 初期状態の作成
     // アプリケーション全体で共有される状態（ビンゴ、あみだくじ）を初期化します。
-    let state = AppState::new(seed);
+    let state = AppState::new(seed, &config);
+    if let Some(pin) = state.admin.host_pin() {
+        println!("Host PIN (admin operations): {}", pin);
+    }
 
     // CORS設定
-    // 開発環境向けに、全てのオリジンからのリクエストを許可しています。
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods([Method::GET, Method::POST])
-        .allow_headers(Any);
+    // 設定で許可されたオリジン・メソッド・ヘッダーからのリクエストのみ許可します。
+    let cors = config.cors.layer();
 
     // ルーティング設定
-    // /next_number: ビンゴの次の数字を引く
-    // /reset: ゲームをリセットする
-    // /amida: あみだくじの設定（GET: 取得, POST: 更新）
-    // /amida/result: あみだくじの結果（誰がどの番号か）を取得
-    let app = Router::new()
-        .route("/next_number", get(get_next_number))
-        .route("/reset", post(reset_game))
-        .route("/amida", post(set_amida).get(get_amida))
-        .route("/amida/result", get(get_amida_result))
+    // 状態を変更する操作には管理者トークンまたは司会者用PINが必要です。
+    // ルートの一覧は routes.rs、APIドキュメントは /api/docs (OpenAPI: /api/openapi.json) を参照してください。
+    // APIは /api 以下で提供し、それ以外のパスではフロントエンドを配信します。
+    let app = config
+        .frontend
+        .serve(
+            Router::new()
+                .merge(openapi::docs_router())
+                .nest("/api", routes::api_router(&state)),
+        )
         .layer(cors)
         .with_state(state);
 
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
+    let listener = match tokio::net::TcpListener::bind(config.bind).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Error: cannot listen on {}: {}", config.bind, e);
+            process::exit(1);
+        }
+    };
     println!("listening on {}", listener.local_addr().unwrap());
+    // 管理者トークンの失敗をクライアントごとに数えるため、接続元のアドレスを渡します。
+    let app = app.into_make_service_with_connect_info::<SocketAddr>();
     axum::serve(listener, app).await.unwrap();
 }
@@ -0,0 +1,293 @@
+use crate::auth::ADMIN_TOKEN_HEADER;
+use crate::state::AppState;
+use crate::{
+    autodraw, card, domain, error, export, handlers, import, legacy, lifecycle, player, session,
+    sheet, stats, tiebreak, winner,
+};
+use axum::Router;
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, Http, HttpAuthScheme, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+use utoipa_swagger_ui::SwaggerUi;
+
+/// APIドキュメントビューアのパス
+pub const DOCS_PATH: &str = "/api/docs";
+/// OpenAPIドキュメント (JSON) のパス
+pub const OPENAPI_PATH: &str = "/api/openapi.json";
+
+/// ハンドラの型から生成するOpenAPIドキュメント
+///
+/// パスは `/api` からの相対パスで、`servers` に `/api` を指定しています。
+#[allow(deprecated)]
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Christmas Bingo API"),
+    servers((url = "/api")),
+    paths(
+        handlers::get_game,
+        handlers::get_game_status,
+        handlers::create_draw,
+        handlers::delete_draws,
+        handlers::create_transition,
+        handlers::create_claim,
+        handlers::get_auto_draw,
+        handlers::start_auto_draw,
+        handlers::update_auto_draw,
+        handlers::stop_auto_draw,
+        handlers::get_session,
+        handlers::create_round,
+        handlers::get_round,
+        handlers::update_round,
+        handlers::list_players,
+        handlers::create_player,
+        handlers::get_player,
+        handlers::update_player,
+        handlers::delete_player,
+        handlers::list_cards,
+        handlers::get_card_sheet,
+        handlers::verify_card,
+        handlers::create_card,
+        handlers::get_player_card,
+        handlers::create_card_claim,
+        handlers::get_winners,
+        handlers::create_winner,
+        handlers::replace_prizes,
+        handlers::get_tie_breaks,
+        handlers::create_tie_break,
+        handlers::get_statistics,
+        handlers::get_amida,
+        handlers::replace_amida,
+        handlers::patch_amida,
+        handlers::get_amida_result,
+        handlers::reveal_amida,
+        handlers::mark_amida_absent,
+        handlers::import_amida,
+        handlers::export_results,
+        legacy::get_next_number,
+        legacy::reset_game,
+        legacy::get_amida,
+        legacy::set_amida,
+        legacy::get_amida_result,
+        legacy::reveal_amida,
+        legacy::mark_amida_absent,
+        legacy::import_amida,
+        legacy::export_results,
+    ),
+    components(schemas(
+        handlers::DrawStatus,
+        handlers::AmidaSettingsStatus,
+        handlers::AmidaResultStatus,
+        handlers::NumberResponse,
+        handlers::GameResponse,
+        handlers::GameStatusResponse,
+        handlers::PhaseTransitionRequest,
+        handlers::AutoDrawRequest,
+        handlers::AutoDrawPatchRequest,
+        handlers::ClaimRequest,
+        handlers::ClaimResponse,
+        handlers::SessionResponse,
+        handlers::RoundSummary,
+        handlers::RoundResponse,
+        handlers::RoundRequest,
+        handlers::RoundPatchRequest,
+        handlers::PlayersResponse,
+        handlers::PlayerPatchRequest,
+        handlers::CardsResponse,
+        handlers::CardRequest,
+        handlers::CardVerificationRequest,
+        handlers::CardVerificationResponse,
+        handlers::PlayerCardResponse,
+        handlers::WinnerRequest,
+        handlers::PrizesRequest,
+        handlers::WinnersResponse,
+        handlers::TieBreakRequest,
+        handlers::TieBreakResponse,
+        handlers::TieBreaksResponse,
+        handlers::AmidaRequest,
+        handlers::AmidaPatchRequest,
+        handlers::AmidaExclusionSpec,
+        handlers::AmidaSpreadSpec,
+        handlers::AmidaConstraintsSpec,
+        handlers::AmidaRedrawRecord,
+        handlers::AmidaResponse,
+        handlers::AmidaRevealRequest,
+        handlers::AmidaRevealResponse,
+        handlers::AmidaAbsentRequest,
+        handlers::AmidaAbsentResponse,
+        handlers::ImportResponse,
+        handlers::AmidaResultResponse,
+        domain::BingoVariant,
+        domain::BingoClaim,
+        autodraw::AutoDrawState,
+        autodraw::AutoDrawStopReason,
+        autodraw::AutoDrawStatus,
+        lifecycle::GamePhase,
+        lifecycle::PhaseAction,
+        lifecycle::PhaseTransition,
+        session::WinPattern,
+        session::RoundSettings,
+        session::Round,
+        player::Player,
+        player::PlayerProfile,
+        card::BingoCard,
+        sheet::SheetFormat,
+        winner::BingoPrize,
+        winner::Winner,
+        tiebreak::TieBreakMethod,
+        tiebreak::TieBreakDraw,
+        tiebreak::TieBreak,
+        stats::DrawStatistics,
+        stats::NumberStats,
+        stats::FirstBingoStats,
+        stats::ChiSquareTest,
+        stats::WinnerProbability,
+        domain::AmidaRule,
+        domain::AmidaConstraintError,
+        import::ImportFormat,
+        import::TextEncoding,
+        import::ImportRowError,
+        export::ExportFormat,
+        error::ErrorCode,
+        error::ErrorResponse,
+    )),
+    modifiers(&SecurityAddon),
+    tags(
+        (name = "bingo", description = "ビンゴの抽選"),
+        (name = "players", description = "参加者名簿 (ビンゴとあみだくじで共有)"),
+        (name = "cards", description = "ビンゴカード (参加コードでスマートフォンに表示する)"),
+        (name = "winners", description = "ビンゴの景品と当選者"),
+        (name = "statistics", description = "抽選の統計とシミュレーション"),
+        (name = "amida", description = "あみだくじ (景品の割り当て)"),
+        (name = "export", description = "結果のダウンロード"),
+        (name = "legacy", description = "旧API (非推奨。`/v1` 以下の対応するAPIを使用してください)"),
+    )
+)]
+pub struct ApiDoc;
+
+/// 管理者用の認証方式をドキュメントに追加する
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "admin_token",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new(ADMIN_TOKEN_HEADER))),
+        );
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)),
+        );
+    }
+}
+
+/// OpenAPIドキュメントとビューア (Swagger UI) のルーター
+pub fn docs_router() -> Router<AppState> {
+    SwaggerUi::new(DOCS_PATH)
+        .url(OPENAPI_PATH, ApiDoc::openapi())
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routes::{api_routes, Access};
+    use std::collections::BTreeSet;
+    use utoipa::openapi::path::Operation;
+    use utoipa::openapi::{Deprecated, PathItem};
+
+    /// axumのパス (`/games/:id`) をOpenAPIの形式 (`/games/{id}`) に変換する
+    fn openapi_path(path: &str) -> String {
+        path.split('/')
+            .map(|segment| match segment.strip_prefix(':') {
+                Some(param) => format!("{{{}}}", param),
+                None => segment.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// パス項目に含まれる (メソッド, 操作) の一覧
+    fn operations(item: &PathItem) -> Vec<(&'static str, &Operation)> {
+        [
+            ("GET", &item.get),
+            ("PUT", &item.put),
+            ("POST", &item.post),
+            ("DELETE", &item.delete),
+            ("OPTIONS", &item.options),
+            ("HEAD", &item.head),
+            ("PATCH", &item.patch),
+            ("TRACE", &item.trace),
+        ]
+        .into_iter()
+        .filter_map(|(method, operation)| operation.as_ref().map(|op| (method, op)))
+        .collect()
+    }
+
+    /// ルーターとOpenAPIドキュメントで、メソッド・パス・認証の要否・非推奨かどうかが一致すること
+    ///
+    /// ルートを追加・変更したらハンドラの `#[utoipa::path]` と `ApiDoc` も更新してください。
+    #[test]
+    fn test_document_matches_routes() {
+        let doc = ApiDoc::openapi();
+        let documented: BTreeSet<(String, String, bool, bool)> = doc
+            .paths
+            .paths
+            .iter()
+            .flat_map(|(path, item)| {
+                operations(item).into_iter().map(move |(method, op)| {
+                    let admin = op.security.as_ref().is_some_and(|s| !s.is_empty());
+                    let deprecated = matches!(op.deprecated, Some(Deprecated::True));
+                    (method.to_string(), path.clone(), admin, deprecated)
+                })
+            })
+            .collect();
+        let routed: BTreeSet<(String, String, bool, bool)> = api_routes()
+            .iter()
+            .map(|route| {
+                (
+                    route.method.to_string(),
+                    openapi_path(route.path),
+                    route.access == Access::Admin,
+                    route.successor.is_some(),
+                )
+            })
+            .collect();
+        assert_eq!(documented, routed);
+    }
+
+    /// ドキュメントから参照されている全てのスキーマが定義されていること
+    #[test]
+    fn test_schema_references_resolve() {
+        let doc = ApiDoc::openapi();
+        let json = serde_json::to_value(&doc).unwrap();
+        let schemas = json["components"]["schemas"].as_object().unwrap();
+
+        let mut refs = Vec::new();
+        collect_refs(&json, &mut refs);
+        assert!(!refs.is_empty());
+        for reference in refs {
+            let name = reference.trim_start_matches("#/components/schemas/");
+            assert!(
+                schemas.contains_key(name),
+                "undefined schema: {}",
+                reference
+            );
+        }
+        for scheme in ["admin_token", "bearer"] {
+            assert!(json["components"]["securitySchemes"].get(scheme).is_some());
+        }
+    }
+
+    fn collect_refs(value: &serde_json::Value, refs: &mut Vec<String>) {
+        match value {
+            serde_json::Value::Object(map) => {
+                if let Some(serde_json::Value::String(r)) = map.get("$ref") {
+                    refs.push(r.clone());
+                }
+                map.values().for_each(|v| collect_refs(v, refs));
+            }
+            serde_json::Value::Array(items) => items.iter().for_each(|v| collect_refs(v, refs)),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,383 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use utoipa::ToSchema;
+
+/// 参加者ID (名簿への登録順に1から振られ、削除しても再利用しない)
+pub type PlayerId = u32;
+
+/// アバターの絵文字の最大文字数 (肌の色や結合文字を含む絵文字のため複数文字を許す)
+pub const MAX_AVATAR_CHARS: usize = 8;
+
+/// 参加者の情報
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct Player {
+    /// 参加者ID
+    pub id: PlayerId,
+    /// 表示名 (名簿の中で重複しない)
+    pub name: String,
+    /// ふりがな
+    pub furigana: Option<String>,
+    /// 所属チーム
+    pub team: Option<String>,
+    /// アバターの絵文字
+    pub avatar: Option<String>,
+}
+
+/// 参加者の登録内容 (IDを除いた参加者の情報)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct PlayerProfile {
+    /// 表示名
+    pub name: String,
+    /// ふりがな
+    #[serde(default)]
+    pub furigana: Option<String>,
+    /// 所属チーム
+    #[serde(default)]
+    pub team: Option<String>,
+    /// アバターの絵文字
+    #[serde(default)]
+    pub avatar: Option<String>,
+}
+
+/// 参加者の情報の変更内容 (Noneの項目は変更しない、`Some(None)` で消去)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlayerChanges {
+    /// 表示名
+    pub name: Option<String>,
+    /// ふりがな
+    pub furigana: Option<Option<String>>,
+    /// 所属チーム
+    pub team: Option<Option<String>>,
+    /// アバターの絵文字
+    pub avatar: Option<Option<String>>,
+}
+
+/// 参加者名簿の操作に失敗した理由
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlayerError {
+    /// 指定されたIDの参加者がいない
+    NotFound(PlayerId),
+    /// 表示名が空
+    EmptyName,
+    /// 同じ表示名の参加者がすでにいる
+    DuplicateName(String),
+    /// アバターが絵文字ではない、または長すぎる
+    InvalidAvatar,
+}
+
+impl fmt::Display for PlayerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(id) => write!(f, "player {} does not exist", id),
+            Self::EmptyName => write!(f, "player name is empty"),
+            Self::DuplicateName(name) => write!(f, "player '{}' already exists", name),
+            Self::InvalidAvatar => write!(
+                f,
+                "avatar must be an emoji of at most {} characters",
+                MAX_AVATAR_CHARS
+            ),
+        }
+    }
+}
+
+/// ビンゴとあみだくじで共有する参加者名簿
+///
+/// ビンゴの申告やラウンドの当選者、あみだくじの枠と割り当て制約は参加者IDで参加者を指します。
+/// 表示名は、読み上げや画面で参加者を見分けられるように、また参加者リストを取り込み直したときに
+/// 同じ参加者として対応付けられるように、名簿の中で重複させません。
+#[derive(Debug, Clone)]
+pub struct PlayerRegistry {
+    /// 登録されている参加者 (登録順)
+    players: Vec<Player>,
+    /// 次に登録する参加者のID
+    next_id: PlayerId,
+}
+
+impl Default for PlayerRegistry {
+    fn default() -> Self {
+        Self {
+            players: Vec::new(),
+            next_id: 1,
+        }
+    }
+}
+
+impl PlayerRegistry {
+    /// 空の名簿を作成する
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登録されている参加者 (登録順)
+    pub fn players(&self) -> &[Player] {
+        &self.players
+    }
+
+    /// 参加者をIDで探す
+    pub fn get(&self, id: PlayerId) -> Result<&Player, PlayerError> {
+        self.players
+            .iter()
+            .find(|player| player.id == id)
+            .ok_or(PlayerError::NotFound(id))
+    }
+
+    /// 参加者を表示名で探す
+    pub fn find_by_name(&self, name: &str) -> Option<&Player> {
+        self.players.iter().find(|player| player.name == name)
+    }
+
+    /// 参加者の表示名 (名簿にない参加者はIDを文字列にしたもの)
+    pub fn display_name(&self, id: PlayerId) -> String {
+        self.get(id)
+            .map(|player| player.name.clone())
+            .unwrap_or_else(|_| id.to_string())
+    }
+
+    /// 参加者を登録する
+    ///
+    /// # Arguments
+    /// * `profile` - 登録内容 (前後の空白は取り除き、空の項目はNoneにする)
+    ///
+    /// # Returns
+    /// * `Result<&Player, PlayerError>` - 登録した参加者
+    pub fn add(&mut self, profile: PlayerProfile) -> Result<&Player, PlayerError> {
+        let name = self.check_name(&profile.name, None)?;
+        let player = Player {
+            id: self.next_id,
+            name,
+            furigana: optional(profile.furigana),
+            team: optional(profile.team),
+            avatar: avatar(profile.avatar)?,
+        };
+        self.next_id += 1;
+        self.players.push(player);
+        Ok(&self.players[self.players.len() - 1])
+    }
+
+    /// 表示名の参加者のIDを返す (名簿にない場合は登録する)
+    pub fn ensure(&mut self, name: &str) -> Result<PlayerId, PlayerError> {
+        if let Some(player) = self.find_by_name(name.trim()) {
+            return Ok(player.id);
+        }
+        self.add(PlayerProfile {
+            name: name.to_string(),
+            ..PlayerProfile::default()
+        })
+        .map(|player| player.id)
+    }
+
+    /// 参加者の情報を変更する
+    ///
+    /// # Returns
+    /// * `Result<&Player, PlayerError>` - 変更後の参加者
+    pub fn update(&mut self, id: PlayerId, changes: PlayerChanges) -> Result<&Player, PlayerError> {
+        self.get(id)?;
+        let name = match changes.name {
+            Some(name) => Some(self.check_name(&name, Some(id))?),
+            None => None,
+        };
+        let avatar = match changes.avatar {
+            Some(value) => Some(avatar(value)?),
+            None => None,
+        };
+
+        let player = self
+            .players
+            .iter_mut()
+            .find(|player| player.id == id)
+            .ok_or(PlayerError::NotFound(id))?;
+        if let Some(name) = name {
+            player.name = name;
+        }
+        if let Some(furigana) = changes.furigana {
+            player.furigana = optional(furigana);
+        }
+        if let Some(team) = changes.team {
+            player.team = optional(team);
+        }
+        if let Some(avatar) = avatar {
+            player.avatar = avatar;
+        }
+        Ok(player)
+    }
+
+    /// 参加者を名簿から削除する
+    ///
+    /// # Returns
+    /// * `Result<Player, PlayerError>` - 削除した参加者
+    pub fn remove(&mut self, id: PlayerId) -> Result<Player, PlayerError> {
+        let index = self
+            .players
+            .iter()
+            .position(|player| player.id == id)
+            .ok_or(PlayerError::NotFound(id))?;
+        Ok(self.players.remove(index))
+    }
+
+    /// 名簿の内容をまとめて置き換える
+    ///
+    /// 同じ表示名の参加者はIDとアバターを引き継ぐため、
+    /// 取り込み直しても申告や当選者の記録は同じ参加者を指したままになります。
+    /// 空の表示名・重複した表示名の参加者は登録しません。
+    ///
+    /// # Arguments
+    /// * `profiles` - 新しい参加者リスト
+    pub fn replace_all(&mut self, profiles: Vec<PlayerProfile>) {
+        let previous = std::mem::take(&mut self.players);
+        for profile in profiles {
+            let name = profile.name.trim();
+            if name.is_empty() || self.find_by_name(name).is_some() {
+                continue;
+            }
+            let kept = previous.iter().find(|player| player.name == name);
+            let id = match kept {
+                Some(player) => player.id,
+                None => {
+                    let id = self.next_id;
+                    self.next_id += 1;
+                    id
+                }
+            };
+            self.players.push(Player {
+                id,
+                name: name.to_string(),
+                furigana: optional(profile.furigana),
+                team: optional(profile.team),
+                avatar: avatar(profile.avatar)
+                    .ok()
+                    .flatten()
+                    .or_else(|| kept.and_then(|player| player.avatar.clone())),
+            });
+        }
+    }
+
+    /// 登録されている参加者の表示名を登録順に返す
+    pub fn names(&self) -> Vec<String> {
+        self.players.iter().map(|p| p.name.clone()).collect()
+    }
+
+    /// 表示名を検証する (前後の空白を取り除き、空・重複をエラーにする)
+    fn check_name(&self, name: &str, id: Option<PlayerId>) -> Result<String, PlayerError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(PlayerError::EmptyName);
+        }
+        match self.find_by_name(name) {
+            Some(other) if Some(other.id) != id => {
+                Err(PlayerError::DuplicateName(name.to_string()))
+            }
+            _ => Ok(name.to_string()),
+        }
+    }
+}
+
+/// 前後の空白を取り除き、空の文字列をNoneにする
+fn optional(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// アバターを検証する (ASCII文字を含まない、`MAX_AVATAR_CHARS` 文字以内の文字列)
+fn avatar(value: Option<String>) -> Result<Option<String>, PlayerError> {
+    match optional(value) {
+        Some(avatar)
+            if avatar.chars().any(|c| c.is_ascii())
+                || avatar.chars().count() > MAX_AVATAR_CHARS =>
+        {
+            Err(PlayerError::InvalidAvatar)
+        }
+        avatar => Ok(avatar),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(name: &str) -> PlayerProfile {
+        PlayerProfile {
+            name: name.to_string(),
+            ..PlayerProfile::default()
+        }
+    }
+
+    #[test]
+    fn test_add_and_update_players() {
+        let mut registry = PlayerRegistry::new();
+        let alice = registry
+            .add(PlayerProfile {
+                name: " Alice ".to_string(),
+                furigana: Some("ありす".to_string()),
+                team: Some("".to_string()),
+                avatar: Some("🦌".to_string()),
+            })
+            .unwrap()
+            .clone();
+        assert_eq!(alice.id, 1);
+        assert_eq!(alice.name, "Alice");
+        assert_eq!(alice.team, None);
+        assert_eq!(registry.add(profile("Bob")).unwrap().id, 2);
+
+        assert_eq!(
+            registry.add(profile("Alice")),
+            Err(PlayerError::DuplicateName("Alice".to_string()))
+        );
+        assert_eq!(registry.add(profile("  ")), Err(PlayerError::EmptyName));
+        assert_eq!(
+            registry.add(PlayerProfile {
+                avatar: Some("A".to_string()),
+                ..profile("Carol")
+            }),
+            Err(PlayerError::InvalidAvatar)
+        );
+
+        let changes = PlayerChanges {
+            name: Some("Bob".to_string()),
+            ..PlayerChanges::default()
+        };
+        assert!(registry.update(1, changes).is_err());
+        let changes = PlayerChanges {
+            name: Some("Alicia".to_string()),
+            avatar: Some(None),
+            ..PlayerChanges::default()
+        };
+        let alicia = registry.update(1, changes).unwrap();
+        assert_eq!(alicia.name, "Alicia");
+        assert_eq!(alicia.furigana.as_deref(), Some("ありす"));
+        assert_eq!(alicia.avatar, None);
+
+        assert_eq!(registry.remove(2).unwrap().name, "Bob");
+        assert_eq!(registry.get(2), Err(PlayerError::NotFound(2)));
+        // 削除したIDは再利用しない
+        assert_eq!(registry.ensure("Bob").unwrap(), 3);
+        assert_eq!(registry.ensure("Bob").unwrap(), 3);
+    }
+
+    #[test]
+    fn test_replace_all_keeps_ids() {
+        let mut registry = PlayerRegistry::new();
+        registry.add(profile("Alice")).unwrap();
+        registry
+            .update(
+                1,
+                PlayerChanges {
+                    avatar: Some(Some("🎅".to_string())),
+                    ..PlayerChanges::default()
+                },
+            )
+            .unwrap();
+        registry.add(profile("Bob")).unwrap();
+
+        registry.replace_all(vec![
+            profile("Carol"),
+            profile("Alice"),
+            profile(""),
+            profile("Carol"),
+        ]);
+        let ids: Vec<_> = registry.players().iter().map(|p| p.id).collect();
+        assert_eq!(ids, vec![3, 1]);
+        assert_eq!(registry.names(), vec!["Carol", "Alice"]);
+        assert_eq!(registry.get(1).unwrap().avatar.as_deref(), Some("🎅"));
+    }
+}
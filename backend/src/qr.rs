@@ -0,0 +1,88 @@
+use png::{ColorType, Decoder, Limits, Transformations};
+use std::fmt;
+
+/// 読み取る画像の最大の画素数 (展開後のメモリを抑えるため)
+pub const MAX_IMAGE_PIXELS: usize = 4096 * 4096;
+
+/// QRコードの画像を読み取れなかった理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QrError {
+    /// PNG画像として読めない (形式が違う・大きすぎる)
+    InvalidImage,
+    /// 画像に読み取れるQRコードがない
+    NotFound,
+}
+
+impl fmt::Display for QrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidImage => write!(
+                f,
+                "the image is not a PNG image of at most {} pixels",
+                MAX_IMAGE_PIXELS
+            ),
+            Self::NotFound => write!(f, "no readable QR code was found in the image"),
+        }
+    }
+}
+
+/// PNG画像に写っているQRコードを読み取る
+///
+/// 紙のカードを撮影した画像やスキャンした画像をグレースケールに変換し、
+/// `rqrr` でQRコードを探して最初に読み取れた内容を返します。
+/// 透明な背景は白として扱います。
+///
+/// # Returns
+/// * `Result<String, QrError>` - QRコードの内容
+pub fn decode_png(data: &[u8]) -> Result<String, QrError> {
+    let (width, height, pixels) = read_png(data)?;
+    let mut image =
+        rqrr::PreparedImage::prepare_from_greyscale(width, height, |x, y| pixels[y * width + x]);
+    image
+        .detect_grids()
+        .into_iter()
+        .find_map(|grid| grid.decode().ok())
+        .map(|(_, content)| content)
+        .ok_or(QrError::NotFound)
+}
+
+/// PNG画像をグレースケールに変換して読み込む
+///
+/// # Returns
+/// * `Result<(usize, usize, Vec<u8>), QrError>` - (幅, 高さ, 画素の明るさ)
+fn read_png(data: &[u8]) -> Result<(usize, usize, Vec<u8>), QrError> {
+    let mut decoder = Decoder::new(data);
+    decoder.set_transformations(Transformations::normalize_to_color8());
+    decoder.set_limits(Limits {
+        bytes: MAX_IMAGE_PIXELS * 4,
+    });
+    let mut reader = decoder.read_info().map_err(|_| QrError::InvalidImage)?;
+    let (width, height) = (reader.info().width as usize, reader.info().height as usize);
+    if width.saturating_mul(height) > MAX_IMAGE_PIXELS {
+        return Err(QrError::InvalidImage);
+    }
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let frame = reader
+        .next_frame(&mut buffer)
+        .map_err(|_| QrError::InvalidImage)?;
+    let channels = frame.color_type.samples();
+    let pixels = buffer[..frame.buffer_size()]
+        .chunks_exact(channels)
+        .map(|pixel| luminance(frame.color_type, pixel))
+        .collect();
+    Ok((width, height, pixels))
+}
+
+/// 画素の明るさ (透明な部分は白い背景に重ねる)
+fn luminance(color: ColorType, pixel: &[u8]) -> u8 {
+    let (value, alpha) = match color {
+        ColorType::GrayscaleAlpha => (u32::from(pixel[0]), u32::from(pixel[1])),
+        ColorType::Rgb | ColorType::Rgba => {
+            let [r, g, b] = [pixel[0], pixel[1], pixel[2]].map(u32::from);
+            let alpha = pixel.get(3).copied().map_or(255, u32::from);
+            ((r * 299 + g * 587 + b * 114) / 1000, alpha)
+        }
+        _ => (u32::from(pixel[0]), 255),
+    };
+    ((value * alpha + 255 * (255 - alpha)) / 255) as u8
+}
@@ -1,10 +1,24 @@
 const DEFAULT_SEED: u32 = 1_234_567_890;
 
+/// シード値とストリーム番号から、サブストリーム用のシード値を導出します
+///
+/// 同じシード値・ストリーム番号からは常に同じ値が得られます。
+/// (SplitMix32 の混合関数を使用)
+pub fn derive_seed(seed: u32, stream: u32) -> u32 {
+    let mut z = seed.wrapping_add(stream.wrapping_mul(0x9e37_79b9));
+    z = (z ^ (z >> 16)).wrapping_mul(0x85eb_ca6b);
+    z = (z ^ (z >> 13)).wrapping_mul(0xc2b2_ae35);
+    z ^ (z >> 16)
+}
+
 /// 乱数生成器のインターフェース
 ///
 /// 乱数の生成、シャッフル、状態のリセットなどの機能を提供します。
 /// スレッドセーフ（Send + Sync）である必要があります。
 pub trait IRng: Send + Sync {
+    /// アルゴリズム名を返します (結果の記録用)
+    fn name(&self) -> &'static str;
+
     /// 次の32ビット乱数を生成します
     fn next(&mut self) -> u32;
     /// バイト列をシャッフルします
@@ -24,6 +38,12 @@ pub trait IRng: Send + Sync {
     
     /// 乱数生成器の状態を初期化します
     fn reset(&mut self);
+
+    /// 同じアルゴリズムで、独立したサブストリームの乱数生成器を作成します
+    ///
+    /// 初期シード値とストリーム番号から導出したシード値で初期化されるため、
+    /// 元の乱数生成器の状態には影響せず、同じ番号からは常に同じ乱数列が得られます。
+    fn fork(&self, stream: u32) -> Box<dyn IRng>;
 }
 
 /// XorShiftアルゴリズムによる擬似乱数生成器
@@ -38,19 +58,30 @@ pub struct XorShift {
 
 impl XorShift {
     pub fn new(seed: u32) -> Self {
+        let rng = Self::quiet(seed);
+        println!("current XorShift seed:{}", &rng.initial_state);
+        rng
+    }
+
+    /// シード値を出力せずに作成します
+    ///
+    /// カードの数字やシミュレーションのように、大量の乱数生成器を作る場合に使います。
+    pub fn quiet(seed: u32) -> Self {
         // seedが0だとXorShiftは動かないので、0の場合は適当な値にする
         let mut rng = Self {
             initial_state: if seed != 0 { seed } else { DEFAULT_SEED },
             state: 0,
         };
-        println!("current XorShift seed:{}", &rng.initial_state);
-
         rng.reset();
         rng
     }
 }
 
 impl IRng for XorShift {
+    fn name(&self) -> &'static str {
+        "xorshift32"
+    }
+
     fn next(&mut self) -> u32 {
         let mut x = self.state;
         x ^= x << 13;
@@ -61,7 +92,11 @@ impl IRng for XorShift {
     }
 
     fn reset(&mut self) {
-        self.state = self.initial_state.clone();
+        self.state = self.initial_state;
+    }
+
+    fn fork(&self, stream: u32) -> Box<dyn IRng> {
+        Box::new(XorShift::quiet(derive_seed(self.initial_state, stream)))
     }
 }
 
@@ -84,14 +119,21 @@ impl MersenneTwister {
     const LOWER_MASK: u32 = 0x7fffffff;
 
     pub fn new(seed: u32) -> Self {
+        let rng = Self::quiet(seed);
+        println!("current MersenneTwister seed:{}", &rng.initial_seed);
+        rng
+    }
+
+    /// シード値を出力せずに作成します
+    ///
+    /// 欠席者の再抽選のように、サブストリームの乱数生成器を作る場合に使います。
+    pub fn quiet(seed: u32) -> Self {
         let seed = if seed != 0 { seed } else { DEFAULT_SEED };
         let mut rng = Self {
             initial_seed: seed,
             mt: [0; 624],
             index: 624,
         };
-        println!("current MersenneTwister seed:{}", &seed);
-
         rng.initialize(seed);
         rng
     }
@@ -120,6 +162,9 @@ impl MersenneTwister {
 }
 
 impl IRng for MersenneTwister {
+    fn name(&self) -> &'static str {
+        "mt19937"
+    }
 
     fn next(&mut self) -> u32 {
         if self.index >= Self::N {
@@ -141,6 +186,10 @@ impl IRng for MersenneTwister {
     fn reset(&mut self) {
         self.initialize(self.initial_seed);
     }
+
+    fn fork(&self, stream: u32) -> Box<dyn IRng> {
+        Box::new(MersenneTwister::quiet(derive_seed(self.initial_seed, stream)))
+    }
 }
 
 #[cfg(test)]
@@ -159,4 +208,26 @@ mod tests {
         assert_eq!(rng.next(), 3586334585);
         assert_eq!(rng.next(), 545404204);
     }
+
+    #[test]
+    fn test_fork_is_reproducible_and_independent() {
+        let mut rng = XorShift::new(42);
+        let first = rng.next();
+
+        let mut a = rng.fork(1);
+        let mut b = rng.fork(1);
+        let mut c = rng.fork(2);
+        let (a1, b1, c1) = (a.next(), b.next(), c.next());
+        assert_eq!(a1, b1);
+        assert_ne!(a1, c1);
+
+        // 元の乱数生成器の状態には影響しない
+        rng.reset();
+        assert_eq!(rng.next(), first);
+
+        // シード値を出力しない乱数生成器も同じ乱数列になる
+        let mut mt = MersenneTwister::new(5489).fork(3);
+        let mut quiet = MersenneTwister::quiet(derive_seed(5489, 3));
+        assert_eq!(mt.next(), quiet.next());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,1447 @@
+use crate::auth;
+use crate::handlers::{
+    api_not_found, create_card, create_card_claim, create_claim, create_draw, create_player,
+    create_round, create_tie_break, create_transition, create_winner, delete_draws, delete_player,
+    export_results, get_amida, get_amida_result, get_auto_draw, get_card_sheet, get_game,
+    get_game_status, get_player, get_player_card, get_round, get_session, get_statistics,
+    get_tie_breaks, get_winners, import_amida, list_cards, list_players, mark_amida_absent,
+    patch_amida, replace_amida, replace_prizes, reveal_amida, start_auto_draw, stop_auto_draw,
+    update_auto_draw, update_player, update_round, verify_card,
+};
+use crate::legacy;
+use crate::state::AppState;
+use axum::{
+    handler::Handler,
+    http::{header, HeaderName, HeaderValue, Method},
+    middleware,
+    response::Response,
+    routing::{on, MethodFilter, MethodRouter},
+    Router,
+};
+
+/// 非推奨のAPIであることを示すレスポンスヘッダー
+const DEPRECATION: HeaderName = HeaderName::from_static("deprecation");
+
+/// APIを呼び出せる利用者
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// 誰でも呼び出せる (参照のみ)
+    Public,
+    /// 管理者トークンまたは司会者用PINが必要 (状態を変更する操作)
+    Admin,
+    /// ビンゴカードの参加コードを知っている参加者 (パスの参加コードで確認する)
+    Player,
+}
+
+/// APIのルート定義
+///
+/// ルーターとOpenAPIドキュメントが食い違っていないかをテストで確認できるように、
+/// メソッド・パス・アクセス権を一覧として持ちます。
+pub struct ApiRoute {
+    /// HTTPメソッド (ハンドラの登録時とドキュメントとの照合に使う)
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub method: Method,
+    /// `/api` からの相対パス (パスパラメータは `:id` の形式)
+    pub path: &'static str,
+    /// 呼び出せる利用者
+    pub access: Access,
+    /// 非推奨の場合、移行先のパス (`/api` からの相対パス)
+    pub successor: Option<&'static str>,
+    /// ハンドラ
+    handler: MethodRouter<AppState>,
+}
+
+impl ApiRoute {
+    fn new<H, T>(method: Method, path: &'static str, access: Access, handler: H) -> Self
+    where
+        H: Handler<T, AppState>,
+        T: 'static,
+    {
+        let filter = MethodFilter::try_from(method.clone()).expect("unsupported HTTP method");
+        Self {
+            method,
+            path,
+            access,
+            successor: None,
+            handler: on(filter, handler),
+        }
+    }
+
+    /// 非推奨のルートとし、移行先を指定する
+    fn deprecated(mut self, successor: &'static str) -> Self {
+        self.successor = Some(successor);
+        self
+    }
+}
+
+/// `/api` 以下の全てのルート
+///
+/// `/v1` 以下がリソース指向のAPIです。
+/// * `GET /v1/games/:id` - ビンゴのゲームの状態を取得する
+/// * `GET /v1/games/:id/status` - 表示用の状態を取得する (`ETag` による条件付きリクエストに対応)
+/// * `POST /v1/games/:id/draws` - ビンゴの次の数字を引く
+/// * `DELETE /v1/games/:id/draws` - 抽選履歴を消去し、ゲームをリセットする
+/// * `POST /v1/games/:id/transitions` - ゲームのフェーズを遷移する (`lock` / `start` / `pause` など)
+/// * `POST /v1/games/:id/claims` - ビンゴの申告を記録する (自動抽選は停止する)
+/// * `GET /v1/games/:id/auto-draw` - 自動抽選の状況を取得する
+/// * `POST` / `PATCH` / `DELETE /v1/games/:id/auto-draw` - 自動抽選を開始する・変更する・停止する
+/// * `GET /v1/session` - ビンゴのセッション (全てのラウンド) の概要を取得する
+/// * `POST /v1/session/rounds` - 進行中のラウンドを終え、次のラウンドを開始する
+/// * `GET /v1/session/rounds/:number` / `PATCH /v1/session/rounds/:number` - ラウンドの記録を取得する・設定を変更する
+/// * `GET /v1/players` / `POST /v1/players` - 参加者名簿を取得する・参加者を登録する
+/// * `GET` / `PATCH` / `DELETE /v1/players/:player_id` - 参加者を取得する・変更する・削除する
+/// * `GET /v1/cards` / `POST /v1/cards` - 発行したビンゴカードを取得する・参加者にカードを発行する
+/// * `GET /v1/cards/sheet` - 発行したビンゴカードを印刷用のシート (PDF/SVG) として取得する
+/// * `POST /v1/cards/verifications` - 印刷したカードのQRコードを確認し、抽選履歴を重ねて取得する
+/// * `GET /v1/join/:code` - 参加コードのビンゴカードを、抽選済みの数字に印を付けて取得する
+/// * `POST /v1/join/:code/claims` - 参加コードのビンゴカードで申告する
+/// * `GET /v1/winners` / `POST /v1/winners` - ビンゴの景品と当選者を取得する・司会者が確認した申告を当選者として記録する
+/// * `PUT /v1/winners/prizes` - まだ渡していないビンゴの景品を置き換える
+/// * `GET /v1/winners/tie-breaks` / `POST /v1/winners/tie-breaks` - くじ引きの記録を取得する・同じ数字で揃った当選者の順位をくじ引きで決める
+/// * `GET /v1/amida` - あみだくじの設定を取得する
+/// * `PUT /v1/amida` / `PATCH /v1/amida` - あみだくじの設定を置き換える・部分的に更新する
+/// * `GET /v1/amida/result` - あみだくじの公開済みの結果を取得する
+/// * `POST /v1/amida/reveals` - あみだくじの結果を1件公開する
+/// * `POST /v1/amida/absences` - 欠席者を記録し、その景品を再抽選する
+/// * `POST /v1/amida/imports` - CSV/TSVファイルから参加者リストを取り込む
+/// * `GET /v1/statistics` - 終了したラウンドの抽選の統計を取得する
+/// * `GET /v1/export` - ビンゴとあみだくじの結果一式をダウンロードする (CSV / JSON / HTML)
+///
+/// バージョンなしのルート (`/next_number` など) は旧クライアントのための非推奨の別名です。
+#[allow(deprecated)]
+pub fn api_routes() -> Vec<ApiRoute> {
+    use Access::{Admin, Player, Public};
+    vec![
+        ApiRoute::new(Method::GET, "/v1/games/:id", Public, get_game),
+        ApiRoute::new(Method::GET, "/v1/games/:id/status", Public, get_game_status),
+        ApiRoute::new(Method::POST, "/v1/games/:id/draws", Admin, create_draw),
+        ApiRoute::new(Method::DELETE, "/v1/games/:id/draws", Admin, delete_draws),
+        ApiRoute::new(
+            Method::POST,
+            "/v1/games/:id/transitions",
+            Admin,
+            create_transition,
+        ),
+        ApiRoute::new(Method::POST, "/v1/games/:id/claims", Admin, create_claim),
+        ApiRoute::new(
+            Method::GET,
+            "/v1/games/:id/auto-draw",
+            Public,
+            get_auto_draw,
+        ),
+        ApiRoute::new(
+            Method::POST,
+            "/v1/games/:id/auto-draw",
+            Admin,
+            start_auto_draw,
+        ),
+        ApiRoute::new(
+            Method::PATCH,
+            "/v1/games/:id/auto-draw",
+            Admin,
+            update_auto_draw,
+        ),
+        ApiRoute::new(
+            Method::DELETE,
+            "/v1/games/:id/auto-draw",
+            Admin,
+            stop_auto_draw,
+        ),
+        ApiRoute::new(Method::GET, "/v1/session", Public, get_session),
+        ApiRoute::new(Method::POST, "/v1/session/rounds", Admin, create_round),
+        ApiRoute::new(Method::GET, "/v1/session/rounds/:number", Public, get_round),
+        ApiRoute::new(
+            Method::PATCH,
+            "/v1/session/rounds/:number",
+            Admin,
+            update_round,
+        ),
+        ApiRoute::new(Method::GET, "/v1/players", Public, list_players),
+        ApiRoute::new(Method::POST, "/v1/players", Admin, create_player),
+        ApiRoute::new(Method::GET, "/v1/players/:player_id", Public, get_player),
+        ApiRoute::new(
+            Method::PATCH,
+            "/v1/players/:player_id",
+            Admin,
+            update_player,
+        ),
+        ApiRoute::new(
+            Method::DELETE,
+            "/v1/players/:player_id",
+            Admin,
+            delete_player,
+        ),
+        ApiRoute::new(Method::GET, "/v1/cards", Admin, list_cards),
+        ApiRoute::new(Method::POST, "/v1/cards", Admin, create_card),
+        ApiRoute::new(Method::GET, "/v1/cards/sheet", Admin, get_card_sheet),
+        ApiRoute::new(Method::POST, "/v1/cards/verifications", Admin, verify_card),
+        ApiRoute::new(Method::GET, "/v1/join/:code", Player, get_player_card),
+        ApiRoute::new(
+            Method::POST,
+            "/v1/join/:code/claims",
+            Player,
+            create_card_claim,
+        ),
+        ApiRoute::new(Method::GET, "/v1/winners", Public, get_winners),
+        ApiRoute::new(Method::POST, "/v1/winners", Admin, create_winner),
+        ApiRoute::new(Method::PUT, "/v1/winners/prizes", Admin, replace_prizes),
+        ApiRoute::new(
+            Method::GET,
+            "/v1/winners/tie-breaks",
+            Public,
+            get_tie_breaks,
+        ),
+        ApiRoute::new(
+            Method::POST,
+            "/v1/winners/tie-breaks",
+            Admin,
+            create_tie_break,
+        ),
+        ApiRoute::new(Method::GET, "/v1/amida", Public, get_amida),
+        ApiRoute::new(Method::PUT, "/v1/amida", Admin, replace_amida),
+        ApiRoute::new(Method::PATCH, "/v1/amida", Admin, patch_amida),
+        ApiRoute::new(Method::GET, "/v1/amida/result", Public, get_amida_result),
+        ApiRoute::new(Method::POST, "/v1/amida/reveals", Admin, reveal_amida),
+        ApiRoute::new(Method::POST, "/v1/amida/absences", Admin, mark_amida_absent),
+        ApiRoute::new(Method::POST, "/v1/amida/imports", Admin, import_amida),
+        ApiRoute::new(Method::GET, "/v1/statistics", Public, get_statistics),
+        ApiRoute::new(Method::GET, "/v1/export", Public, export_results),
+        // 旧API
+        ApiRoute::new(Method::GET, "/next_number", Admin, legacy::get_next_number)
+            .deprecated("/v1/games/default/draws"),
+        ApiRoute::new(Method::POST, "/reset", Admin, legacy::reset_game)
+            .deprecated("/v1/games/default/draws"),
+        ApiRoute::new(Method::GET, "/amida", Public, legacy::get_amida).deprecated("/v1/amida"),
+        ApiRoute::new(Method::POST, "/amida", Admin, legacy::set_amida).deprecated("/v1/amida"),
+        ApiRoute::new(
+            Method::GET,
+            "/amida/result",
+            Public,
+            legacy::get_amida_result,
+        )
+        .deprecated("/v1/amida/result"),
+        ApiRoute::new(Method::POST, "/amida/reveal", Admin, legacy::reveal_amida)
+            .deprecated("/v1/amida/reveals"),
+        ApiRoute::new(
+            Method::POST,
+            "/amida/absent",
+            Admin,
+            legacy::mark_amida_absent,
+        )
+        .deprecated("/v1/amida/absences"),
+        ApiRoute::new(Method::POST, "/amida/import", Admin, legacy::import_amida)
+            .deprecated("/v1/amida/imports"),
+        ApiRoute::new(Method::GET, "/export", Public, legacy::export_results)
+            .deprecated("/v1/export"),
+    ]
+}
+
+/// `/api` 以下のルーターを作成する
+///
+/// 管理者用のルートには認証のミドルウェアを、非推奨のルートには移行先を示すヘッダーを付け、
+/// 存在しないパスにはJSONの404を返します。
+pub fn api_router(state: &AppState) -> Router<AppState> {
+    api_routes()
+        .into_iter()
+        .fold(Router::new(), |router, route| {
+            let mut handler = route.handler;
+            if route.access == Access::Admin {
+                handler = handler.route_layer(middleware::from_fn_with_state(
+                    state.clone(),
+                    auth::require_admin,
+                ));
+            }
+            if let Some(successor) = route.successor {
+                handler = handler.layer(middleware::map_response(move |response| {
+                    deprecate(response, successor)
+                }));
+            }
+            router.route(route.path, handler)
+        })
+        .fallback(api_not_found)
+}
+
+/// 非推奨のルートのレスポンスに `Deprecation` ヘッダーと移行先の `Link` ヘッダーを付ける
+async fn deprecate(mut response: Response, successor: &'static str) -> Response {
+    let headers = response.headers_mut();
+    headers.insert(DEPRECATION, HeaderValue::from_static("true"));
+    if let Ok(link) =
+        HeaderValue::from_str(&format!("</api{}>; rel=\"successor-version\"", successor))
+    {
+        headers.insert(header::LINK, link);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::{BingoCard, CardKey};
+    use crate::config::{Args, Config, ConfigFile};
+    use crate::handlers::DEFAULT_GAME_ID;
+    use crate::player::PlayerProfile;
+    use crate::stats;
+    use crate::tiebreak::{TieBreak, TieBreakMethod};
+    use axum::{
+        body::Body,
+        extract::ConnectInfo,
+        http::{Request, StatusCode},
+    };
+    use std::net::SocketAddr;
+    use tower::ServiceExt;
+
+    fn test_state() -> AppState {
+        test_state_with(2)
+    }
+
+    /// 数字の最大値を指定して状態を作成する (ビンゴカードは75ボールのみ)
+    fn test_state_with(max_number: usize) -> AppState {
+        let args = Args {
+            bingo_max_number: Some(max_number),
+            prize_count: Some(8),
+            admin_token: Some("test-token".to_string()),
+            card_secret: Some("test-card-secret".to_string()),
+            no_frontend: true,
+            ..Args::default()
+        };
+        let config = Config::resolve(args, ConfigFile::default(), None).unwrap();
+        AppState::new(1, &config)
+    }
+
+    /// 参加者を名簿に登録する
+    fn register(state: &AppState, name: &str) -> u32 {
+        state
+            .players()
+            .add(PlayerProfile {
+                name: name.to_string(),
+                ..PlayerProfile::default()
+            })
+            .unwrap()
+            .id
+    }
+
+    /// 参加者にビンゴカードを発行し、参加コードを返す
+    fn issue_card(state: &AppState, player: u32) -> String {
+        let seed = state.session().seed();
+        let (phase, variant) = {
+            let game = state.game();
+            (game.phase(), game.variant())
+        };
+        let mut cards = state.cards();
+        let (card, _) = cards.issue(player, seed, phase, variant).unwrap();
+        card.join_code.clone()
+    }
+
+    /// 一覧の全てのルートが、宣言したメソッドとアクセス権で実際に呼び出せること
+    #[tokio::test]
+    async fn test_routes_are_reachable() {
+        let state = test_state_with(75);
+        let code = issue_card(&state, register(&state, "Alice"));
+        for route in api_routes() {
+            let router = api_router(&state).with_state(state.clone());
+            let uri = route
+                .path
+                .replace(":id", DEFAULT_GAME_ID)
+                .replace(":number", "1")
+                .replace(":player_id", "1")
+                .replace(":code", &code);
+            let request = || Request::builder().method(route.method.clone()).uri(&uri);
+
+            let anonymous = router
+                .clone()
+                .oneshot(request().body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            match route.access {
+                Access::Admin => assert_eq!(
+                    anonymous.status(),
+                    StatusCode::UNAUTHORIZED,
+                    "{} {}",
+                    route.method,
+                    route.path
+                ),
+                Access::Public => assert!(
+                    anonymous.status().is_success(),
+                    "{} {}",
+                    route.method,
+                    route.path
+                ),
+                Access::Player => assert!(
+                    ![
+                        StatusCode::UNAUTHORIZED,
+                        StatusCode::NOT_FOUND,
+                        StatusCode::METHOD_NOT_ALLOWED
+                    ]
+                    .contains(&anonymous.status()),
+                    "{} {} -> {}",
+                    route.method,
+                    route.path,
+                    anonymous.status()
+                ),
+            }
+
+            let authorized = router
+                .oneshot(
+                    request()
+                        .header(auth::ADMIN_TOKEN_HEADER, "test-token")
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert!(
+                ![StatusCode::NOT_FOUND, StatusCode::METHOD_NOT_ALLOWED]
+                    .contains(&authorized.status()),
+                "{} {} -> {}",
+                route.method,
+                route.path,
+                authorized.status()
+            );
+
+            for response in [&anonymous, &authorized] {
+                assert_eq!(
+                    response.headers().contains_key(DEPRECATION),
+                    route.successor.is_some(),
+                    "{} {}",
+                    route.method,
+                    route.path
+                );
+            }
+        }
+    }
+
+    async fn send(state: &AppState, method: Method, uri: &str) -> (StatusCode, serde_json::Value) {
+        send_json(state, method, uri, None).await
+    }
+
+    async fn send_json(
+        state: &AppState,
+        method: Method,
+        uri: &str,
+        body: Option<serde_json::Value>,
+    ) -> (StatusCode, serde_json::Value) {
+        let body = body.map(|body| ("application/json", body.to_string()));
+        send_body(state, method, uri, body).await
+    }
+
+    /// Content-Type を指定したボディを送る
+    async fn send_body(
+        state: &AppState,
+        method: Method,
+        uri: &str,
+        body: Option<(&str, String)>,
+    ) -> (StatusCode, serde_json::Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(auth::ADMIN_TOKEN_HEADER, "test-token");
+        let request = match body {
+            Some((content_type, body)) => request
+                .header(header::CONTENT_TYPE, content_type)
+                .body(Body::from(body)),
+            None => request.body(Body::empty()),
+        };
+        let response = api_router(state)
+            .with_state(state.clone())
+            .oneshot(request.unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    /// 管理者トークンを続けて間違えたクライアントだけが、しばらく受け付けられないこと
+    #[tokio::test]
+    async fn test_admin_lockout_per_client() {
+        let state = test_state();
+        let attempt = |client: [u8; 4], token: &str| {
+            let mut request = Request::post("/v1/games/default/draws")
+                .header(auth::ADMIN_TOKEN_HEADER, token)
+                .body(Body::empty())
+                .unwrap();
+            request
+                .extensions_mut()
+                .insert(ConnectInfo(SocketAddr::from((client, 50000))));
+            api_router(&state)
+                .with_state(state.clone())
+                .oneshot(request)
+        };
+
+        for _ in 0..6 {
+            let response = attempt([10, 0, 0, 9], "wrong").await.unwrap();
+            assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        }
+        let response = attempt([10, 0, 0, 9], "test-token").await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "30");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["code"], "too_many_attempts");
+
+        let response = attempt([10, 0, 0, 2], "test-token").await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+    }
+
+    /// v1のビンゴAPIが作成・取得・削除のHTTPの意味に従うこと
+    #[tokio::test]
+    async fn test_v1_game_resource() {
+        let state = test_state();
+        let draws = "/v1/games/default/draws";
+
+        assert_eq!(
+            send(&state, Method::POST, draws).await.0,
+            StatusCode::CREATED
+        );
+        let (status, body) = send(&state, Method::POST, draws).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body["history"].as_array().unwrap().len(), 2);
+
+        let (status, body) = send(&state, Method::POST, draws).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["code"], "game_over");
+
+        let (_, body) = send(&state, Method::GET, "/v1/games/default").await;
+        assert_eq!(body["remaining"], 0);
+        assert_eq!(body["finished"], true);
+
+        let (status, body) = send(&state, Method::DELETE, draws).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["remaining"], 2);
+        assert!(body["history"].as_array().unwrap().is_empty());
+
+        let (status, body) = send(&state, Method::GET, "/v1/games/other").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["code"], "game_not_found");
+    }
+
+    /// 表示用の状態が条件付きリクエストに対応し、状態を変更しないこと
+    #[tokio::test]
+    async fn test_game_status_conditional_requests() {
+        let state = test_state();
+        let status = |etag: Option<HeaderValue>| {
+            let mut request = Request::get("/v1/games/default/status");
+            if let Some(etag) = etag {
+                request = request.header(header::IF_NONE_MATCH, etag);
+            }
+            api_router(&state)
+                .with_state(state.clone())
+                .oneshot(request.body(Body::empty()).unwrap())
+        };
+
+        let first = status(None).await.unwrap();
+        assert_eq!(first.status(), StatusCode::OK);
+        let etag = first.headers()[header::ETAG].clone();
+        let body = axum::body::to_bytes(first.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["phase"], "setup");
+        assert_eq!(body["last"], serde_json::Value::Null);
+
+        let unchanged = status(Some(etag.clone())).await.unwrap();
+        assert_eq!(unchanged.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(unchanged.headers()[header::ETAG], etag);
+
+        send(&state, Method::POST, "/v1/games/default/draws").await;
+        let changed = status(Some(etag.clone())).await.unwrap();
+        assert_eq!(changed.status(), StatusCode::OK);
+        assert_ne!(changed.headers()[header::ETAG], etag);
+        assert_eq!(state.game().history.len(), 1);
+    }
+
+    /// 参加コードで開いたカードに抽選済みの数字の印が付き、揃ったカードだけが申告できること
+    #[tokio::test]
+    async fn test_player_card_by_join_code() {
+        let state = test_state_with(75);
+        let alice = register(&state, "Alice");
+        let bob = register(&state, "Bob");
+        let cards = "/v1/cards";
+
+        let (status, card) = send_json(
+            &state,
+            Method::POST,
+            cards,
+            Some(serde_json::json!({ "player": alice })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        let (status, again) = send_json(
+            &state,
+            Method::POST,
+            cards,
+            Some(serde_json::json!({ "player": alice })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(again["id"], card["id"]);
+        let (status, body) = send_json(
+            &state,
+            Method::POST,
+            cards,
+            Some(serde_json::json!({ "player": 99 })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["code"], "player_not_found");
+
+        let join = format!(
+            "/v1/join/{}",
+            card["join_code"].as_str().unwrap().to_lowercase()
+        );
+        let (status, body) = send(&state, Method::GET, &join).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["player"]["name"], "Alice");
+        assert_eq!(body["marks"][2][2], true);
+        assert_eq!(body["marks"][0][0], false);
+        assert_eq!(body["winning"], serde_json::Value::Null);
+
+        let claims = format!("{}/claims", join);
+        let (status, body) = send(&state, Method::POST, &claims).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["code"], "not_bingo");
+
+        // 1列揃うまで抽選する
+        let mut body = body;
+        while body["winning"].is_null() {
+            send(&state, Method::POST, "/v1/games/default/draws").await;
+            body = send(&state, Method::GET, &join).await.1;
+        }
+        let last = body["last"].as_u64().unwrap();
+        assert!(body["card"]["numbers"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .flat_map(|row| row.as_array().unwrap())
+            .any(|number| number.as_u64() == Some(last)));
+        assert_eq!(body["claimed"], false);
+
+        let (status, body) = send(&state, Method::POST, &claims).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body["claim"]["player"], alice);
+        assert_eq!(body["claim"]["card"], card["id"]);
+        assert_eq!(send(&state, Method::GET, &join).await.1["claimed"], true);
+
+        // 抽選が始まった後は新しいカードを発行できない
+        let (status, body) = send_json(
+            &state,
+            Method::POST,
+            cards,
+            Some(serde_json::json!({ "player": bob })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["code"], "invalid_phase");
+
+        let (status, body) = send(&state, Method::GET, "/v1/join/ZZZZZZ").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["code"], "card_not_found");
+        let (status, body) = send(&state, Method::DELETE, "/v1/players/1").await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["code"], "player_in_use");
+    }
+
+    /// 発行したカードが印刷用のシートとして取得でき、範囲外のページが拒否されること
+    #[tokio::test]
+    async fn test_card_sheet() {
+        let state = test_state_with(75);
+        let sheet = |uri: &'static str| {
+            api_router(&state).with_state(state.clone()).oneshot(
+                Request::get(uri)
+                    .header(auth::ADMIN_TOKEN_HEADER, "test-token")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
+
+        let (status, body) = send(&state, Method::GET, "/v1/cards/sheet").await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["code"], "invalid_request");
+
+        for name in ["Alice", "Bob"] {
+            let player = register(&state, name);
+            issue_card(&state, player);
+        }
+        let pdf = sheet("/v1/cards/sheet").await.unwrap();
+        assert_eq!(pdf.status(), StatusCode::OK);
+        assert_eq!(pdf.headers()[header::CONTENT_TYPE], "application/pdf");
+        let body = axum::body::to_bytes(pdf.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(body.starts_with(b"%PDF-"));
+
+        let svg = sheet("/v1/cards/sheet?format=svg").await.unwrap();
+        assert_eq!(svg.status(), StatusCode::OK);
+        assert_eq!(
+            svg.headers()[header::CONTENT_TYPE],
+            "image/svg+xml; charset=utf-8"
+        );
+        let body = axum::body::to_bytes(svg.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains("No. 1") && body.contains("No. 2"));
+
+        let (status, _) = send(&state, Method::GET, "/v1/cards/sheet?format=svg&page=2").await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        // 起動ごとに生成した鍵では、再起動すると検証できないカードになるため印刷できない
+        let args = Args {
+            bingo_max_number: Some(75),
+            prize_count: Some(8),
+            admin_token: Some("test-token".to_string()),
+            no_frontend: true,
+            ..Args::default()
+        };
+        let config = Config::resolve(args, ConfigFile::default(), None).unwrap();
+        let unsigned = AppState::new(1, &config);
+        assert!(!unsigned.card_key.is_configured());
+        let player = register(&unsigned, "Alice");
+        issue_card(&unsigned, player);
+        let (status, body) = send(&unsigned, Method::GET, "/v1/cards/sheet").await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["code"], "invalid_request");
+    }
+
+    /// 確認した申告が当選者として記録され、同じ数字で揃った当選者が景品を分け合うこと
+    #[tokio::test]
+    async fn test_winners_and_prizes() {
+        let state = test_state_with(75);
+        let alice = register(&state, "Alice");
+        let bob = register(&state, "Bob");
+        let join = format!("/v1/join/{}", issue_card(&state, alice));
+        let (status, body) = send_json(
+            &state,
+            Method::PUT,
+            "/v1/winners/prizes",
+            Some(serde_json::json!({ "prizes": ["Turkey", " Cake "] })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["remaining"][1]["name"], "Cake");
+        let winner = |player: u32| {
+            send_json(
+                &state,
+                Method::POST,
+                "/v1/winners",
+                Some(serde_json::json!({ "player": player })),
+            )
+        };
+        let (status, body) = winner(bob).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["code"], "claim_not_found");
+
+        // カードで揃った申告は当選者として記録される
+        while send(&state, Method::GET, &join).await.1["winning"].is_null() {
+            send(&state, Method::POST, "/v1/games/default/draws").await;
+        }
+        let claims = format!("{}/claims", join);
+        let (status, body) = send(&state, Method::POST, &claims).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body["winner"]["player"], alice);
+        assert_eq!(body["winner"]["prize"]["name"], "Turkey");
+        assert_eq!(body["winner"]["draw_index"], body["claim"]["draw_count"]);
+        let (status, body) = send(&state, Method::POST, &claims).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body["winner"], serde_json::Value::Null);
+
+        // 同じ数字で司会者が確認した当選者は景品を分け合う
+        send_json(
+            &state,
+            Method::POST,
+            "/v1/games/default/claims",
+            Some(serde_json::json!({ "player": bob })),
+        )
+        .await;
+        let (status, body) = winner(bob).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body["prize"]["name"], "Turkey");
+        assert_eq!(body["share"], 2);
+        let (status, body) = winner(bob).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["code"], "already_won");
+
+        let (_, body) = send(&state, Method::GET, "/v1/winners").await;
+        assert_eq!(body["remaining"].as_array().unwrap().len(), 1);
+        assert_eq!(body["winners"][0]["share"], 2);
+        assert_eq!(state.winners().round_winners(1), [alice, bob]);
+
+        // リセットすると当選者の記録を取り消し、景品を戻す
+        send(&state, Method::DELETE, "/v1/games/default/draws").await;
+        let (_, body) = send(&state, Method::GET, "/v1/winners").await;
+        assert!(body["winners"].as_array().unwrap().is_empty());
+        assert_eq!(body["remaining"][0]["name"], "Turkey");
+        assert!(state.winners().round_winners(1).is_empty());
+
+        // 申告が遅れても、当たりの形が揃った数字の抽選順で記録する
+        while send(&state, Method::GET, &join).await.1["winning"].is_null() {
+            send(&state, Method::POST, "/v1/games/default/draws").await;
+        }
+        let (_, card) = send(&state, Method::GET, &join).await;
+        send(&state, Method::POST, "/v1/games/default/draws").await;
+        let (status, body) = send(&state, Method::POST, &claims).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body["claim"]["draw_index"], card["draw_count"]);
+        assert_eq!(body["winner"]["draw_index"], card["draw_count"]);
+        assert_eq!(body["winner"]["number"], card["last"]);
+        assert_ne!(body["claim"]["draw_count"], card["draw_count"]);
+    }
+
+    /// 同じ数字で揃った当選者の順位がくじ引きで決まり、記録が再現できること
+    #[tokio::test]
+    async fn test_tie_break() {
+        let state = test_state();
+        let players = [register(&state, "Alice"), register(&state, "Bob")];
+        send_json(
+            &state,
+            Method::PUT,
+            "/v1/winners/prizes",
+            Some(serde_json::json!({ "prizes": ["Turkey", "Cake"] })),
+        )
+        .await;
+        let tie_break = || {
+            send_json(
+                &state,
+                Method::POST,
+                "/v1/winners/tie-breaks",
+                Some(serde_json::json!({ "method": "highest_number" })),
+            )
+        };
+        let (status, body) = tie_break().await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["code"], "no_tie");
+
+        send(&state, Method::POST, "/v1/games/default/draws").await;
+        for player in players {
+            let body = serde_json::json!({ "player": player });
+            send_json(
+                &state,
+                Method::POST,
+                "/v1/games/default/claims",
+                Some(body.clone()),
+            )
+            .await;
+            send_json(&state, Method::POST, "/v1/winners", Some(body)).await;
+        }
+
+        let (status, body) = tie_break().await;
+        assert_eq!(status, StatusCode::CREATED);
+        let record = &body["tie_break"];
+        assert_eq!(record["draw_index"], 1);
+        assert_eq!(record["entrants"], serde_json::json!(players));
+        assert_eq!(record["draws"].as_array().unwrap().len(), 2);
+        assert_eq!(body["winners"][0]["player"], record["order"][0]);
+        assert_eq!(body["winners"][0]["prize"]["name"], "Turkey");
+        assert_eq!(body["winners"][1]["prize"]["name"], "Cake");
+        assert_eq!(body["winners"][1]["share"], 1);
+        // ラウンドのシード値から同じ結果を再現できる
+        let seed = state.session().current().seed;
+        let again = TieBreak::run(1, seed, 1, TieBreakMethod::HighestNumber, &players);
+        assert_eq!(record["order"], serde_json::json!(again.order));
+        assert_eq!(tie_break().await.0, StatusCode::CONFLICT);
+
+        let (status, body) = send(&state, Method::GET, "/v1/winners/tie-breaks").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["tie_breaks"][0]["order"], record["order"]);
+        assert_eq!(state.session().current().tie_breaks.len(), 1);
+    }
+
+    /// 終了したラウンドの抽選履歴と当選者から統計が求められること
+    #[tokio::test]
+    async fn test_statistics() {
+        let state = test_state_with(75);
+        let alice = register(&state, "Alice");
+        let join = format!("/v1/join/{}", issue_card(&state, alice));
+        while send(&state, Method::GET, &join).await.1["winning"].is_null() {
+            send(&state, Method::POST, "/v1/games/default/draws").await;
+        }
+        send(&state, Method::POST, &format!("{}/claims", join)).await;
+        let draws = state.game().history.len();
+        let first = state.game().history[0];
+        send_json(
+            &state,
+            Method::POST,
+            "/v1/games/default/transitions",
+            Some(serde_json::json!({ "action": "finish" })),
+        )
+        .await;
+        let round = send_json(
+            &state,
+            Method::POST,
+            "/v1/session/rounds",
+            Some(serde_json::json!({})),
+        )
+        .await;
+        assert_eq!(round.0, StatusCode::CREATED);
+        // 進行中のラウンドの抽選は集計しない
+        send(&state, Method::POST, "/v1/games/default/draws").await;
+
+        let (status, body) = send(&state, Method::GET, "/v1/statistics?simulations=20").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["rounds"], 1);
+        assert_eq!(body["draws"], draws);
+        assert_eq!(body["numbers"].as_array().unwrap().len(), 75);
+        let number = &body["numbers"][first as usize - 1];
+        assert_eq!(number["count"], 1);
+        assert_eq!(number["positions"], serde_json::json!([1]));
+        assert_eq!(body["first_bingo"]["rounds"], 1);
+        assert_eq!(body["first_bingo"]["mean_draws"], draws as f64);
+        // 抽選された数字ごとに、抽選があった順番の区間で検定する
+        let buckets = ((draws - 1) * stats::POSITION_BUCKETS / 75 + 1).min(stats::POSITION_BUCKETS);
+        if buckets < 2 {
+            assert!(body["uniformity"].is_null());
+        } else {
+            assert_eq!(
+                body["uniformity"]["degrees_of_freedom"],
+                (draws - 1) * (buckets - 1)
+            );
+        }
+        let probability = &body["winner_probability"];
+        assert_eq!(probability["cards"], 1);
+        assert_eq!(probability["simulations"], 20);
+        assert_eq!(probability["by_draw"].as_array().unwrap().len(), 75);
+        assert_eq!(probability["by_draw"][74], 1.0);
+
+        let (status, body) = send(&state, Method::GET, "/v1/statistics?simulations=0").await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["code"], "invalid_request");
+        let (status, _) = send(&state, Method::GET, "/v1/statistics?cards=1001").await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        // 仮想カードの枚数×回数には上限があり、省略した回数は上限に収まるように減らす
+        let (status, _) = send(
+            &state,
+            Method::GET,
+            "/v1/statistics?cards=1000&simulations=101",
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        let (status, body) = send(&state, Method::GET, "/v1/statistics?cards=1000").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["winner_probability"]["simulations"], 100);
+    }
+
+    /// 同時に行うシミュレーションの数を超えた場合は、保存した結果がなければ503を返すこと
+    #[tokio::test]
+    async fn test_statistics_simulation_busy() {
+        let state = test_state_with(75);
+        let (status, _) = send(&state, Method::GET, "/v1/statistics?simulations=20").await;
+        assert_eq!(status, StatusCode::OK);
+
+        let permits = state
+            .simulation_permits
+            .clone()
+            .try_acquire_many_owned(stats::MAX_CONCURRENT_SIMULATIONS as u32)
+            .unwrap();
+        let (status, _) = send(&state, Method::GET, "/v1/statistics?simulations=20").await;
+        assert_eq!(status, StatusCode::OK);
+        let request = Request::get("/v1/statistics?simulations=30")
+            .body(Body::empty())
+            .unwrap();
+        let response = api_router(&state)
+            .with_state(state.clone())
+            .oneshot(request)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["code"], "simulation_busy");
+
+        drop(permits);
+        let (status, _) = send(&state, Method::GET, "/v1/statistics?simulations=30").await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    /// 印刷したカードのQRコードが検証され、偽造・改変されたカードが拒否されること
+    #[tokio::test]
+    async fn test_verify_printed_card() {
+        let state = test_state_with(75);
+        let seed = state.session().seed();
+        let numbers = BingoCard::generate(seed, 101);
+        let payload = state.card_key.qr_payload(101, &numbers);
+        let verify = |payload: String| {
+            send_json(
+                &state,
+                Method::POST,
+                "/v1/cards/verifications",
+                Some(serde_json::json!({ "payload": payload })),
+            )
+        };
+
+        let (status, body) = verify(payload.clone()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["card"], 101);
+        assert_eq!(body["player"], serde_json::Value::Null);
+        assert_eq!(body["numbers"][2][2], serde_json::Value::Null);
+        assert_eq!(body["winning"], serde_json::Value::Null);
+
+        // 1列揃うまで抽選すると、揃ったマスが示される
+        let mut body = body;
+        while body["winning"].is_null() {
+            send(&state, Method::POST, "/v1/games/default/draws").await;
+            body = verify(payload.clone()).await.1;
+        }
+        let last = body["last"].as_u64().unwrap() as u8;
+        assert!(numbers.iter().flatten().any(|n| *n == Some(last)));
+        assert_eq!(body["claimed"], false);
+
+        for forged in [
+            payload.replacen(":101:", ":102:", 1),
+            CardKey::new("other-secret").qr_payload(101, &numbers),
+            "XMASBINGO:101".to_string(),
+        ] {
+            let (status, body) = verify(forged).await;
+            assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+            assert_eq!(body["code"], "invalid_card");
+        }
+
+        // QRコードの内容の代わりに、カードを撮影したPNG画像を送れる
+        let upload = |image: Vec<u8>| {
+            api_router(&state).with_state(state.clone()).oneshot(
+                Request::post("/v1/cards/verifications")
+                    .header(auth::ADMIN_TOKEN_HEADER, "test-token")
+                    .header(header::CONTENT_TYPE, "image/png")
+                    .body(Body::from(image))
+                    .unwrap(),
+            )
+        };
+        let code = qrcode::QrCode::new(&payload).unwrap();
+        let (width, scale) = (code.width() + 8, 4);
+        let colors = code.to_colors();
+        let mut pixels = vec![255u8; width * width * scale * scale];
+        for (i, pixel) in pixels.iter_mut().enumerate() {
+            let (x, y) = (i % (width * scale) / scale, i / (width * scale) / scale);
+            let module = (4..width - 4).contains(&x) && (4..width - 4).contains(&y);
+            if module && colors[(y - 4) * code.width() + x - 4] == qrcode::Color::Dark {
+                *pixel = 0;
+            }
+        }
+        let mut image = Vec::new();
+        let size = (width * scale) as u32;
+        let mut encoder = png::Encoder::new(&mut image, size, size);
+        encoder.set_color(png::ColorType::Grayscale);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&pixels).unwrap();
+        writer.finish().unwrap();
+
+        let response = upload(image).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["card"], 101);
+
+        let response = upload(b"not an image".to_vec()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["code"], "unreadable_image");
+    }
+
+    /// フェーズの遷移が記録され、許可されない操作や一時停止中の抽選が拒否されること
+    #[tokio::test]
+    async fn test_phase_transitions() {
+        let state = test_state();
+        let transitions = "/v1/games/default/transitions";
+        let transition = |action: &str| {
+            send_json(
+                &state,
+                Method::POST,
+                transitions,
+                Some(serde_json::json!({ "action": action })),
+            )
+        };
+
+        let (status, body) = transition("start").await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["code"], "invalid_phase");
+
+        for action in ["lock", "start", "pause"] {
+            assert_eq!(transition(action).await.0, StatusCode::OK);
+        }
+        let (status, body) = send(&state, Method::POST, "/v1/games/default/draws").await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["code"], "invalid_phase");
+
+        let (_, body) = transition("resume").await;
+        assert_eq!(body["phase"], "running");
+        assert_eq!(
+            body["actions"],
+            serde_json::json!(["pause", "finish", "reset"])
+        );
+        assert_eq!(body["transitions"].as_array().unwrap().len(), 4);
+        assert_eq!(body["transitions"][2]["from"], "running");
+        assert_eq!(body["transitions"][2]["to"], "paused");
+
+        let (status, _) = transition("jump").await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    /// 自動抽選をAPIで操作でき、ビンゴの申告で停止すること
+    #[tokio::test(start_paused = true)]
+    async fn test_auto_draw_stops_on_claim() {
+        let state = test_state();
+        register(&state, "Alice");
+        let auto_draw = "/v1/games/default/auto-draw";
+
+        let (status, _) = send_json(
+            &state,
+            Method::PATCH,
+            auto_draw,
+            Some(serde_json::json!({})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let (status, _) = send_json(
+            &state,
+            Method::POST,
+            auto_draw,
+            Some(serde_json::json!({ "interval_secs": 0 })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        let (status, response) = send_json(
+            &state,
+            Method::POST,
+            auto_draw,
+            Some(serde_json::json!({ "interval_secs": 5 })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(response["state"], "running");
+        let (status, response) =
+            send_json(&state, Method::POST, auto_draw, Some(serde_json::json!({}))).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(response["code"], "auto_draw_active");
+
+        tokio::time::sleep(std::time::Duration::from_secs(6)).await;
+        let (_, response) = send(&state, Method::GET, auto_draw).await;
+        assert_eq!(response["draws"], 1);
+
+        let (status, response) = send_json(
+            &state,
+            Method::POST,
+            "/v1/games/default/claims",
+            Some(serde_json::json!({ "player": 1 })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(response["claim"]["draw_count"], 1);
+        assert_eq!(response["auto_draw"]["state"], "stopped");
+        assert_eq!(response["auto_draw"]["stop_reason"], "claimed");
+
+        tokio::time::sleep(std::time::Duration::from_secs(20)).await;
+        assert_eq!(state.game().history.len(), 1);
+    }
+
+    /// 次のラウンドを開始しても前のラウンドの記録を参照できること
+    #[tokio::test]
+    async fn test_rounds_keep_history() {
+        let state = test_state();
+        register(&state, "Alice");
+        let rounds = "/v1/session/rounds";
+        let next_round = || {
+            send_json(
+                &state,
+                Method::POST,
+                rounds,
+                Some(serde_json::json!({ "pattern": "blackout", "prize": "Turkey" })),
+            )
+        };
+
+        send(&state, Method::POST, "/v1/games/default/draws").await;
+        let (status, body) = next_round().await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["code"], "invalid_phase");
+        let (status, _) = send_json(
+            &state,
+            Method::POST,
+            "/v1/games/default/claims",
+            Some(serde_json::json!({ "player": 1 })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let first = state.game().history.clone();
+        send_json(
+            &state,
+            Method::POST,
+            "/v1/games/default/transitions",
+            Some(serde_json::json!({ "action": "finish" })),
+        )
+        .await;
+        let (status, body) = next_round().await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body["number"], 2);
+        assert_eq!(body["pattern"], "blackout");
+        assert_eq!(body["phase"], "setup");
+        assert!(state.game().history.is_empty());
+
+        let (status, body) = send(&state, Method::GET, "/v1/session/rounds/1").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["phase"], "archived");
+        assert_eq!(body["history"], serde_json::json!(first));
+
+        // 当選者は終了したラウンドの申告から当選者の一覧に記録する
+        let round_winners = |winners: serde_json::Value| {
+            send_json(
+                &state,
+                Method::PATCH,
+                "/v1/session/rounds/1",
+                Some(serde_json::json!({ "winners": winners })),
+            )
+        };
+        let bob = register(&state, "Bob");
+        let (status, body) = round_winners(serde_json::json!([1, bob])).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["code"], "claim_not_found");
+        assert!(state.winners().winners().is_empty());
+        let (status, body) = round_winners(serde_json::json!([1])).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["winners"], serde_json::json!([1]));
+        assert_eq!(state.winners().winners()[0].round, 1);
+        let (_, body) = send(&state, Method::GET, "/v1/session").await;
+        assert_eq!(body["current_round"], 2);
+        assert_eq!(body["rounds"][0]["winners"], serde_json::json!([1]));
+        assert_eq!(body["rounds"][0]["draws"], 1);
+        assert_eq!(body["rounds"][1]["prize"], "Turkey");
+        let (_, body) = send(&state, Method::GET, "/v1/winners").await;
+        assert_eq!(body["winners"][0]["player"], 1);
+
+        // 含まれなくなった当選者の記録は取り消す
+        let (status, body) = round_winners(serde_json::json!([])).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["winners"], serde_json::json!([]));
+        assert!(!state.winners().has_won(1));
+
+        let (status, body) = send(&state, Method::GET, "/v1/session/rounds/3").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["code"], "round_not_found");
+    }
+
+    /// 参加者名簿をAPIで管理でき、申告とあみだくじの枠が参加者IDで参加者を指すこと
+    #[tokio::test]
+    async fn test_players_are_shared() {
+        let state = test_state();
+        let players = "/v1/players";
+
+        let (status, alice) = send_json(
+            &state,
+            Method::POST,
+            players,
+            Some(serde_json::json!({ "name": "Alice", "furigana": "ありす", "avatar": "🦌" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(alice["id"], 1);
+        assert_eq!(alice["avatar"], "🦌");
+        let (status, body) = send_json(
+            &state,
+            Method::POST,
+            players,
+            Some(serde_json::json!({ "name": "Alice" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["code"], "duplicate_player");
+        let (status, body) = send_json(
+            &state,
+            Method::POST,
+            players,
+            Some(serde_json::json!({ "name": "Bob", "avatar": ":)" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["code"], "invalid_player");
+
+        // 名簿にない参加者名は名簿に登録される
+        let (_, amida) = send_json(
+            &state,
+            Method::PUT,
+            "/v1/amida",
+            Some(serde_json::json!({ "items": ["Carol", "Alice"], "rule": "leftover" })),
+        )
+        .await;
+        assert_eq!(amida["players"], serde_json::json!([2, 1]));
+        let (_, amida) = send_json(
+            &state,
+            Method::PATCH,
+            "/v1/amida",
+            Some(serde_json::json!({ "players": [1] })),
+        )
+        .await;
+        assert_eq!(amida["items"], serde_json::json!(["Alice"]));
+        let exclusions = serde_json::json!({ "exclusions": [{ "guest": "Alice", "prize": 2 }] });
+        let (_, amida) = send_json(
+            &state,
+            Method::PATCH,
+            "/v1/amida",
+            Some(serde_json::json!({ "constraints": exclusions })),
+        )
+        .await;
+        assert_eq!(amida["constraints"]["exclusions"][0]["player"], 1);
+
+        // 枠番号の最大数を超える参加者は、名簿に登録する前に拒否する
+        let crowd: Vec<String> = (0..256).map(|i| format!("Guest {}", i)).collect();
+        let (status, body) = send_json(
+            &state,
+            Method::PATCH,
+            "/v1/amida",
+            Some(serde_json::json!({ "items": crowd })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["code"], "invalid_request");
+
+        // 表示名を変更すると、あみだくじの枠も同じ参加者を指したままになる
+        let (status, _) = send_json(
+            &state,
+            Method::PATCH,
+            "/v1/players/1",
+            Some(serde_json::json!({ "name": "Alicia", "avatar": "" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (_, amida) = send(&state, Method::GET, "/v1/amida").await;
+        assert_eq!(amida["items"], serde_json::json!(["Alicia"]));
+        assert_eq!(amida["players"], serde_json::json!([1]));
+        assert_eq!(
+            amida["constraints"]["exclusions"][0],
+            serde_json::json!({ "guest": "Alicia", "player": 1, "prize": 2 })
+        );
+
+        let claims = "/v1/games/default/claims";
+        let (status, body) = send_json(
+            &state,
+            Method::POST,
+            claims,
+            Some(serde_json::json!({ "player": 9 })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["code"], "player_not_found");
+        let (status, body) = send_json(
+            &state,
+            Method::POST,
+            claims,
+            Some(serde_json::json!({ "player": 2 })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body["claim"]["player"], 2);
+
+        // 参照されている参加者は削除できない
+        for id in [1, 2] {
+            let (status, body) = send(&state, Method::DELETE, &format!("/v1/players/{}", id)).await;
+            assert_eq!(status, StatusCode::CONFLICT);
+            assert_eq!(body["code"], "player_in_use");
+        }
+        let (_, body) = send(&state, Method::GET, players).await;
+        assert_eq!(body["players"].as_array().unwrap().len(), 2);
+
+        // 参照されている参加者を含まないファイルは取り込まない
+        let import = |csv: &str| {
+            send_body(
+                &state,
+                Method::POST,
+                "/v1/amida/imports",
+                Some(("text/csv", csv.to_string())),
+            )
+        };
+        let (status, body) = import("name\nAlicia\nDave\n").await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["code"], "player_in_use");
+        let (_, body) = send(&state, Method::GET, players).await;
+        assert_eq!(body["players"].as_array().unwrap().len(), 2);
+        // あみだくじの割り当て制約で参照されている参加者も外せない
+        let (status, body) = import("name\nCarol\nDave\n").await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["code"], "player_in_use");
+        let (_, amida) = send_json(
+            &state,
+            Method::PATCH,
+            "/v1/amida",
+            Some(serde_json::json!({ "constraints": {} })),
+        )
+        .await;
+        assert_eq!(amida["constraints"]["exclusions"], serde_json::json!([]));
+        let (status, body) = import("name\nCarol\nDave\n").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["items"], serde_json::json!(["Carol", "Dave"]));
+        let (_, body) = send(&state, Method::GET, players).await;
+        assert_eq!(body["players"][0]["id"], 2);
+    }
+
+    /// 全ての枠を公開するまで、あみだくじのシード値を返さないこと
+    #[tokio::test]
+    async fn test_amida_seed_hidden_until_revealed() {
+        let state = test_state();
+        let (status, amida) = send_json(
+            &state,
+            Method::PUT,
+            "/v1/amida",
+            Some(serde_json::json!({ "items": ["Alice", "Bob"], "rule": "leftover" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(amida["seed"].is_null());
+        // 公開されるビンゴのシード値からは割り当てを求められない
+        let (_, game) = send(&state, Method::GET, "/v1/games/default").await;
+        assert_eq!(game["seed"], 1);
+
+        for uri in ["/v1/amida", "/v1/amida/result"] {
+            let (status, body) = send(&state, Method::GET, uri).await;
+            assert_eq!(status, StatusCode::OK);
+            assert!(body["seed"].is_null(), "{}", uri);
+        }
+
+        let mut revealed = 0;
+        for prize in 1..=8 {
+            let (status, body) = send_json(
+                &state,
+                Method::POST,
+                "/v1/amida/reveals",
+                Some(serde_json::json!({ "prize": prize })),
+            )
+            .await;
+            if status != StatusCode::OK {
+                continue;
+            }
+            revealed += 1;
+            assert_eq!(body["seed"].is_null(), revealed < 2);
+        }
+        assert_eq!(revealed, 2);
+        let (_, result) = send(&state, Method::GET, "/v1/amida/result").await;
+        assert!(result["seed"].is_u64());
+        let (_, amida) = send(&state, Method::GET, "/v1/amida").await;
+        assert_eq!(amida["seed"], result["seed"]);
+    }
+
+    /// 旧APIのレスポンスに移行先を示す `Link` ヘッダーが付くこと
+    #[tokio::test]
+    async fn test_legacy_routes_link_to_successor() {
+        let state = test_state();
+        let response = api_router(&state)
+            .with_state(state.clone())
+            .oneshot(Request::get("/amida").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[DEPRECATION], "true");
+        assert_eq!(
+            response.headers()[header::LINK],
+            "</api/v1/amida>; rel=\"successor-version\""
+        );
+    }
+}
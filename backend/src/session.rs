@@ -0,0 +1,339 @@
+use crate::domain::{BingoClaim, BingoGame};
+use crate::lifecycle::{GamePhase, GamePhaseError, PhaseAction, PhaseTransition};
+use crate::player::PlayerId;
+use crate::rng::{derive_seed, XorShift};
+use crate::tiebreak::TieBreak;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// ビンゴの当たりの形
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema, clap::ValueEnum,
+)]
+#[serde(rename_all = "snake_case")]
+#[value(rename_all = "snake_case")]
+pub enum WinPattern {
+    /// 縦・横・斜めのいずれか1列
+    #[default]
+    SingleLine,
+    /// 2列
+    TwoLines,
+    /// 四隅
+    FourCorners,
+    /// 中央の十字
+    Cross,
+    /// 全てのマス
+    Blackout,
+}
+
+impl WinPattern {
+    /// APIやコマンドラインで使う名前 (`single_line` など)
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::SingleLine => "single_line",
+            Self::TwoLines => "two_lines",
+            Self::FourCorners => "four_corners",
+            Self::Cross => "cross",
+            Self::Blackout => "blackout",
+        }
+    }
+}
+
+/// ラウンドの設定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct RoundSettings {
+    /// ラウンド番号 (1から始まる)
+    pub number: u32,
+    /// 当たりの形
+    pub pattern: WinPattern,
+    /// このラウンドの景品
+    pub prize: Option<String>,
+    /// このラウンドの抽選に使うシード値 (セッションのシード値から求める)
+    pub seed: u32,
+    /// 同じ数字で揃った当選者のくじ引きの記録 (くじ引きをした順)
+    pub tie_breaks: Vec<TieBreak>,
+    /// ラウンドを開始した日時
+    #[schema(value_type = String, format = DateTime)]
+    pub started_at: DateTime<Local>,
+}
+
+/// ラウンドの記録
+///
+/// 終了したラウンドはこの形で保存され、新しいラウンドを開始した後も参照できます。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct Round {
+    /// ラウンドの設定
+    #[serde(flatten)]
+    pub settings: RoundSettings,
+    /// フェーズ
+    pub phase: GamePhase,
+    /// 抽選履歴
+    pub history: Vec<u8>,
+    /// 抽選日時 (抽選履歴と同じ順)
+    #[schema(value_type = Vec<String>)]
+    pub drawn_at: Vec<DateTime<Local>>,
+    /// ビンゴの申告
+    pub claims: Vec<BingoClaim>,
+    /// フェーズの遷移
+    pub transitions: Vec<PhaseTransition>,
+    /// ラウンドを終えた日時 (進行中のラウンドはNone)
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub ended_at: Option<DateTime<Local>>,
+}
+
+impl Round {
+    /// ゲームの現在の状態からラウンドの記録を作る
+    pub fn snapshot(settings: &RoundSettings, game: &BingoGame) -> Self {
+        Self {
+            settings: settings.clone(),
+            phase: game.phase(),
+            history: game.history.clone(),
+            drawn_at: game.drawn_at.clone(),
+            claims: game.claims.clone(),
+            transitions: game.transitions().to_vec(),
+            ended_at: None,
+        }
+    }
+}
+
+/// ラウンドの設定の変更内容 (Noneの項目は変更しない)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoundChanges {
+    /// 当たりの形
+    pub pattern: Option<WinPattern>,
+    /// 景品 (`Some(None)` で消去)
+    pub prize: Option<Option<String>>,
+}
+
+/// 1回のパーティーのビンゴ (複数のラウンドからなる)
+///
+/// 進行中のラウンドの抽選は `BingoGame` が行い、ここではラウンドの設定と終了したラウンドの記録を管理します。
+/// 各ラウンドの当選者は `WinnerRegistry` に記録します。
+/// 各ラウンドのシード値はセッションのシード値とラウンド番号から求めるため、
+/// 同じシード値なら全てのラウンドの抽選順を再現できます。
+#[derive(Debug, Clone)]
+pub struct Session {
+    /// セッションのシード値
+    seed: u32,
+    /// 進行中のラウンドの設定
+    current: RoundSettings,
+    /// 終了したラウンドの記録 (古い順)
+    rounds: Vec<Round>,
+}
+
+impl Session {
+    /// ラウンド1から始まるセッションを作成する
+    pub fn new(seed: u32) -> Self {
+        Self {
+            seed,
+            current: RoundSettings {
+                number: 1,
+                pattern: WinPattern::default(),
+                prize: None,
+                seed: round_seed(seed, 1),
+                tie_breaks: Vec::new(),
+                started_at: Local::now(),
+            },
+            rounds: Vec::new(),
+        }
+    }
+
+    /// セッションのシード値
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    /// 進行中のラウンドの設定
+    pub fn current(&self) -> &RoundSettings {
+        &self.current
+    }
+
+    /// 終了したラウンドの記録
+    pub fn rounds(&self) -> &[Round] {
+        &self.rounds
+    }
+
+    /// 終了したラウンドの記録を番号で探す
+    pub fn round(&self, number: u32) -> Option<&Round> {
+        self.rounds
+            .iter()
+            .find(|round| round.settings.number == number)
+    }
+
+    /// ラウンドの設定を変更する
+    ///
+    /// 終了したラウンドも変更できます (記入漏れを後から直すため)。
+    ///
+    /// # Returns
+    /// * `bool` - 指定した番号のラウンドがあったかどうか
+    pub fn update_round(&mut self, number: u32, changes: RoundChanges) -> bool {
+        let Some(settings) = self.settings_mut(number) else {
+            return false;
+        };
+        if let Some(pattern) = changes.pattern {
+            settings.pattern = pattern;
+        }
+        if let Some(prize) = changes.prize {
+            settings.prize = prize;
+        }
+        true
+    }
+
+    /// 当選の記録を取り消した参加者が加わっていたくじ引きの記録を除く
+    ///
+    /// # Arguments
+    /// * `number` - ラウンド番号
+    /// * `players` - 当選の記録を取り消した参加者
+    pub fn remove_tie_breaks(&mut self, number: u32, players: &[PlayerId]) {
+        let Some(settings) = self.settings_mut(number) else {
+            return;
+        };
+        settings.tie_breaks.retain(|tie_break| {
+            !tie_break
+                .entrants
+                .iter()
+                .any(|player| players.contains(player))
+        });
+    }
+
+    /// 進行中または終了したラウンドの設定を番号で探す
+    fn settings_mut(&mut self, number: u32) -> Option<&mut RoundSettings> {
+        if self.current.number == number {
+            return Some(&mut self.current);
+        }
+        self.rounds
+            .iter_mut()
+            .find(|round| round.settings.number == number)
+            .map(|round| &mut round.settings)
+    }
+
+    /// 進行中のラウンドにくじ引きの記録を加える
+    pub fn add_tie_break(&mut self, tie_break: TieBreak) {
+        self.current.tie_breaks.push(tie_break);
+    }
+
+    /// 全てのラウンドのくじ引きの記録 (ラウンド順)
+    pub fn tie_breaks(&self) -> Vec<&TieBreak> {
+        self.rounds
+            .iter()
+            .map(|round| &round.settings)
+            .chain(std::iter::once(&self.current))
+            .flat_map(|settings| settings.tie_breaks.iter())
+            .collect()
+    }
+
+    /// 進行中のラウンドを終え、次のラウンドを開始する
+    ///
+    /// 終了したゲームは保管済み (`archived`) にしてから記録し、
+    /// 次のラウンドのシード値で新しいゲームに置き換えます。
+    ///
+    /// # Arguments
+    /// * `game` - 進行中のラウンドのゲーム
+    /// * `pattern` - 次のラウンドの当たりの形
+    /// * `prize` - 次のラウンドの景品
+    ///
+    /// # Returns
+    /// * `Result<&RoundSettings, GamePhaseError>` - 次のラウンドの設定
+    ///   (進行中のラウンドが終了・保管済みでない場合はエラー)
+    pub fn start_next_round(
+        &mut self,
+        game: &mut BingoGame,
+        pattern: WinPattern,
+        prize: Option<String>,
+    ) -> Result<&RoundSettings, GamePhaseError> {
+        if game.phase() != GamePhase::Archived {
+            game.transition(PhaseAction::Archive)?;
+        }
+
+        let mut round = Round::snapshot(&self.current, game);
+        round.ended_at = Some(Local::now());
+        self.rounds.push(round);
+
+        let number = self.current.number + 1;
+        self.current = RoundSettings {
+            number,
+            pattern,
+            prize,
+            seed: round_seed(self.seed, number),
+            tie_breaks: Vec::new(),
+            started_at: Local::now(),
+        };
+        *game = BingoGame::new(
+            game.max_number(),
+            Box::new(XorShift::new(self.current.seed)),
+        );
+        Ok(&self.current)
+    }
+}
+
+/// ラウンドのシード値を求める
+///
+/// ラウンド1はセッションのシード値そのもの (ラウンドを導入する前と同じ抽選順) で、
+/// 2以降はラウンド番号をストリーム番号として導出します。
+pub fn round_seed(seed: u32, number: u32) -> u32 {
+    match number {
+        1 => seed,
+        _ => derive_seed(seed, number),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_game(seed: u32) -> BingoGame {
+        BingoGame::new(75, Box::new(XorShift::new(seed)))
+    }
+
+    #[test]
+    fn test_round_seeds() {
+        assert_eq!(round_seed(12345, 1), 12345);
+        let seeds: Vec<_> = (1..=4).map(|number| round_seed(12345, number)).collect();
+        for (i, seed) in seeds.iter().enumerate() {
+            assert!(!seeds[i + 1..].contains(seed));
+        }
+    }
+
+    #[test]
+    fn test_start_next_round_keeps_history() {
+        let mut session = Session::new(42);
+        let mut game = new_game(42);
+        game.draw().unwrap();
+        game.draw().unwrap();
+        let first = game.history.clone();
+
+        // 抽選中のラウンドは終えられない
+        assert!(session
+            .start_next_round(&mut game, WinPattern::Blackout, None)
+            .is_err());
+        assert_eq!(game.history, first);
+
+        game.transition(PhaseAction::Finish).unwrap();
+        assert!(session.update_round(
+            1,
+            RoundChanges {
+                prize: Some(Some("Cake".to_string())),
+                ..RoundChanges::default()
+            }
+        ));
+        let next = session
+            .start_next_round(&mut game, WinPattern::Blackout, Some("Turkey".to_string()))
+            .unwrap();
+        assert_eq!(next.number, 2);
+        assert_eq!(next.seed, round_seed(42, 2));
+        assert!(game.history.is_empty());
+        assert_eq!(game.phase(), GamePhase::Setup);
+
+        let round = session.round(1).unwrap();
+        assert_eq!(round.history, first);
+        assert_eq!(round.phase, GamePhase::Archived);
+        assert_eq!(round.settings.prize.as_deref(), Some("Cake"));
+        assert!(round.ended_at.is_some());
+        assert!(!session.update_round(3, RoundChanges::default()));
+
+        // 同じシード値のラウンドは同じ順序で抽選される
+        let mut replay = new_game(round_seed(42, 2));
+        assert_eq!(replay.draw().unwrap(), game.draw().unwrap());
+    }
+}
@@ -0,0 +1,401 @@
+use crate::card::{BingoCard, CardGrid, CardId, CardKey, CARD_SIZE};
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, Str};
+use qrcode::{Color, EcLevel, QrCode};
+use serde::Deserialize;
+use std::fmt::Write;
+use utoipa::ToSchema;
+
+/// 用紙の幅 (mm、A4)
+const PAGE_WIDTH: f32 = 210.0;
+/// 用紙の高さ (mm、A4)
+const PAGE_HEIGHT: f32 = 297.0;
+/// 1ページの列数
+const SHEET_COLUMNS: usize = 2;
+/// 1ページの行数
+const SHEET_ROWS: usize = 3;
+/// 1ページに並べるカードの枚数
+pub const CARDS_PER_PAGE: usize = SHEET_COLUMNS * SHEET_ROWS;
+/// カード1枚分の枠の幅 (mm)
+const SLOT_WIDTH: f32 = PAGE_WIDTH / SHEET_COLUMNS as f32;
+/// カード1枚分の枠の高さ (mm)
+const SLOT_HEIGHT: f32 = PAGE_HEIGHT / SHEET_ROWS as f32;
+/// マスの1辺 (mm)
+const CELL: f32 = 12.0;
+/// QRコードの1辺 (mm、周りの余白を含む)
+const QR_SIZE: f32 = 26.0;
+/// 線の太さ (mm)
+const LINE_WIDTH: f32 = 0.3;
+/// 列の見出し
+const HEADER: [&str; CARD_SIZE] = ["B", "I", "N", "G", "O"];
+/// 1mmあたりのポイント数 (PDFの座標の単位)
+const PT_PER_MM: f32 = 72.0 / 25.4;
+
+/// 印刷用のシートの出力形式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum SheetFormat {
+    /// PDF (全てのページを1つのファイルにまとめる)
+    #[default]
+    Pdf,
+    /// SVG (1ページずつ)
+    Svg,
+}
+
+impl SheetFormat {
+    /// Content-Type ヘッダーの値
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Pdf => "application/pdf",
+            Self::Svg => "image/svg+xml; charset=utf-8",
+        }
+    }
+
+    /// ダウンロード時のファイル拡張子
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Pdf => "pdf",
+            Self::Svg => "svg",
+        }
+    }
+}
+
+/// 印刷するカード
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrintedCard {
+    /// カードID
+    pub id: CardId,
+    /// マスの数字
+    pub numbers: CardGrid<Option<u8>>,
+    /// QRコードの内容 (カードIDと署名)
+    pub qr: String,
+}
+
+impl PrintedCard {
+    /// シード値とカードIDからカードを作る (サーバーが発行するカードと同じ数字になる)
+    pub fn generate(key: &CardKey, seed: u32, id: CardId) -> Self {
+        let numbers = BingoCard::generate(seed, id);
+        Self {
+            id,
+            numbers,
+            qr: key.qr_payload(id, &numbers),
+        }
+    }
+
+    /// 発行したカードを印刷する
+    pub fn issued(key: &CardKey, card: &BingoCard) -> Self {
+        Self {
+            id: card.id,
+            numbers: card.numbers,
+            qr: key.qr_payload(card.id, &card.numbers),
+        }
+    }
+}
+
+/// ページに描く図形 (座標はページの左上からのmm)
+#[derive(Debug, Clone, PartialEq)]
+enum Shape {
+    /// 長方形 (`fill` がfalseの場合は枠線のみ)
+    Rect {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        fill: bool,
+    },
+    /// 中央揃えの文字列 (`y` はベースライン)
+    Text {
+        x: f32,
+        y: f32,
+        size: f32,
+        text: String,
+    },
+}
+
+/// 全てのカードをA4のページに並べる
+///
+/// SVGとPDFは同じ図形から描くため、どちらで印刷しても同じ配置になります。
+fn layout(cards: &[PrintedCard]) -> Vec<Vec<Shape>> {
+    cards
+        .chunks(CARDS_PER_PAGE)
+        .map(|page| {
+            page.iter()
+                .enumerate()
+                .flat_map(|(index, card)| {
+                    let x = (index % SHEET_COLUMNS) as f32 * SLOT_WIDTH;
+                    let y = (index / SHEET_COLUMNS) as f32 * SLOT_HEIGHT;
+                    card_shapes(card, x, y)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// カード1枚分の図形
+fn card_shapes(card: &PrintedCard, left: f32, top: f32) -> Vec<Shape> {
+    let mut shapes = vec![Shape::Rect {
+        x: left + 5.0,
+        y: top + 5.0,
+        width: SLOT_WIDTH - 10.0,
+        height: SLOT_HEIGHT - 10.0,
+        fill: false,
+    }];
+
+    let grid_x = left + 10.0;
+    let grid_y = top + 22.0;
+    for (column, letter) in HEADER.iter().enumerate() {
+        shapes.push(Shape::Text {
+            x: grid_x + (column as f32 + 0.5) * CELL,
+            y: grid_y - 3.0,
+            size: 8.0,
+            text: letter.to_string(),
+        });
+    }
+    for (row, numbers) in card.numbers.iter().enumerate() {
+        for (column, number) in numbers.iter().enumerate() {
+            let x = grid_x + column as f32 * CELL;
+            let y = grid_y + row as f32 * CELL;
+            shapes.push(Shape::Rect {
+                x,
+                y,
+                width: CELL,
+                height: CELL,
+                fill: false,
+            });
+            let (text, size) = match number {
+                Some(number) => (number.to_string(), 5.5),
+                None => ("FREE".to_string(), 3.0),
+            };
+            shapes.push(Shape::Text {
+                x: x + CELL / 2.0,
+                y: y + CELL / 2.0 + size * 0.35,
+                size,
+                text,
+            });
+        }
+    }
+
+    let qr_x = grid_x + CARD_SIZE as f32 * CELL + 3.0;
+    shapes.extend(qr_shapes(&card.qr, qr_x, grid_y));
+    shapes.push(Shape::Text {
+        x: qr_x + QR_SIZE / 2.0,
+        y: grid_y + QR_SIZE + 6.0,
+        size: 4.5,
+        text: format!("No. {}", card.id),
+    });
+    shapes
+}
+
+/// QRコードの図形 (横に続く黒いモジュールは1つの長方形にまとめる)
+fn qr_shapes(payload: &str, left: f32, top: f32) -> Vec<Shape> {
+    let code = QrCode::with_error_correction_level(payload, EcLevel::M)
+        .expect("card payload always fits in a QR code");
+    let width = code.width();
+    let colors = code.to_colors();
+    // 読み取りやすいように周りに2モジュール分の余白を取る
+    let module = QR_SIZE / (width + 4) as f32;
+    let mut shapes = Vec::new();
+    for (row, modules) in colors.chunks(width).enumerate() {
+        let mut column = 0;
+        while column < width {
+            if modules[column] != Color::Dark {
+                column += 1;
+                continue;
+            }
+            let start = column;
+            while column < width && modules[column] == Color::Dark {
+                column += 1;
+            }
+            shapes.push(Shape::Rect {
+                x: left + (start + 2) as f32 * module,
+                y: top + (row + 2) as f32 * module,
+                width: (column - start) as f32 * module,
+                height: module,
+                fill: true,
+            });
+        }
+    }
+    shapes
+}
+
+/// カードをSVGのシートにする
+///
+/// # Returns
+/// * `Vec<String>` - 1ページずつのSVG文書
+pub fn render_svg(cards: &[PrintedCard]) -> Vec<String> {
+    layout(cards)
+        .iter()
+        .map(|shapes| {
+            let mut svg = format!(
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}mm\" height=\"{h}mm\" viewBox=\"0 0 {w} {h}\">\n\
+                 <rect width=\"{w}\" height=\"{h}\" fill=\"white\"/>\n\
+                 <g font-family=\"Helvetica, Arial, sans-serif\" text-anchor=\"middle\">\n",
+                w = PAGE_WIDTH,
+                h = PAGE_HEIGHT
+            );
+            for shape in shapes {
+                match shape {
+                    Shape::Rect {
+                        x,
+                        y,
+                        width,
+                        height,
+                        fill: true,
+                    } => writeln!(
+                        svg,
+                        "<rect x=\"{:.3}\" y=\"{:.3}\" width=\"{:.3}\" height=\"{:.3}\" fill=\"black\"/>",
+                        x, y, width, height
+                    ),
+                    Shape::Rect {
+                        x,
+                        y,
+                        width,
+                        height,
+                        fill: false,
+                    } => writeln!(
+                        svg,
+                        "<rect x=\"{:.3}\" y=\"{:.3}\" width=\"{:.3}\" height=\"{:.3}\" fill=\"none\" stroke=\"black\" stroke-width=\"{}\"/>",
+                        x, y, width, height, LINE_WIDTH
+                    ),
+                    Shape::Text { x, y, size, text } => writeln!(
+                        svg,
+                        "<text x=\"{:.3}\" y=\"{:.3}\" font-size=\"{}\">{}</text>",
+                        x, y, size, text
+                    ),
+                }
+                .expect("writing to a String never fails");
+            }
+            svg.push_str("</g>\n</svg>\n");
+            svg
+        })
+        .collect()
+}
+
+/// カードをPDFのシートにする
+///
+/// 文字はPDFの標準フォント (Helvetica) で描くため、フォントを埋め込みません。
+///
+/// # Returns
+/// * `Vec<u8>` - 全てのページをまとめたPDF文書
+pub fn render_pdf(cards: &[PrintedCard]) -> Vec<u8> {
+    let pages = layout(cards);
+    let catalog_id = Ref::new(1);
+    let tree_id = Ref::new(2);
+    let font_id = Ref::new(3);
+    let page_ids: Vec<Ref> = (0..pages.len() as i32)
+        .map(|i| Ref::new(4 + i * 2))
+        .collect();
+    let font = Name(b"F1");
+
+    let mut pdf = Pdf::new();
+    pdf.catalog(catalog_id).pages(tree_id);
+    pdf.pages(tree_id)
+        .kids(page_ids.iter().copied())
+        .count(pages.len() as i32);
+    pdf.type1_font(font_id).base_font(Name(b"Helvetica"));
+
+    for (shapes, &page_id) in pages.iter().zip(&page_ids) {
+        let content_id = Ref::new(page_id.get() + 1);
+        let mut page = pdf.page(page_id);
+        page.parent(tree_id)
+            .media_box(Rect::new(0.0, 0.0, pt(PAGE_WIDTH), pt(PAGE_HEIGHT)))
+            .contents(content_id);
+        page.resources().fonts().pair(font, font_id);
+        page.finish();
+
+        let mut content = Content::new();
+        content.set_line_width(pt(LINE_WIDTH));
+        for shape in shapes {
+            match shape {
+                Shape::Rect {
+                    x,
+                    y,
+                    width,
+                    height,
+                    fill,
+                } => {
+                    content.rect(
+                        pt(*x),
+                        pt(PAGE_HEIGHT - y - height),
+                        pt(*width),
+                        pt(*height),
+                    );
+                    if *fill {
+                        content.fill_nonzero();
+                    } else {
+                        content.stroke();
+                    }
+                }
+                Shape::Text { x, y, size, text } => {
+                    let x = x - helvetica_width(text) * size / 2.0;
+                    content
+                        .begin_text()
+                        .set_font(font, pt(*size))
+                        .next_line(pt(x), pt(PAGE_HEIGHT - y))
+                        .show(Str(text.as_bytes()))
+                        .end_text();
+                }
+            }
+        }
+        pdf.stream(content_id, &content.finish());
+    }
+    pdf.finish()
+}
+
+/// mmをPDFのポイントに変換する
+fn pt(mm: f32) -> f32 {
+    mm * PT_PER_MM
+}
+
+/// Helveticaで描いた文字列の幅 (文字の大きさに対する比)
+///
+/// 中央揃えのために使います。カードに描く文字 (数字・英大文字・記号) の幅のみ持ちます。
+fn helvetica_width(text: &str) -> f32 {
+    text.chars()
+        .map(|c| match c {
+            ' ' | '.' => 278,
+            'I' => 278,
+            'B' | 'E' | 'S' => 667,
+            'F' => 611,
+            'N' | 'R' => 722,
+            'G' | 'O' => 778,
+            _ => 556,
+        })
+        .sum::<u32>() as f32
+        / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sheets_are_deterministic() {
+        let key = CardKey::new("secret");
+        let cards: Vec<PrintedCard> = (1..=7)
+            .map(|id| PrintedCard::generate(&key, 42, id))
+            .collect();
+        let pages = layout(&cards);
+        assert_eq!(pages.len(), 2);
+        // 2ページ目は1枚だけ
+        let borders = |shapes: &[Shape]| {
+            shapes
+                .iter()
+                .filter(|shape| matches!(shape, Shape::Rect { width, .. } if *width == SLOT_WIDTH - 10.0))
+                .count()
+        };
+        assert_eq!(borders(&pages[0]), CARDS_PER_PAGE);
+        assert_eq!(borders(&pages[1]), 1);
+
+        let svg = render_svg(&cards);
+        assert_eq!(svg, render_svg(&cards));
+        assert_eq!(svg.len(), 2);
+        assert!(svg[1].contains(">No. 7</text>"));
+        let first = &cards[0].numbers[0][0].unwrap().to_string();
+        assert!(svg[0].contains(&format!(">{}</text>", first)));
+
+        let pdf = render_pdf(&cards);
+        assert!(pdf.starts_with(b"%PDF-"));
+        assert_eq!(pdf, render_pdf(&cards));
+        assert!(String::from_utf8_lossy(&pdf).contains("/Count 2"));
+    }
+}
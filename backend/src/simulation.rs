@@ -0,0 +1,356 @@
+use crate::card::{self, BingoCard, CardGrid};
+use crate::domain::BingoGame;
+use crate::rng::{derive_seed, XorShift};
+use crate::session::WinPattern;
+use serde::Serialize;
+use std::fmt;
+
+/// 仮想カードの乱数のストリーム番号 (抽選の乱数と重ならないようにする)
+const DECK_STREAM: u32 = 0xdec4;
+
+/// シミュレーションで使う数字の最大値 (カードは75ボールのみ)
+pub const SIMULATION_MAX_NUMBER: usize = 75;
+
+/// シミュレーションした1ゲームの結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimulatedGame {
+    /// 最初に当たりの形が揃った数字の抽選順 (1から、誰も揃わなかった場合はNone)
+    pub first_winner: Option<usize>,
+    /// その数字で同時に揃ったカードの枚数
+    pub winners: usize,
+}
+
+/// シミュレーションの`index`番目のゲームのシード値を求める
+pub fn game_seed(seed: u32, index: usize) -> u32 {
+    derive_seed(seed, index as u32)
+}
+
+/// 仮想カードで1ゲームをシミュレーションする
+///
+/// 実際のゲームと同じ `BingoGame` で数字を抽選し、仮想カードのいずれかで当たりの形が揃うまで続けます。
+/// 同じシード値からは常に同じ結果になります。
+///
+/// # Arguments
+/// * `seed` - ゲームのシード値 (仮想カードの数字もここから求める)
+/// * `cards` - 仮想カードの枚数
+/// * `pattern` - 当たりの形
+pub fn simulate_game(seed: u32, cards: usize, pattern: WinPattern) -> SimulatedGame {
+    let mut deck_rng = XorShift::quiet(derive_seed(seed, DECK_STREAM));
+    let deck: Vec<CardGrid<Option<u8>>> = (0..cards)
+        .map(|_| BingoCard::generate_with(&mut deck_rng))
+        .collect();
+    let mut marks: Vec<CardGrid<bool>> = deck
+        .iter()
+        .map(|numbers| card::marks(numbers, &[]))
+        .collect();
+    let mut game = BingoGame::new(SIMULATION_MAX_NUMBER, Box::new(XorShift::quiet(seed)));
+
+    while let Ok(Some(number)) = game.draw() {
+        let mut winners = 0;
+        for (numbers, marks) in deck.iter().zip(marks.iter_mut()) {
+            let Some((row, column)) = position(numbers, number) else {
+                continue;
+            };
+            marks[row][column] = true;
+            if card::winning_cells(pattern, marks).is_some() {
+                winners += 1;
+            }
+        }
+        if winners > 0 {
+            return SimulatedGame {
+                first_winner: Some(game.history.len()),
+                winners,
+            };
+        }
+    }
+    SimulatedGame {
+        first_winner: None,
+        winners: 0,
+    }
+}
+
+/// モンテカルロシミュレーションの条件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SimulationSettings {
+    /// 参加者 (仮想カード) の数
+    pub players: usize,
+    /// シミュレーションするゲームの数
+    pub games: usize,
+    /// 当たりの形
+    pub pattern: WinPattern,
+    /// シード値
+    pub seed: u32,
+    /// 抽選間隔 (秒)
+    pub draw_interval_secs: u64,
+    /// 1ラウンドあたりの抽選以外の時間 (秒、当選の確認や景品の受け渡し)
+    pub overhead_secs: u64,
+    /// イベントの時間 (秒)
+    pub event_secs: u64,
+}
+
+/// 度数分布のビン
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct HistogramBin {
+    /// 値
+    pub value: u64,
+    /// 度数
+    pub count: usize,
+}
+
+/// 値の分布
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Distribution {
+    /// 平均
+    pub mean: f64,
+    /// 最小値
+    pub min: u64,
+    /// 中央値
+    pub median: u64,
+    /// 90パーセンタイル
+    pub p90: u64,
+    /// 最大値
+    pub max: u64,
+    /// 度数分布 (値の小さい順、度数が0の値は含まない)
+    pub histogram: Vec<HistogramBin>,
+}
+
+impl Distribution {
+    /// 標本から分布を求める (標本が空の場合はNone)
+    pub fn from_samples(samples: &[u64]) -> Option<Self> {
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+        let (&min, &max) = (sorted.first()?, sorted.last()?);
+        let percentile = |p: usize| sorted[((sorted.len() - 1) * p).div_ceil(100)];
+        let mut histogram: Vec<HistogramBin> = Vec::new();
+        for &value in &sorted {
+            match histogram.last_mut() {
+                Some(bin) if bin.value == value => bin.count += 1,
+                _ => histogram.push(HistogramBin { value, count: 1 }),
+            }
+        }
+        Some(Self {
+            mean: sorted.iter().sum::<u64>() as f64 / sorted.len() as f64,
+            min,
+            median: percentile(50),
+            p90: percentile(90),
+            max,
+            histogram,
+        })
+    }
+}
+
+/// モンテカルロシミュレーションの結果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SimulationReport {
+    /// シミュレーションの条件
+    pub settings: SimulationSettings,
+    /// 最初の当選者が出るまでの抽選回数
+    pub draws_to_first_winner: Distribution,
+    /// 最初の当選者と同時に揃った当選者の数 (最初の当選者を含む)
+    pub simultaneous_winners: Distribution,
+    /// 1ラウンドの長さ (秒、抽選回数×抽選間隔＋抽選以外の時間)
+    pub round_secs: Distribution,
+    /// イベントの時間内に終えられるラウンドの数
+    ///
+    /// シミュレーションしたゲームを順にイベントの時間へ詰め、終えられたラウンドを数えます。
+    pub rounds_per_event: Distribution,
+}
+
+/// 仮想カードでゲームを繰り返しシミュレーションし、ラウンドの長さの分布を求める
+///
+/// 各ゲームは `game_seed(settings.seed, i)` をシード値とするため、同じ条件からは常に同じ結果になります。
+///
+/// # Returns
+/// * `Option<SimulationReport>` - シミュレーションの結果 (参加者かゲームの数が0の場合はNone)
+pub fn run(settings: SimulationSettings) -> Option<SimulationReport> {
+    if settings.players == 0 {
+        return None;
+    }
+    let games: Vec<SimulatedGame> = (0..settings.games)
+        .map(|index| {
+            simulate_game(
+                game_seed(settings.seed, index),
+                settings.players,
+                settings.pattern,
+            )
+        })
+        .collect();
+    // 仮想カードが1枚以上あれば、全ての数字を抽選するまでに必ず当選者が出る
+    let draws: Vec<u64> = games
+        .iter()
+        .filter_map(|game| game.first_winner)
+        .map(|draws| draws as u64)
+        .collect();
+    let winners: Vec<u64> = games.iter().map(|game| game.winners as u64).collect();
+    let round_secs: Vec<u64> = draws
+        .iter()
+        .map(|draws| draws * settings.draw_interval_secs + settings.overhead_secs)
+        .collect();
+
+    let mut rounds_per_event = Vec::new();
+    let (mut elapsed, mut rounds) = (0, 0);
+    for &secs in &round_secs {
+        if elapsed + secs > settings.event_secs {
+            // 次のラウンドが入らないところでイベントを締める
+            if rounds > 0 {
+                rounds_per_event.push(rounds);
+            }
+            (elapsed, rounds) = (0, 0);
+            if secs > settings.event_secs {
+                rounds_per_event.push(0);
+                continue;
+            }
+        }
+        elapsed += secs;
+        rounds += 1;
+    }
+    if rounds_per_event.is_empty() {
+        // 全てのゲームがイベントの時間内に収まった場合は、その数を下限として返す
+        rounds_per_event.push(rounds);
+    }
+
+    Some(SimulationReport {
+        settings,
+        draws_to_first_winner: Distribution::from_samples(&draws)?,
+        simultaneous_winners: Distribution::from_samples(&winners)?,
+        round_secs: Distribution::from_samples(&round_secs)?,
+        rounds_per_event: Distribution::from_samples(&rounds_per_event)?,
+    })
+}
+
+impl fmt::Display for SimulationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let settings = &self.settings;
+        writeln!(f, "Simulation:")?;
+        writeln!(f, "  players          : {}", settings.players)?;
+        writeln!(f, "  games            : {}", settings.games)?;
+        writeln!(f, "  pattern          : {}", settings.pattern.name())?;
+        writeln!(f, "  seed             : {}", settings.seed)?;
+        writeln!(f, "  draw interval    : {}s", settings.draw_interval_secs)?;
+        writeln!(f, "  round overhead   : {}s", settings.overhead_secs)?;
+        writeln!(
+            f,
+            "  event length     : {}",
+            format_secs(settings.event_secs)
+        )?;
+        writeln!(
+            f,
+            "{:<22}{:>8}{:>8}{:>8}{:>8}{:>8}",
+            "", "mean", "min", "median", "p90", "max"
+        )?;
+        let rows = [
+            ("draws to 1st winner", &self.draws_to_first_winner, false),
+            ("simultaneous winners", &self.simultaneous_winners, false),
+            ("round length", &self.round_secs, true),
+            ("rounds per event", &self.rounds_per_event, false),
+        ];
+        for (label, distribution, secs) in rows {
+            let value = |value: u64| match secs {
+                true => format_secs(value),
+                false => value.to_string(),
+            };
+            let mean = match secs {
+                true => format_secs(distribution.mean.round() as u64),
+                false => format!("{:.1}", distribution.mean),
+            };
+            writeln!(
+                f,
+                "{:<22}{:>8}{:>8}{:>8}{:>8}{:>8}",
+                label,
+                mean,
+                value(distribution.min),
+                value(distribution.median),
+                value(distribution.p90),
+                value(distribution.max)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// 秒数を `分:秒` で表す
+fn format_secs(secs: u64) -> String {
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+/// カードのマスから数字の位置を探す
+fn position(numbers: &CardGrid<Option<u8>>, number: u8) -> Option<(usize, usize)> {
+    numbers.iter().enumerate().find_map(|(row, row_numbers)| {
+        row_numbers
+            .iter()
+            .position(|cell| *cell == Some(number))
+            .map(|column| (row, column))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simulated_games_are_reproducible() {
+        let game = simulate_game(42, 80, WinPattern::SingleLine);
+        assert_eq!(game, simulate_game(42, 80, WinPattern::SingleLine));
+        let first = game.first_winner.unwrap();
+        // 1列揃うには少なくとも4つの数字が必要
+        assert!((4..=SIMULATION_MAX_NUMBER).contains(&first));
+        assert!(game.winners >= 1);
+
+        // 全てのマスが揃うのは1列より後
+        let blackout = simulate_game(42, 80, WinPattern::Blackout);
+        assert!(blackout.first_winner.unwrap() >= first);
+        assert_eq!(
+            simulate_game(42, 0, WinPattern::SingleLine),
+            SimulatedGame {
+                first_winner: None,
+                winners: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_distribution() {
+        let distribution = Distribution::from_samples(&[3, 1, 2, 2, 10]).unwrap();
+        assert_eq!(distribution.mean, 3.6);
+        assert_eq!((distribution.min, distribution.median), (1, 2));
+        assert_eq!((distribution.p90, distribution.max), (10, 10));
+        assert_eq!(
+            distribution.histogram[1],
+            HistogramBin { value: 2, count: 2 }
+        );
+        assert_eq!(Distribution::from_samples(&[]), None);
+    }
+
+    #[test]
+    fn test_run_reports_round_lengths() {
+        let settings = SimulationSettings {
+            players: 80,
+            games: 40,
+            pattern: WinPattern::SingleLine,
+            seed: 7,
+            draw_interval_secs: 10,
+            overhead_secs: 60,
+            event_secs: 30 * 60,
+        };
+        let report = run(settings).unwrap();
+        assert_eq!(report, run(settings).unwrap());
+        let draws = &report.draws_to_first_winner;
+        assert_eq!(
+            report.round_secs.min,
+            draws.min * settings.draw_interval_secs + settings.overhead_secs
+        );
+        assert!(report.simultaneous_winners.min >= 1);
+        let games: usize = draws.histogram.iter().map(|bin| bin.count).sum();
+        assert_eq!(games, settings.games);
+        // 1ラウンドは30分より十分短いため、数ラウンドは入る
+        assert!(report.rounds_per_event.min >= 2);
+        assert!(report.to_string().contains("rounds per event"));
+        assert_eq!(
+            run(SimulationSettings {
+                players: 0,
+                ..settings
+            }),
+            None
+        );
+    }
+}
@@ -1,50 +1,173 @@
+use crate::auth::AdminAuth;
+use crate::autodraw::{AutoDraw, AutoDrawStopReason};
+use crate::card::{CardKey, CardRegistry};
+use crate::config::Config;
 use crate::domain::{AmidaGame, BingoGame};
+use crate::lifecycle::GamePhaseError;
+use crate::player::PlayerRegistry;
 use crate::rng::{MersenneTwister, XorShift};
-use std::fs;
-use std::sync::{Arc, Mutex};
-
-const BINGO_MAX_NUMBER: usize = 75;
+use crate::session::Session;
+use crate::stats::{SimulationCache, MAX_CONCURRENT_SIMULATIONS};
+use crate::winner::WinnerRegistry;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::sync::Semaphore;
 
 /// アプリケーション全体の状態を管理する構造体
 ///
-/// ビンゴゲームとあみだくじの状態をスレッドセーフに保持します。
+/// ビンゴゲーム、ビンゴカード、当選者、あみだくじ、参加者名簿の状態をスレッドセーフに保持します。
 /// Axumの状態共有機能を通じて、各ハンドラからアクセスされます。
+/// 複数の状態をロックする場合は、必ずセッション・ビンゴゲーム・ビンゴカード・当選者・あみだくじ・参加者名簿の順にロックしてください。
 #[derive(Clone)]
 pub struct AppState {
-    /// ビンゴゲームの状態（排他制御あり）
+    /// ビンゴゲームの状態（進行中のラウンド、排他制御あり）
     pub game: Arc<Mutex<BingoGame>>,
+    /// ビンゴのセッション（ラウンドの設定と記録、排他制御あり）
+    pub session: Arc<Mutex<Session>>,
+    /// 発行したビンゴカード（排他制御あり）
+    pub cards: Arc<Mutex<CardRegistry>>,
+    /// ビンゴの景品と当選者（排他制御あり）
+    pub winners: Arc<Mutex<WinnerRegistry>>,
     /// あみだくじの状態（排他制御あり）
     pub amida: Arc<Mutex<AmidaGame>>,
+    /// 参加者名簿（排他制御あり）
+    pub players: Arc<Mutex<PlayerRegistry>>,
+    /// 当選確率のシミュレーションの結果（排他制御あり）
+    pub simulations: Arc<Mutex<SimulationCache>>,
+    /// 同時に行える当選確率のシミュレーションの数
+    pub simulation_permits: Arc<Semaphore>,
+    /// ビンゴの自動抽選
+    pub auto_draw: AutoDraw,
+    /// 管理者の認証情報
+    pub admin: AdminAuth,
+    /// 印刷したカードのQRコードに署名する鍵
+    pub card_key: CardKey,
     /// 初期シード値（参照用）
     pub seed: u32,
+    /// あみだくじのシード値（全ての枠を公開するまで返さない）
+    amida_seed: u32,
 }
 
 impl AppState {
     /// 新しいアプリケーション状態を作成します
     ///
-    /// 指定されたシード値を使用して、ビンゴとあみだくじのゲーム状態を初期化します。
-    /// あみだくじの乱数生成器は、ビンゴの乱数生成器と状態が重ならないように
-    /// シード値をシフトして初期化されます。
-    pub fn new(seed: u32) -> Self {
-        let prize_count = fs::read_to_string("prize.txt")
-            .or_else(|_| fs::read_to_string("../prize.txt"))
-            .ok()
-            .and_then(|s| {
-                eprintln!("prize count loaded: [{}]", &s.trim().to_string());
-                Some(s.trim().parse().ok()?)
-            })
-            .unwrap_or(8);
-
+    /// 指定されたシード値と設定（数字の最大値・景品数）を使用して、
+    /// ビンゴとあみだくじのゲーム状態を初期化します。
+    /// ビンゴのシード値はレスポンスで公開されるため、あみだくじの乱数生成器は
+    /// ビンゴのシード値から求められないように、OSの乱数から生成したシード値で初期化されます。
+    /// カードのQRコードの鍵が設定されていない場合は、起動ごとにOSの乱数から鍵を生成します。
+    pub fn new(seed: u32, config: &Config) -> Self {
+        let admin = AdminAuth::new(config.admin_token.clone());
+        let card_key = config
+            .card_key_secret()
+            .map_or_else(CardKey::random, CardKey::new);
+        let amida_seed = random_seed();
         Self {
             game: Arc::new(Mutex::new(BingoGame::new(
-                BINGO_MAX_NUMBER,
+                config.bingo_max_number,
                 Box::new(XorShift::new(seed)),
             ))),
+            session: Arc::new(Mutex::new(Session::new(seed))),
+            cards: Arc::new(Mutex::new(CardRegistry::new())),
+            winners: Arc::new(Mutex::new(WinnerRegistry::new())),
             amida: Arc::new(Mutex::new(AmidaGame::new(
-                prize_count,
-                Box::new(MersenneTwister::new(seed)),
+                config.prize_count,
+                Box::new(MersenneTwister::new(amida_seed)),
             ))),
+            players: Arc::new(Mutex::new(PlayerRegistry::new())),
+            simulations: Arc::new(Mutex::new(SimulationCache::new())),
+            simulation_permits: Arc::new(Semaphore::new(MAX_CONCURRENT_SIMULATIONS)),
+            auto_draw: AutoDraw::new(config.auto_draw_interval),
+            admin,
+            card_key,
             seed,
+            amida_seed,
         }
     }
+
+    /// あみだくじのシード値
+    ///
+    /// シード値から未公開の割り当てを求められないように、
+    /// 割り当てのある枠を全て公開するまではNoneを返します。
+    pub fn revealed_amida_seed(&self, amida: &AmidaGame) -> Option<u32> {
+        (amida.assigned_count() > 0 && amida.is_fully_revealed()).then_some(self.amida_seed)
+    }
+
+    /// ビンゴゲームの状態をロックする
+    pub fn game(&self) -> MutexGuard<'_, BingoGame> {
+        lock(&self.game, "bingo")
+    }
+
+    /// ビンゴのセッションをロックする
+    pub fn session(&self) -> MutexGuard<'_, Session> {
+        lock(&self.session, "session")
+    }
+
+    /// 進行中のラウンドのシード値
+    ///
+    /// セッションをロックするため、ビンゴゲームをロックする前に呼び出してください。
+    pub fn round_seed(&self) -> u32 {
+        self.session().current().seed
+    }
+
+    /// ビンゴゲームをリセットする
+    ///
+    /// 自動抽選中の場合は停止します。
+    /// 進行中のラウンドの当選者の記録は取り消し、渡した景品を戻します。
+    pub fn reset_game(&self) -> Result<(), GamePhaseError> {
+        let mut session = self.session();
+        self.game().reset()?;
+        let number = session.current().number;
+        let revoked = self.winners().revoke_round(number);
+        session.remove_tie_breaks(number, &revoked);
+        drop(session);
+        self.auto_draw.stop(AutoDrawStopReason::Reset);
+        Ok(())
+    }
+
+    /// 発行したビンゴカードをロックする
+    pub fn cards(&self) -> MutexGuard<'_, CardRegistry> {
+        lock(&self.cards, "cards")
+    }
+
+    /// ビンゴの景品と当選者をロックする
+    pub fn winners(&self) -> MutexGuard<'_, WinnerRegistry> {
+        lock(&self.winners, "winners")
+    }
+
+    /// あみだくじの状態をロックする
+    pub fn amida(&self) -> MutexGuard<'_, AmidaGame> {
+        lock(&self.amida, "amida")
+    }
+
+    /// 参加者名簿をロックする
+    pub fn players(&self) -> MutexGuard<'_, PlayerRegistry> {
+        lock(&self.players, "players")
+    }
+
+    /// 当選確率のシミュレーションの結果をロックする
+    ///
+    /// 他の状態とは関係しないため、他の状態をロックせずに使ってください。
+    pub fn simulations(&self) -> MutexGuard<'_, SimulationCache> {
+        lock(&self.simulations, "simulations")
+    }
+}
+
+/// OSの乱数からシード値を生成する
+fn random_seed() -> u32 {
+    let mut bytes = [0u8; 4];
+    getrandom::getrandom(&mut bytes).expect("OS random number generator is unavailable");
+    u32::from_le_bytes(bytes)
+}
+
+/// 状態をロックする
+///
+/// 他のリクエストの処理中にパニックが発生してロックが汚染 (poison) されても、
+/// 以降の全てのリクエストが失敗し続けないように、汚染を解除して状態をそのまま使い続けます。
+/// イベントの進行を止めないことを優先し、警告を出力するだけにしています。
+pub(crate) fn lock<'a, T>(mutex: &'a Mutex<T>, name: &str) -> MutexGuard<'a, T> {
+    mutex.lock().unwrap_or_else(|poisoned| {
+        eprintln!("Warning: {} state lock was poisoned; recovering.", name);
+        mutex.clear_poison();
+        poisoned.into_inner()
+    })
 }
@@ -0,0 +1,507 @@
+use crate::rng::derive_seed;
+use crate::session::{Round, WinPattern};
+use crate::simulation::{self, SIMULATION_MAX_NUMBER};
+use crate::winner::Winner;
+use serde::Serialize;
+use std::collections::VecDeque;
+use utoipa::ToSchema;
+
+/// 当選確率のシミュレーションの既定の回数
+pub const DEFAULT_SIMULATIONS: usize = 1000;
+
+/// 当選確率のシミュレーションの最大回数
+pub const MAX_SIMULATIONS: usize = 10000;
+
+/// 当選確率のシミュレーションの仮想カードの最大枚数
+pub const MAX_SIMULATED_CARDS: usize = 1000;
+
+/// 1回の当選確率の計算で判定する仮想カードの最大枚数 (仮想カードの枚数×シミュレーションの回数)
+pub const MAX_SIMULATED_CARD_GAMES: usize = 100_000;
+
+/// 同時に行う当選確率のシミュレーションの最大数 (超えた場合は混み合っているエラーを返す)
+pub const MAX_CONCURRENT_SIMULATIONS: usize = 2;
+
+/// シミュレーションが混み合っている場合に、再試行まで待ってもらう秒数
+pub const SIMULATION_RETRY_SECONDS: u64 = 1;
+
+/// 当選確率のシミュレーションの結果を保持する件数
+const CACHED_SIMULATIONS: usize = 16;
+
+/// カイ二乗検定の有意水準
+pub const SIGNIFICANCE_LEVEL: f64 = 0.05;
+
+/// 抽選順の検定で、抽選の順番を分ける区間の数 (序盤から終盤まで)
+pub const POSITION_BUCKETS: usize = 5;
+
+/// 当選確率のシミュレーションの乱数のストリーム番号
+const SIMULATION_STREAM: u32 = 0x57a7;
+
+/// 数字ごとの抽選の統計
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct NumberStats {
+    /// 数字
+    pub number: u8,
+    /// 抽選された回数
+    pub count: usize,
+    /// 抽選された順番の平均 (1から、一度も抽選されていない場合はnull)
+    pub mean_position: Option<f64>,
+    /// 抽選された順番 (1から、ラウンド順)
+    pub positions: Vec<usize>,
+}
+
+/// 最初の当選者が出るまでの抽選回数の統計
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct FirstBingoStats {
+    /// 当選者がいたラウンドの数
+    pub rounds: usize,
+    /// 最初の当選者が出るまでの抽選回数の平均 (当選者がいたラウンドがない場合はnull)
+    pub mean_draws: Option<f64>,
+    /// 最初の当選者が出るまでの抽選回数の最小値
+    pub min_draws: Option<usize>,
+    /// 最初の当選者が出るまでの抽選回数の最大値
+    pub max_draws: Option<usize>,
+}
+
+/// カイ二乗検定の結果
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct ChiSquareTest {
+    /// カイ二乗統計量
+    pub statistic: f64,
+    /// 自由度
+    pub degrees_of_freedom: usize,
+    /// p値
+    pub p_value: f64,
+    /// 有意水準 (5%) で一様でないとは言えないかどうか
+    pub uniform: bool,
+}
+
+/// シミュレーションで求めた当選確率
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct WinnerProbability {
+    /// 仮想カードの枚数
+    pub cards: usize,
+    /// 当たりの形
+    pub pattern: WinPattern,
+    /// シミュレーションの回数
+    pub simulations: usize,
+    /// シミュレーションの乱数のシード値
+    pub seed: u32,
+    /// N番目の抽選までに当選者が出る確率 (先頭が1番目の抽選)
+    pub by_draw: Vec<f64>,
+}
+
+/// 当選確率のシミュレーションの結果のキャッシュ
+///
+/// 同じ条件 (シード値・仮想カードの枚数・当たりの形・回数) のシミュレーションは常に同じ結果になるため、
+/// 認証不要の統計APIを繰り返し呼ばれても計算し直さないように、最近の結果を保持する。
+#[derive(Debug, Default)]
+pub struct SimulationCache {
+    /// (セッションのシード値, 結果) のリスト (古い順)
+    entries: VecDeque<(u32, WinnerProbability)>,
+}
+
+impl SimulationCache {
+    /// 空のキャッシュを作成する
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 同じ条件のシミュレーションの結果を探す
+    pub fn get(
+        &self,
+        seed: u32,
+        cards: usize,
+        pattern: WinPattern,
+        simulations: usize,
+    ) -> Option<WinnerProbability> {
+        self.entries
+            .iter()
+            .find(|(entry_seed, probability)| {
+                *entry_seed == seed
+                    && probability.cards == cards
+                    && probability.pattern == pattern
+                    && probability.simulations == simulations
+            })
+            .map(|(_, probability)| probability.clone())
+    }
+
+    /// シミュレーションの結果を加える (保持する件数を超えた場合は最も古い結果を除く)
+    pub fn insert(&mut self, seed: u32, probability: WinnerProbability) {
+        if self.entries.len() >= CACHED_SIMULATIONS {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((seed, probability));
+    }
+}
+
+/// 終了したラウンドの抽選の統計
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct DrawStatistics {
+    /// 集計したラウンドの数
+    pub rounds: usize,
+    /// 集計した抽選の回数
+    pub draws: usize,
+    /// 数字の最大値
+    pub max_number: usize,
+    /// 数字ごとの抽選の統計 (数字順)
+    pub numbers: Vec<NumberStats>,
+    /// 最初の当選者が出るまでの抽選回数の統計
+    pub first_bingo: FirstBingoStats,
+    /// 数字ごとの抽選された順番に偏りがないかどうかのカイ二乗検定 (検定できる抽選がない場合はnull)
+    pub uniformity: Option<ChiSquareTest>,
+    /// 進行中のラウンドの当選確率 (75ボールのみ)
+    pub winner_probability: Option<WinnerProbability>,
+}
+
+impl DrawStatistics {
+    /// 終了したラウンドの抽選履歴を集計する
+    ///
+    /// # Arguments
+    /// * `rounds` - 終了したラウンドの記録
+    /// * `winners` - 当選者 (最初の当選者が出るまでの抽選回数に使う)
+    /// * `max_number` - 数字の最大値
+    pub fn collect(rounds: &[Round], winners: &[Winner], max_number: usize) -> Self {
+        let mut numbers: Vec<NumberStats> = (1..=max_number as u8)
+            .map(|number| NumberStats {
+                number,
+                count: 0,
+                mean_position: None,
+                positions: Vec::new(),
+            })
+            .collect();
+        let mut draws = 0;
+        for round in rounds {
+            for (index, &number) in round.history.iter().enumerate() {
+                let Some(stats) = numbers.get_mut((number as usize).wrapping_sub(1)) else {
+                    continue;
+                };
+                stats.count += 1;
+                stats.positions.push(index + 1);
+                draws += 1;
+            }
+        }
+        for stats in &mut numbers {
+            stats.mean_position = mean(&stats.positions);
+        }
+
+        let first_draws: Vec<usize> = rounds
+            .iter()
+            .filter_map(|round| {
+                winners
+                    .iter()
+                    .filter(|winner| winner.round == round.settings.number)
+                    .map(|winner| winner.draw_index)
+                    .min()
+            })
+            .collect();
+        let table = position_table(
+            rounds.iter().map(|round| round.history.as_slice()),
+            max_number,
+        );
+        Self {
+            rounds: rounds.len(),
+            draws,
+            max_number,
+            numbers,
+            first_bingo: FirstBingoStats {
+                rounds: first_draws.len(),
+                mean_draws: mean(&first_draws),
+                min_draws: first_draws.iter().copied().min(),
+                max_draws: first_draws.iter().copied().max(),
+            },
+            uniformity: chi_square_independence(&table),
+            winner_probability: None,
+        }
+    }
+}
+
+/// 数字ごとに、抽選の順番の各区間で抽選された回数を数える
+///
+/// 抽選の順番は数字の最大値を `POSITION_BUCKETS` 個に等分した区間に分けるため、
+/// 途中で終わったラウンドの抽選も同じ区間に数えられます。
+///
+/// # Arguments
+/// * `histories` - ラウンドごとの抽選履歴
+/// * `max_number` - 数字の最大値
+///
+/// # Returns
+/// * `Vec<Vec<usize>>` - `[数字 - 1][区間]` の抽選回数
+pub fn position_table<'a>(
+    histories: impl IntoIterator<Item = &'a [u8]>,
+    max_number: usize,
+) -> Vec<Vec<usize>> {
+    let mut table = vec![vec![0; POSITION_BUCKETS]; max_number];
+    for history in histories {
+        for (index, &number) in history.iter().enumerate() {
+            let Some(row) = table.get_mut((number as usize).wrapping_sub(1)) else {
+                continue;
+            };
+            let bucket = (index * POSITION_BUCKETS / max_number).min(POSITION_BUCKETS - 1);
+            row[bucket] += 1;
+        }
+    }
+    table
+}
+
+/// 抽選された順番が数字によって偏っていないかを、分割表のカイ二乗独立性検定で調べる
+///
+/// 公平な抽選では、どの数字も序盤・中盤・終盤に同じ割合で抽選されると期待できます。
+/// 全て抽選したラウンドでは各数字の抽選回数は必ず同じになるため、回数ではなく順番の区間を調べます。
+/// 1ラウンドだけではどの並びでも統計量が同じになるため、偏りを調べるには複数のラウンドが必要です。
+/// 期待度数が小さい (ラウンドが少ない) 間は近似が粗くなります。
+///
+/// # Arguments
+/// * `table` - `[数字][区間]` の抽選回数 ([`position_table`])
+///
+/// # Returns
+/// * `Option<ChiSquareTest>` - 検定の結果 (抽選された数字か抽選があった区間が2つ未満の場合はNone)
+pub fn chi_square_independence(table: &[Vec<usize>]) -> Option<ChiSquareTest> {
+    let rows: Vec<&Vec<usize>> = table
+        .iter()
+        .filter(|row| row.iter().any(|&count| count > 0))
+        .collect();
+    let columns = rows.first().map_or(0, |row| row.len());
+    let column_totals: Vec<usize> = (0..columns)
+        .map(|column| rows.iter().map(|row| row[column]).sum())
+        .collect();
+    let used_columns = column_totals.iter().filter(|&&total| total > 0).count();
+    if rows.len() < 2 || used_columns < 2 {
+        return None;
+    }
+    let total: usize = column_totals.iter().sum();
+    let mut statistic = 0.0;
+    for row in &rows {
+        let row_total: usize = row.iter().sum();
+        for (&count, &column_total) in row.iter().zip(&column_totals) {
+            if column_total == 0 {
+                continue;
+            }
+            let expected = row_total as f64 * column_total as f64 / total as f64;
+            statistic += (count as f64 - expected).powi(2) / expected;
+        }
+    }
+    let degrees_of_freedom = (rows.len() - 1) * (used_columns - 1);
+    let p_value = chi_square_p_value(statistic, degrees_of_freedom);
+    Some(ChiSquareTest {
+        statistic,
+        degrees_of_freedom,
+        p_value,
+        uniform: p_value >= SIGNIFICANCE_LEVEL,
+    })
+}
+
+/// カイ二乗分布の上側確率 (p値) を求める
+pub fn chi_square_p_value(statistic: f64, degrees_of_freedom: usize) -> f64 {
+    upper_regularized_gamma(degrees_of_freedom as f64 / 2.0, statistic / 2.0)
+}
+
+/// 仮想カードで抽選をシミュレーションし、N番目の抽選までに当選者が出る確率を求める
+///
+/// # Arguments
+/// * `seed` - セッションのシード値
+/// * `cards` - 仮想カードの枚数
+/// * `pattern` - 当たりの形
+/// * `simulations` - シミュレーションの回数
+pub fn simulate_winner_probability(
+    seed: u32,
+    cards: usize,
+    pattern: WinPattern,
+    simulations: usize,
+) -> WinnerProbability {
+    let seed = derive_seed(seed, SIMULATION_STREAM);
+    let mut first_wins = vec![0usize; SIMULATION_MAX_NUMBER];
+    if cards > 0 {
+        for index in 0..simulations {
+            let game =
+                simulation::simulate_game(simulation::game_seed(seed, index), cards, pattern);
+            if let Some(draw) = game.first_winner {
+                first_wins[draw - 1] += 1;
+            }
+        }
+    }
+    let by_draw = first_wins
+        .iter()
+        .scan(0, |total, &wins| {
+            *total += wins;
+            Some(*total as f64 / simulations.max(1) as f64)
+        })
+        .collect();
+    WinnerProbability {
+        cards,
+        pattern,
+        simulations,
+        seed,
+        by_draw,
+    }
+}
+
+/// 平均を求める (空の場合はNone)
+fn mean(values: &[usize]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    Some(values.iter().sum::<usize>() as f64 / values.len() as f64)
+}
+
+/// 正則化された上側不完全ガンマ関数 Q(a, x)
+///
+/// xが小さい場合は級数展開、大きい場合は連分数展開で求めます。
+fn upper_regularized_gamma(a: f64, x: f64) -> f64 {
+    const EPSILON: f64 = 1e-12;
+    const MAX_ITERATIONS: usize = 500;
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let log_prefix = a * x.ln() - x - ln_gamma(a);
+    if x < a + 1.0 {
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        let mut denominator = a;
+        for _ in 0..MAX_ITERATIONS {
+            denominator += 1.0;
+            term *= x / denominator;
+            sum += term;
+            if term.abs() < sum.abs() * EPSILON {
+                break;
+            }
+        }
+        (1.0 - sum * log_prefix.exp()).clamp(0.0, 1.0)
+    } else {
+        // Lentz法による連分数展開
+        let tiny = f64::MIN_POSITIVE / EPSILON;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..=MAX_ITERATIONS {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < EPSILON {
+                break;
+            }
+        }
+        (log_prefix.exp() * h).clamp(0.0, 1.0)
+    }
+}
+
+/// ガンマ関数の自然対数 (Lanczos近似)
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.180_091_729_471_46,
+        -86.505_320_329_416_77,
+        24.014_098_240_830_91,
+        -1.231_739_572_450_155,
+        0.120_865_097_386_617_9e-2,
+        -0.539_523_938_495_3e-5,
+    ];
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let mut series = 1.000_000_000_190_015;
+    let mut y = x;
+    for coefficient in COEFFICIENTS {
+        y += 1.0;
+        series += coefficient / y;
+    }
+    -tmp + (2.506_628_274_631_000_5 * series / x).ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::{IRng, XorShift};
+
+    #[test]
+    fn test_chi_square_p_value() {
+        // 自由度2ではp値はexp(-x/2)
+        assert!((chi_square_p_value(2.0, 2) - (-1.0f64).exp()).abs() < 1e-9);
+        // 自由度1の5%点は3.841
+        assert!((chi_square_p_value(3.841_458_820_694_124, 1) - 0.05).abs() < 1e-6);
+        // 自由度74の5%点は95.081
+        assert!((chi_square_p_value(95.081_445, 74) - 0.05).abs() < 1e-4);
+        assert_eq!(chi_square_p_value(0.0, 3), 1.0);
+    }
+
+    #[test]
+    fn test_draw_order_independence() {
+        let rounds = 20;
+        let shuffled: Vec<Vec<u8>> = (0..rounds)
+            .map(|round| {
+                let mut numbers: Vec<u8> = (1..=75).collect();
+                XorShift::quiet(derive_seed(7, round)).shuffle(&mut numbers);
+                numbers
+            })
+            .collect();
+        let fair = chi_square_independence(&position_table(shuffled.iter().map(Vec::as_slice), 75))
+            .unwrap();
+        assert_eq!(fair.degrees_of_freedom, 74 * (POSITION_BUCKETS - 1));
+        assert!(fair.uniform, "{:?}", fair);
+
+        // 全て抽選したラウンドでも、小さい数字ほど先に出る抽選は偏りとして検出する
+        let biased: Vec<Vec<u8>> = shuffled
+            .iter()
+            .map(|numbers| {
+                let mut numbers = numbers.clone();
+                for half in numbers.chunks_mut(38) {
+                    half.sort_unstable();
+                }
+                numbers
+            })
+            .collect();
+        let table = position_table(biased.iter().map(Vec::as_slice), 75);
+        assert!(table
+            .iter()
+            .all(|row| row.iter().sum::<usize>() == rounds as usize));
+        let biased = chi_square_independence(&table).unwrap();
+        assert!(biased.statistic > fair.statistic);
+        assert!(!biased.uniform, "{:?}", biased);
+
+        // 1つの区間の抽選だけでは検定できない
+        let early: Vec<Vec<u8>> = shuffled
+            .iter()
+            .map(|numbers| numbers[..10].to_vec())
+            .collect();
+        assert_eq!(
+            chi_square_independence(&position_table(early.iter().map(Vec::as_slice), 75)),
+            None
+        );
+        assert_eq!(chi_square_independence(&position_table([], 75)), None);
+    }
+
+    #[test]
+    fn test_winner_probability_is_cumulative() {
+        let probability = simulate_winner_probability(1, 30, WinPattern::SingleLine, 50);
+        assert_eq!(probability.by_draw.len(), SIMULATION_MAX_NUMBER);
+        assert_eq!(probability.by_draw[2], 0.0);
+        assert!(probability
+            .by_draw
+            .windows(2)
+            .all(|pair| pair[0] <= pair[1]));
+        assert_eq!(*probability.by_draw.last().unwrap(), 1.0);
+        assert_eq!(
+            probability,
+            simulate_winner_probability(1, 30, WinPattern::SingleLine, 50)
+        );
+        let none = simulate_winner_probability(1, 0, WinPattern::SingleLine, 50);
+        assert!(none.by_draw.iter().all(|&p| p == 0.0));
+
+        let mut cache = SimulationCache::new();
+        cache.insert(1, probability.clone());
+        assert_eq!(
+            cache.get(1, 30, WinPattern::SingleLine, 50),
+            Some(probability)
+        );
+        assert_eq!(cache.get(2, 30, WinPattern::SingleLine, 50), None);
+        assert_eq!(cache.get(1, 30, WinPattern::Cross, 50), None);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::player::PlayerId;
+use crate::rng::{derive_seed, IRng, XorShift};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// くじ引きの乱数のストリーム番号 (ラウンドの抽選やカードの乱数と重ならないようにする)
+const TIE_BREAK_STREAM: u32 = 0x71eb;
+
+/// `highest_number` で引く数字の最大値 (当選者がこれより多い場合は当選者の数まで広げる)
+const TIE_BREAK_NUMBERS: usize = 100;
+
+/// 同じ数字で揃った当選者の順位の決め方
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TieBreakMethod {
+    /// 当選者の順番をランダムに並べる
+    #[default]
+    RandomOrder,
+    /// 当選者ごとに重ならない数字を1つずつ引き、大きい順に並べる
+    HighestNumber,
+}
+
+/// くじ引きで当選者が引いた数字
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct TieBreakDraw {
+    /// 参加者ID
+    pub player: PlayerId,
+    /// 引いた数字
+    pub number: u32,
+}
+
+/// 同じ数字で揃った当選者のくじ引きの記録
+///
+/// 乱数のシード値はラウンドのシード値と揃った数字の抽選順から求めるため、
+/// 同じラウンドの同じ同点は何度やり直しても同じ結果になります。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct TieBreak {
+    /// ラウンド番号
+    pub round: u32,
+    /// 当選者の当たりの形が揃った数字の抽選順
+    pub draw_index: usize,
+    /// 順位の決め方
+    pub method: TieBreakMethod,
+    /// くじ引きに使った乱数のシード値
+    pub seed: u32,
+    /// 乱数生成アルゴリズム
+    pub algorithm: String,
+    /// くじ引きに参加した当選者 (参加者ID順)
+    pub entrants: Vec<PlayerId>,
+    /// 当選者が引いた数字 (`highest_number` のみ、くじ引きに参加した当選者の順)
+    pub draws: Vec<TieBreakDraw>,
+    /// 決まった順位 (先頭の当選者が分け合っていた景品を受け取る)
+    pub order: Vec<PlayerId>,
+    /// くじ引きをした日時
+    #[schema(value_type = String, format = DateTime)]
+    pub at: DateTime<Local>,
+}
+
+impl TieBreak {
+    /// ラウンドのシード値から求めた乱数でくじ引きをする
+    ///
+    /// # Arguments
+    /// * `round` - ラウンド番号
+    /// * `round_seed` - ラウンドのシード値
+    /// * `draw_index` - 当選者の当たりの形が揃った数字の抽選順
+    /// * `method` - 順位の決め方
+    /// * `entrants` - 同じ数字で揃った当選者
+    pub fn run(
+        round: u32,
+        round_seed: u32,
+        draw_index: usize,
+        method: TieBreakMethod,
+        entrants: &[PlayerId],
+    ) -> Self {
+        let seed = tie_break_seed(round_seed, draw_index);
+        let mut rng = XorShift::new(seed);
+        let mut entrants = entrants.to_vec();
+        entrants.sort_unstable();
+        let (draws, order) = break_tie(&mut rng, method, &entrants);
+        Self {
+            round,
+            draw_index,
+            method,
+            seed,
+            algorithm: rng.name().to_string(),
+            entrants,
+            draws,
+            order,
+            at: Local::now(),
+        }
+    }
+}
+
+/// くじ引きの乱数のシード値を求める
+pub fn tie_break_seed(round_seed: u32, draw_index: usize) -> u32 {
+    derive_seed(derive_seed(round_seed, TIE_BREAK_STREAM), draw_index as u32)
+}
+
+/// 当選者の順位を決める
+///
+/// # Arguments
+/// * `rng` - 乱数生成器
+/// * `method` - 順位の決め方
+/// * `entrants` - くじ引きに参加する当選者
+///
+/// # Returns
+/// * `(Vec<TieBreakDraw>, Vec<PlayerId>)` - 当選者が引いた数字と、決まった順位
+pub fn break_tie(
+    rng: &mut dyn IRng,
+    method: TieBreakMethod,
+    entrants: &[PlayerId],
+) -> (Vec<TieBreakDraw>, Vec<PlayerId>) {
+    match method {
+        TieBreakMethod::RandomOrder => {
+            let mut order = entrants.to_vec();
+            shuffle(rng, &mut order);
+            (Vec::new(), order)
+        }
+        TieBreakMethod::HighestNumber => {
+            let mut numbers: Vec<u32> =
+                (1..=TIE_BREAK_NUMBERS.max(entrants.len()) as u32).collect();
+            shuffle(rng, &mut numbers);
+            let draws: Vec<TieBreakDraw> = entrants
+                .iter()
+                .zip(numbers)
+                .map(|(&player, number)| TieBreakDraw { player, number })
+                .collect();
+            let mut ranked = draws.clone();
+            ranked.sort_by_key(|draw| std::cmp::Reverse(draw.number));
+            let order = ranked.into_iter().map(|draw| draw.player).collect();
+            (draws, order)
+        }
+    }
+}
+
+/// Fisher-Yates法で並べ替える
+fn shuffle<T>(rng: &mut dyn IRng, items: &mut [T]) {
+    for i in (1..items.len()).rev() {
+        let j = (rng.next() as usize) % (i + 1);
+        items.swap(i, j);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tie_breaks_are_reproducible() {
+        let first = TieBreak::run(1, 42, 12, TieBreakMethod::RandomOrder, &[3, 1, 2]);
+        let again = TieBreak::run(1, 42, 12, TieBreakMethod::RandomOrder, &[2, 3, 1]);
+        assert_eq!(first.entrants, [1, 2, 3]);
+        assert_eq!(first.order, again.order);
+        assert_eq!(first.seed, tie_break_seed(42, 12));
+        assert!(first.draws.is_empty());
+        let mut order = first.order.clone();
+        order.sort_unstable();
+        assert_eq!(order, first.entrants);
+
+        let highest = TieBreak::run(1, 42, 12, TieBreakMethod::HighestNumber, &[1, 2, 3]);
+        assert_eq!(highest.draws.len(), 3);
+        let numbers: Vec<u32> = highest
+            .order
+            .iter()
+            .map(|player| {
+                highest
+                    .draws
+                    .iter()
+                    .find(|d| d.player == *player)
+                    .unwrap()
+                    .number
+            })
+            .collect();
+        // 大きい順に並び、数字は重ならない
+        assert!(numbers.windows(2).all(|pair| pair[0] > pair[1]));
+        assert_eq!(
+            highest.order,
+            TieBreak::run(1, 42, 12, TieBreakMethod::HighestNumber, &[1, 2, 3]).order
+        );
+        assert_ne!(tie_break_seed(42, 12), tie_break_seed(42, 13));
+    }
+}
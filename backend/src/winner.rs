@@ -0,0 +1,412 @@
+use crate::card::CardId;
+use crate::domain::BingoClaim;
+use crate::player::PlayerId;
+use crate::session::WinPattern;
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::fmt;
+use utoipa::ToSchema;
+
+/// ビンゴの景品ID (登録順に1から振られる)
+pub type PrizeId = u32;
+
+/// ビンゴの景品
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct BingoPrize {
+    /// 景品ID
+    pub id: PrizeId,
+    /// 景品名
+    pub name: String,
+}
+
+/// ビンゴの当選者
+///
+/// 同じラウンドで同じ数字によって揃った当選者は、1つの景品を分け合います。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct Winner {
+    /// 当選した参加者のID
+    pub player: PlayerId,
+    /// 当選したラウンド番号
+    pub round: u32,
+    /// ラウンドの当たりの形
+    pub pattern: WinPattern,
+    /// 当たりの形が揃った数字の抽選順 (1から)
+    pub draw_index: usize,
+    /// 当たりの形が揃った数字
+    pub number: Option<u8>,
+    /// 申告に使ったビンゴカード (司会者が記録した申告はNone)
+    pub card: Option<CardId>,
+    /// 受け取る景品 (景品が残っていなかった場合はNone)
+    pub prize: Option<BingoPrize>,
+    /// 景品を分け合う人数 (同じ数字で揃った当選者の数)
+    pub share: usize,
+    /// 当選を記録した日時
+    #[schema(value_type = String, format = DateTime)]
+    pub at: DateTime<Local>,
+}
+
+/// 当選者の記録に失敗した理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WinnerError {
+    /// すでに当選している参加者 (景品は1人1つまで)
+    AlreadyWon(PlayerId),
+    /// ラウンドに参加者の申告がない
+    ClaimNotFound(PlayerId),
+    /// 景品を分け合っている当選者がいない
+    NoTie,
+}
+
+impl fmt::Display for WinnerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AlreadyWon(id) => write!(f, "player {} has already won a prize", id),
+            Self::ClaimNotFound(id) => write!(f, "player {} has no claim in the round", id),
+            Self::NoTie => write!(f, "there are no winners sharing a prize in the round"),
+        }
+    }
+}
+
+/// ビンゴの景品と当選者の一覧
+///
+/// 景品は登録順に、当たりの形が揃った順で当選者に渡します。
+/// 同じラウンドで同じ数字によって揃った当選者には同じ景品を割り当て、分け合う人数を記録します。
+#[derive(Debug, Clone)]
+pub struct WinnerRegistry {
+    /// まだ渡していない景品 (渡す順)
+    prizes: Vec<BingoPrize>,
+    /// 当選者 (記録順)
+    winners: Vec<Winner>,
+    /// 次に登録する景品のID
+    next_prize_id: PrizeId,
+}
+
+impl Default for WinnerRegistry {
+    fn default() -> Self {
+        Self {
+            prizes: Vec::new(),
+            winners: Vec::new(),
+            next_prize_id: 1,
+        }
+    }
+}
+
+impl WinnerRegistry {
+    /// 空の一覧を作成する
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// まだ渡していない景品 (渡す順)
+    pub fn remaining(&self) -> &[BingoPrize] {
+        &self.prizes
+    }
+
+    /// 当選者 (記録順)
+    pub fn winners(&self) -> &[Winner] {
+        &self.winners
+    }
+
+    /// ラウンドの当選者の参加者ID (記録順)
+    pub fn round_winners(&self, round: u32) -> Vec<PlayerId> {
+        self.winners
+            .iter()
+            .filter(|winner| winner.round == round)
+            .map(|winner| winner.player)
+            .collect()
+    }
+
+    /// 参加者がすでに当選しているか
+    pub fn has_won(&self, player: PlayerId) -> bool {
+        self.winners.iter().any(|winner| winner.player == player)
+    }
+
+    /// まだ渡していない景品を置き換える
+    ///
+    /// 渡した景品はそのまま当選者の記録に残ります。
+    pub fn set_prizes(&mut self, names: Vec<String>) {
+        self.prizes = names
+            .into_iter()
+            .map(|name| {
+                let id = self.next_prize_id;
+                self.next_prize_id += 1;
+                BingoPrize { id, name }
+            })
+            .collect();
+    }
+
+    /// 申告から当選者を記録する
+    ///
+    /// 同じラウンドで同じ数字によって揃った当選者がいる場合は、その景品を分け合います。
+    /// いない場合は次の景品を渡します。
+    ///
+    /// # Arguments
+    /// * `round` - ラウンド番号
+    /// * `pattern` - ラウンドの当たりの形
+    /// * `claim` - 当たりの形が揃っていることを確認した申告
+    ///
+    /// # Returns
+    /// * `Result<&Winner, WinnerError>` - 記録した当選者 (すでに当選している参加者はエラー)
+    pub fn record(
+        &mut self,
+        round: u32,
+        pattern: WinPattern,
+        claim: &BingoClaim,
+    ) -> Result<&Winner, WinnerError> {
+        if self.has_won(claim.player) {
+            return Err(WinnerError::AlreadyWon(claim.player));
+        }
+
+        let same_draw =
+            |winner: &Winner| winner.round == round && winner.draw_index == claim.draw_index;
+        let prize = match self.winners.iter().find(|winner| same_draw(winner)) {
+            Some(winner) => winner.prize.clone(),
+            None if self.prizes.is_empty() => None,
+            None => Some(self.prizes.remove(0)),
+        };
+        let share = self
+            .winners
+            .iter()
+            .filter(|winner| same_draw(winner))
+            .count()
+            + 1;
+        for winner in self.winners.iter_mut().filter(|winner| same_draw(winner)) {
+            winner.share = share;
+        }
+
+        self.winners.push(Winner {
+            player: claim.player,
+            round,
+            pattern,
+            draw_index: claim.draw_index,
+            number: claim.number,
+            card: claim.card,
+            prize,
+            share,
+            at: Local::now(),
+        });
+        Ok(self.winners.last().expect("winner was just pushed"))
+    }
+
+    /// ラウンドで景品を分け合っている当選者を探す
+    ///
+    /// # Arguments
+    /// * `round` - ラウンド番号
+    /// * `draw_index` - 当たりの形が揃った数字の抽選順 (Noneの場合は最後に揃ったもの)
+    ///
+    /// # Returns
+    /// * `Result<(usize, Vec<PlayerId>), WinnerError>` - 抽選順と、景品を分け合っている当選者 (参加者ID順)
+    pub fn tie(
+        &self,
+        round: u32,
+        draw_index: Option<usize>,
+    ) -> Result<(usize, Vec<PlayerId>), WinnerError> {
+        let draw_index = self
+            .winners
+            .iter()
+            .filter(|winner| winner.round == round && winner.share > 1)
+            .map(|winner| winner.draw_index)
+            .filter(|index| draw_index.is_none_or(|draw_index| draw_index == *index))
+            .max()
+            .ok_or(WinnerError::NoTie)?;
+        let mut players: Vec<PlayerId> = self
+            .winners
+            .iter()
+            .filter(|winner| winner.round == round && winner.draw_index == draw_index)
+            .map(|winner| winner.player)
+            .collect();
+        players.sort_unstable();
+        Ok((draw_index, players))
+    }
+
+    /// くじ引きで決まった順位で景品を分け直す
+    ///
+    /// 1位の当選者が分け合っていた景品を受け取り、2位以降には順にまだ渡していない景品を渡します。
+    ///
+    /// # Arguments
+    /// * `round` - ラウンド番号
+    /// * `draw_index` - 当たりの形が揃った数字の抽選順
+    /// * `order` - くじ引きで決まった順位
+    pub fn apply_tie_break(&mut self, round: u32, draw_index: usize, order: &[PlayerId]) {
+        let mut shared = None;
+        for (rank, player) in order.iter().enumerate() {
+            let Some(position) = self.winners.iter().position(|winner| {
+                winner.round == round && winner.draw_index == draw_index && winner.player == *player
+            }) else {
+                continue;
+            };
+            let prize = if rank == 0 {
+                shared = self.winners[position].prize.clone();
+                shared.clone()
+            } else if shared.is_none() || self.prizes.is_empty() {
+                None
+            } else {
+                Some(self.prizes.remove(0))
+            };
+            let winner = &mut self.winners[position];
+            winner.prize = prize;
+            winner.share = 1;
+        }
+    }
+
+    /// 当選者の記録を1人取り消す
+    ///
+    /// 景品を分け合っていた当選者が残る場合は、残った当選者の分け合う人数を減らします。
+    /// 残らない場合は、渡した景品をまだ渡していない景品の先頭に戻します。
+    ///
+    /// # Returns
+    /// * `Option<Winner>` - 取り消した当選者 (ラウンドの当選者でない場合はNone)
+    pub fn revoke(&mut self, round: u32, player: PlayerId) -> Option<Winner> {
+        let position = self
+            .winners
+            .iter()
+            .position(|winner| winner.round == round && winner.player == player)?;
+        let revoked = self.winners.remove(position);
+        let mut shared = false;
+        for winner in self
+            .winners
+            .iter_mut()
+            .filter(|winner| winner.round == round && winner.draw_index == revoked.draw_index)
+        {
+            winner.share = winner.share.saturating_sub(1).max(1);
+            shared |= winner.prize == revoked.prize;
+        }
+        if let Some(prize) = revoked.prize.clone().filter(|_| !shared) {
+            self.prizes.insert(0, prize);
+        }
+        Some(revoked)
+    }
+
+    /// ラウンドの当選者の記録を取り消す
+    ///
+    /// ゲームをリセットした場合に呼び出します。当選者に渡した景品は、まだ渡していない景品の先頭に戻します。
+    ///
+    /// # Returns
+    /// * `Vec<PlayerId>` - 記録を取り消した参加者
+    pub fn revoke_round(&mut self, round: u32) -> Vec<PlayerId> {
+        let (revoked, kept): (Vec<_>, Vec<_>) = self
+            .winners
+            .drain(..)
+            .partition(|winner| winner.round == round);
+        self.winners = kept;
+
+        let mut returned: Vec<BingoPrize> = Vec::new();
+        for prize in revoked.iter().filter_map(|winner| winner.prize.clone()) {
+            if !returned.contains(&prize) {
+                returned.push(prize);
+            }
+        }
+        returned.sort_by_key(|prize| prize.id);
+        self.prizes.splice(0..0, returned);
+        revoked.into_iter().map(|winner| winner.player).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claim(player: PlayerId, draw_count: usize) -> BingoClaim {
+        BingoClaim {
+            player,
+            card: None,
+            draw_count,
+            last: Some(draw_count as u8),
+            draw_index: draw_count,
+            number: Some(draw_count as u8),
+            at: Local::now(),
+        }
+    }
+
+    #[test]
+    fn test_prizes_are_split_on_the_same_draw() {
+        let mut registry = WinnerRegistry::new();
+        registry.set_prizes(vec!["Turkey".to_string(), "Cake".to_string()]);
+        let pattern = WinPattern::SingleLine;
+
+        let first = registry.record(1, pattern, &claim(1, 12)).unwrap();
+        assert_eq!(first.prize.as_ref().unwrap().name, "Turkey");
+        assert_eq!(first.share, 1);
+        // 同じ数字で揃った当選者は同じ景品を分け合う
+        let second = registry.record(1, pattern, &claim(2, 12)).unwrap();
+        assert_eq!(second.prize.as_ref().unwrap().name, "Turkey");
+        assert_eq!(second.share, 2);
+        assert_eq!(registry.winners()[0].share, 2);
+        assert_eq!(registry.remaining().len(), 1);
+
+        // 景品は1人1つまで
+        assert_eq!(
+            registry.record(1, pattern, &claim(1, 15)),
+            Err(WinnerError::AlreadyWon(1))
+        );
+        let third = registry.record(1, pattern, &claim(3, 15)).unwrap();
+        assert_eq!(third.prize.as_ref().unwrap().name, "Cake");
+        let fourth = registry.record(2, pattern, &claim(4, 15)).unwrap();
+        assert_eq!(fourth.prize, None);
+        assert_eq!(fourth.share, 1);
+    }
+
+    #[test]
+    fn test_revoke_round_returns_prizes() {
+        let mut registry = WinnerRegistry::new();
+        registry.set_prizes(vec!["Turkey".to_string(), "Cake".to_string()]);
+        registry
+            .record(1, WinPattern::Cross, &claim(1, 20))
+            .unwrap();
+        registry
+            .record(2, WinPattern::Cross, &claim(2, 20))
+            .unwrap();
+        registry.set_prizes(vec!["Candy".to_string()]);
+
+        assert_eq!(registry.revoke_round(1), [1]);
+        let names: Vec<_> = registry
+            .remaining()
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(names, ["Turkey", "Candy"]);
+        assert!(!registry.has_won(1));
+        assert!(registry.has_won(2));
+
+        // 1人だけ取り消す場合、分け合っていた景品は残りの当選者が受け取る
+        registry
+            .record(2, WinPattern::Cross, &claim(3, 20))
+            .unwrap();
+        assert_eq!(registry.round_winners(2), [2, 3]);
+        assert_eq!(registry.revoke(2, 3).unwrap().share, 2);
+        assert_eq!(registry.winners()[0].share, 1);
+        assert_eq!(registry.remaining().len(), 2);
+        assert_eq!(registry.revoke(2, 3), None);
+        registry.revoke(2, 2).unwrap();
+        assert_eq!(registry.remaining()[0].name, "Cake");
+        assert!(registry.round_winners(2).is_empty());
+    }
+
+    #[test]
+    fn test_tie_break_reallocates_prizes() {
+        let mut registry = WinnerRegistry::new();
+        registry.set_prizes(vec!["Turkey".to_string(), "Cake".to_string()]);
+        let pattern = WinPattern::SingleLine;
+        assert_eq!(registry.tie(1, None), Err(WinnerError::NoTie));
+        registry.record(1, pattern, &claim(2, 12)).unwrap();
+        registry.record(1, pattern, &claim(1, 12)).unwrap();
+        registry.record(1, pattern, &claim(3, 12)).unwrap();
+
+        assert_eq!(registry.tie(1, None), Ok((12, vec![1, 2, 3])));
+        assert_eq!(registry.tie(1, Some(11)), Err(WinnerError::NoTie));
+        registry.apply_tie_break(1, 12, &[3, 1, 2]);
+        let prizes: Vec<_> = [3, 1, 2]
+            .iter()
+            .map(|player| {
+                let winner = registry
+                    .winners()
+                    .iter()
+                    .find(|w| w.player == *player)
+                    .unwrap();
+                assert_eq!(winner.share, 1);
+                winner.prize.as_ref().map(|prize| prize.name.as_str())
+            })
+            .collect();
+        assert_eq!(prizes, [Some("Turkey"), Some("Cake"), None]);
+        assert_eq!(registry.tie(1, None), Err(WinnerError::NoTie));
+    }
+}
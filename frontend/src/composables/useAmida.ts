@@ -62,7 +62,7 @@ export function useAmida() {
         try {
             const data = await amidaApi.fetchResults()
             console.log('Fetched Amida results:', data.items)
-            return data.items
+            return data
         } catch (e) {
             error.value = e instanceof Error ? e.message : 'Unknown error'
             return null
@@ -71,6 +71,30 @@ export function useAmida() {
         }
     }
 
+    const importGuests = async (file: File) => {
+        isLoading.value = true
+        try {
+            const data = await amidaApi.importGuests(file)
+            await fetchAmida()
+            return data
+        } catch (e) {
+            error.value = e instanceof Error ? e.message : 'Unknown error'
+            return null
+        } finally {
+            isLoading.value = false
+        }
+    }
+
+    const revealPrize = async (prize: number) => {
+        try {
+            const data = await amidaApi.revealPrize(prize)
+            return data.item
+        } catch (e) {
+            error.value = e instanceof Error ? e.message : 'Unknown error'
+            return null
+        }
+    }
+
     return {
         items,
         prizeCount,
@@ -80,6 +104,8 @@ export function useAmida() {
         seed,
         fetchAmida,
         setupAmida,
-        fetchResults
+        fetchResults,
+        importGuests,
+        revealPrize
     }
 }
@@ -6,6 +6,13 @@ import { bingoApi } from '../services/bingoApi'
 vi.mock('../services/bingoApi', () => ({
     bingoApi: {
         fetchNextNumber: vi.fn(),
+        fetchStatus: vi.fn(),
+        transition: vi.fn(),
+        fetchAutoDraw: vi.fn(),
+        startAutoDraw: vi.fn(),
+        updateAutoDraw: vi.fn(),
+        stopAutoDraw: vi.fn(),
+        startRound: vi.fn(),
         resetGame: vi.fn(),
     },
 }))
@@ -48,7 +55,7 @@ describe('useBingoGame', () => {
         const mockData = {
             number: 42,
             history: [1, 2, 42],
-            message: 'Success',
+            status: 'drawn' as const,
             seed: 123456789,
         }
         vi.mocked(bingoApi.fetchNextNumber).mockResolvedValue(mockData)
@@ -104,4 +111,109 @@ describe('useBingoGame', () => {
         expect(displayText.value).toBe('Merry Christmas!')
         expect(history.value).toEqual([])
     })
+
+    it('loadStatusを実行するとサーバーの状態から表示が復元されること', async () => {
+        vi.mocked(bingoApi.fetchStatus).mockResolvedValue({
+            id: 'default',
+            round: 1,
+            version: 2,
+            variant: 'ball75',
+            phase: 'running',
+            actions: ['pause', 'finish', 'reset'],
+            last: 12,
+            history: [5, 12],
+            remaining: 73,
+            seed: 123456789,
+        })
+        const { loadStatus, currentNumber, displayText, history, seed, phase, actions } = useBingoGame()
+
+        await loadStatus()
+
+        expect(phase.value).toBe('running')
+        expect(actions.value).toEqual(['pause', 'finish', 'reset'])
+        expect(currentNumber.value).toBe(12)
+        expect(displayText.value).toBe(12)
+        expect(history.value).toEqual([5, 12])
+        expect(seed.value).toBe(123456789)
+    })
+
+    it('transitionを実行するとフェーズと許可される操作が更新されること', async () => {
+        vi.mocked(bingoApi.transition).mockResolvedValue({
+            id: 'default',
+            round: 1,
+            max_number: 75,
+            history: [5],
+            remaining: 74,
+            finished: false,
+            phase: 'paused',
+            actions: ['resume', 'finish', 'reset'],
+            transitions: [],
+            seed: 123456789,
+        })
+        const { transition, phase, actions } = useBingoGame()
+
+        await transition('pause')
+
+        expect(bingoApi.transition).toHaveBeenCalledWith('pause')
+        expect(phase.value).toBe('paused')
+        expect(actions.value).toEqual(['resume', 'finish', 'reset'])
+    })
+
+    it('自動抽選を開始するとサーバーの状態の確認を始め、停止すると止めること', async () => {
+        vi.useFakeTimers()
+        const running = {
+            state: 'running' as const,
+            interval_secs: 30,
+            next_draw_at: null,
+            draws: 0,
+            last: null,
+            stop_reason: null,
+        }
+        vi.mocked(bingoApi.startAutoDraw).mockResolvedValue(running)
+        vi.mocked(bingoApi.fetchAutoDraw).mockResolvedValue(running)
+        vi.mocked(bingoApi.stopAutoDraw).mockResolvedValue({ ...running, state: 'stopped', stop_reason: 'manual' })
+        const { controlAutoDraw, autoDraw } = useBingoGame()
+
+        await controlAutoDraw('start')
+        expect(autoDraw.value?.state).toBe('running')
+        await vi.advanceTimersByTimeAsync(2500)
+        expect(bingoApi.fetchAutoDraw).toHaveBeenCalledTimes(2)
+
+        await controlAutoDraw('stop')
+        expect(autoDraw.value?.stop_reason).toBe('manual')
+        await vi.advanceTimersByTimeAsync(2500)
+        expect(bingoApi.fetchAutoDraw).toHaveBeenCalledTimes(2)
+
+        vi.useRealTimers()
+    })
+
+    it('startNextRoundを実行すると次のラウンドの空の状態になること', async () => {
+        vi.mocked(bingoApi.startRound).mockResolvedValue({
+            number: 2,
+            pattern: 'single_line',
+            prize: null,
+            seed: 987654321,
+            winners: [],
+            started_at: '2025-12-24T20:00:00+09:00',
+            phase: 'setup',
+            history: [],
+            claims: [],
+            transitions: [],
+            ended_at: null,
+        })
+        vi.mocked(bingoApi.fetchAutoDraw).mockResolvedValue(null)
+        vi.mocked(bingoApi.fetchStatus).mockResolvedValue(null)
+        const { startNextRound, round, seed, phase, history, currentNumber } = useBingoGame()
+        history.value = [1, 2, 3]
+        currentNumber.value = 3
+
+        await startNextRound()
+
+        expect(bingoApi.startRound).toHaveBeenCalled()
+        expect(round.value).toBe(2)
+        expect(seed.value).toBe(987654321)
+        expect(phase.value).toBe('setup')
+        expect(history.value).toEqual([])
+        expect(currentNumber.value).toBeNull()
+    })
 })
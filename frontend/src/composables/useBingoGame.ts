@@ -1,6 +1,7 @@
 import { ref } from 'vue'
 import { useAudio } from './useAudio'
-import { bingoApi } from '../services/bingoApi'
+import { bingoApi, type AutoDrawStatus, type GamePhase, type PhaseAction } from '../services/bingoApi'
+import { ApiError } from '../services/apiBase'
 
 // Global State
 const currentNumber = ref<number | null>(null)
@@ -8,17 +9,154 @@ const displayText = ref<string | number>('Merry Christmas!')
 const history = ref<number[]>([])
 const isSpinning = ref(false)
 const seed = ref<number | null>(null)
+const round = ref(1)
 const isSoundEnabled = ref(true)
+const phase = ref<GamePhase>('setup')
+const actions = ref<PhaseAction[]>([])
+const autoDraw = ref<AutoDrawStatus | null>(null)
+let autoDrawTimer: ReturnType<typeof setInterval> | null = null
+
+/**
+ * 自動抽選中にサーバーの状態を確認する間隔（ミリ秒）
+ */
+const AUTO_DRAW_POLL_MS = 1000
 
 export function useBingoGame() {
     const { playBeep, playWin, resumeAudioContext } = useAudio()
 
+    /**
+     * サーバーの状態から表示を復元する
+     *
+     * 画面を再読み込みしても、抽選履歴と最後の数字を表示し直せるようにします。
+     */
+    const loadStatus = async () => {
+        if (isSpinning.value) return
+        const data = await bingoApi.fetchStatus()
+        if (!data) return
+        seed.value = data.seed
+        round.value = data.round
+        history.value = data.history
+        currentNumber.value = data.last
+        phase.value = data.phase
+        actions.value = data.actions
+        if (data.phase === 'finished') {
+            displayText.value = 'Finished!'
+        } else if (data.last !== null) {
+            displayText.value = data.last
+        } else {
+            displayText.value = 'Merry Christmas!'
+        }
+    }
+
+    /**
+     * フェーズと許可される操作だけをサーバーの状態に合わせる
+     */
+    const refreshPhase = async () => {
+        const data = await bingoApi.fetchStatus()
+        if (!data) return
+        phase.value = data.phase
+        actions.value = data.actions
+    }
+
+    /**
+     * ゲームのフェーズを遷移する（一時停止・再開など）
+     */
+    const transition = async (action: PhaseAction) => {
+        try {
+            const data = await bingoApi.transition(action)
+            phase.value = data.phase
+            actions.value = data.actions
+        } catch (e) {
+            console.error(e)
+            alert(e instanceof Error ? e.message : 'サーバーエラーです')
+        }
+    }
+
+    /**
+     * 自動抽選の状況に合わせて、サーバーの状態の確認を開始・停止する
+     */
+    const syncAutoDrawPolling = () => {
+        const active = autoDraw.value?.state === 'running' || autoDraw.value?.state === 'paused'
+        if (active && !autoDrawTimer) {
+            autoDrawTimer = setInterval(pollAutoDraw, AUTO_DRAW_POLL_MS)
+        } else if (!active && autoDrawTimer) {
+            clearInterval(autoDrawTimer)
+            autoDrawTimer = null
+        }
+    }
+
+    /**
+     * サーバーが抽選した数字を表示に反映する
+     */
+    const pollAutoDraw = async () => {
+        const before = history.value.length
+        await loadStatus()
+        if (history.value.length > before && isSoundEnabled.value) {
+            playWin()
+        }
+        autoDraw.value = await bingoApi.fetchAutoDraw()
+        syncAutoDrawPolling()
+    }
+
+    /**
+     * 自動抽選の状況を取得する（画面の表示時）
+     */
+    const loadAutoDraw = async () => {
+        autoDraw.value = await bingoApi.fetchAutoDraw()
+        syncAutoDrawPolling()
+    }
+
+    /**
+     * 自動抽選を開始・停止・一時停止・再開する
+     */
+    const controlAutoDraw = async (command: 'start' | 'stop' | 'pause' | 'resume') => {
+        try {
+            await resumeAudioContext()
+            if (command === 'start') {
+                autoDraw.value = await bingoApi.startAutoDraw()
+            } else if (command === 'stop') {
+                autoDraw.value = await bingoApi.stopAutoDraw()
+            } else {
+                autoDraw.value = await bingoApi.updateAutoDraw({ paused: command === 'pause' })
+            }
+            syncAutoDrawPolling()
+            await refreshPhase()
+        } catch (e) {
+            console.error(e)
+            alert(e instanceof Error ? e.message : 'サーバーエラーです')
+        }
+    }
+
+    /**
+     * 次のラウンドを開始する（抽選履歴は前のラウンドの記録としてサーバーに残る）
+     */
+    const startNextRound = async () => {
+        if (!confirm(`ラウンド ${round.value} を終えて次のラウンドを開始しますか？`)) return
+        try {
+            const data = await bingoApi.startRound()
+            round.value = data.number
+            seed.value = data.seed
+            phase.value = data.phase
+            currentNumber.value = null
+            displayText.value = 'Merry Christmas!'
+            history.value = []
+            autoDraw.value = await bingoApi.fetchAutoDraw()
+            syncAutoDrawPolling()
+            await refreshPhase()
+        } catch (e) {
+            console.error(e)
+            alert(e instanceof Error ? e.message : 'サーバーエラーです')
+        }
+    }
+
     const resetGame = async () => {
         if (!confirm('本当にリセットしますか？')) return
         try {
             const data = await bingoApi.resetGame()
             if (data) {
                 seed.value = data.seed
+                phase.value = data.phase
+                actions.value = data.actions
             }
             currentNumber.value = null
             displayText.value = 'Merry Christmas!'
@@ -36,7 +174,18 @@ export function useBingoGame() {
         isSpinning.value = true
 
         // バックエンドから数字取得
-        const data = await bingoApi.fetchNextNumber()
+        let data
+        try {
+            data = await bingoApi.fetchNextNumber()
+        } catch (e) {
+            isSpinning.value = false
+            if (e instanceof ApiError && e.code === 'invalid_phase') {
+                alert(`現在のフェーズ (${phase.value}) では抽選できません`)
+                await refreshPhase()
+                return
+            }
+            throw e
+        }
 
         if (!data) {
             isSpinning.value = false
@@ -46,6 +195,7 @@ export function useBingoGame() {
 
         seed.value = data.seed
         const targetNumber = data.number
+        await refreshPhase()
 
         if (targetNumber === null) {
             displayText.value = "Finished!"
@@ -99,7 +249,16 @@ export function useBingoGame() {
         history,
         isSpinning,
         seed,
+        round,
+        phase,
+        actions,
+        autoDraw,
         spin,
+        startNextRound,
+        loadAutoDraw,
+        controlAutoDraw,
+        loadStatus,
+        transition,
         resetGame,
         isSoundEnabled
     }
@@ -1,6 +1,7 @@
 import { createRouter, createWebHistory } from 'vue-router'
 import BingoView from '../views/BingoView.vue'
 import AmidaView from '../views/AmidaView.vue'
+import CardView from '../views/CardView.vue'
 
 const router = createRouter({
     history: createWebHistory(import.meta.env.BASE_URL),
@@ -19,6 +20,11 @@ const router = createRouter({
             path: '/amida/result',
             name: 'amida-result',
             component: AmidaView
+        },
+        {
+            path: '/join/:code?',
+            name: 'card',
+            component: CardView
         }
     ]
 })
@@ -0,0 +1,30 @@
+const STORAGE_KEY = 'bingo-admin-token'
+
+/**
+ * 管理者トークン（または司会者用PIN）を付けてAPIを呼び出します
+ *
+ * 抽選・リセット・あみだくじの更新など状態を変更するAPIは認証が必要です。
+ * トークンはセッション中だけ保存し、未入力（401）または誤り（403）の場合は
+ * 入力を求めてから1回だけ再試行します。
+ * @param input - リクエスト先
+ * @param init - リクエストの設定
+ */
+export async function adminFetch(input: string, init: RequestInit = {}): Promise<Response> {
+    const send = (token: string | null) => {
+        const headers = new Headers(init.headers)
+        if (token) headers.set('X-Admin-Token', token)
+        return fetch(input, { ...init, headers })
+    }
+
+    const res = await send(sessionStorage.getItem(STORAGE_KEY))
+    if (res.status !== 401 && res.status !== 403) return res
+
+    const message = res.status === 401
+        ? '管理者トークンまたは司会者用PINを入力してください'
+        : '管理者トークンまたは司会者用PINが正しくありません。もう一度入力してください'
+    const token = window.prompt(message)?.trim()
+    if (!token) return res
+
+    sessionStorage.setItem(STORAGE_KEY, token)
+    return send(token)
+}
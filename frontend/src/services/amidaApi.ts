@@ -1,16 +1,77 @@
-const API_BASE = 'http://localhost:3000'
+import { adminFetch } from './adminAuth'
+import { API_V1, parseResponse } from './apiBase'
+import type { PlayerId } from './playerApi'
+
+export type AmidaRule = 'strict' | 'leftover' | 'multiple_per_guest'
+
+export type AmidaConstraintError = 'unsatisfiable' | 'too_constrained'
+
+export type AmidaSettingsStatus = 'current' | 'updated' | 'unsatisfied'
+
+export type AmidaResultStatus = 'not_ready' | 'in_progress' | 'complete'
+
+export interface AmidaConstraints {
+    exclusions: { guest: string, player: PlayerId, prize: number }[]
+    spreads: { members: string[], players: PlayerId[], prizes: number[], max: number }[]
+}
 
 export interface AmidaResponse {
     items: string[]
+    players: (PlayerId | null)[]
     prize_count: number
+    rule: AmidaRule
+    constraints: AmidaConstraints
+    constraint_error: AmidaConstraintError | null
+    status: AmidaSettingsStatus
+    seed: number | null
+}
+
+export interface ImportRowError {
+    line: number
     message: string
-    seed: number
+}
+
+export interface ImportResponse {
+    items: string[]
+    errors: ImportRowError[]
+    format: 'csv' | 'tsv'
+    encoding: 'utf8' | 'shift_jis'
+    prize_count: number
+    constraint_error: AmidaConstraintError | null
+    status: AmidaSettingsStatus
+    seed: number | null
+}
+
+export interface AmidaRedraw {
+    prize: number
+    absentee: string
+    absentee_player: PlayerId
+    winner: string
+    winner_player: PlayerId
+    stream: number
 }
 
 export interface AmidaResultResponse {
     items: [string, string][]
-    message: string
-    seed: number
+    players: (PlayerId | null)[]
+    ready: boolean
+    total: number
+    leftover: number[]
+    rule: AmidaRule
+    absentees: string[]
+    absentee_players: PlayerId[]
+    redraws: AmidaRedraw[]
+    status: AmidaResultStatus
+    seed: number | null
+}
+
+export interface AmidaRevealResponse {
+    item: [string, string]
+    player: PlayerId | null
+    revealed_count: number
+    total: number
+    status: AmidaResultStatus
+    seed: number | null
 }
 
 /**
@@ -21,34 +82,63 @@ export const amidaApi = {
      * 現在のあみだくじの設定（景品リストなど）を取得します
      */
     async fetchSettings(): Promise<AmidaResponse> {
-        const res = await fetch(`${API_BASE}/amida`)
-        if (!res.ok) throw new Error('Failed to fetch amida settings')
-        return res.json()
+        const res = await fetch(`${API_V1}/amida`)
+        return parseResponse<AmidaResponse>(res, 'Failed to fetch amida settings')
     },
 
     /**
      * あみだくじの設定（景品リスト）を更新します
      * @param items - 新しい景品リスト
+     * @param rule - 参加者数と景品数が異なる場合の割り当てルール（省略時は変更しない）
      */
-    async updateSettings(items: string[]): Promise<AmidaResponse> {
-        const res = await fetch(`${API_BASE}/amida`, {
-            method: 'POST',
+    async updateSettings(items: string[], rule?: AmidaRule): Promise<AmidaResponse> {
+        const res = await adminFetch(`${API_V1}/amida`, {
+            method: 'PATCH',
             headers: {
                 'Content-Type': 'application/json'
             },
-            body: JSON.stringify({ items })
+            body: JSON.stringify({ items, rule })
         })
-        if (!res.ok) throw new Error('Failed to update amida settings')
-        return res.json()
+        return parseResponse<AmidaResponse>(res, 'Failed to update amida settings')
+    },
+
+    /**
+     * CSV/TSVファイルから参加者リストを取り込みます
+     * 文字コード（UTF-8/Shift_JIS）と区切り文字はサーバー側で自動判定されます
+     * @param file - 取り込むファイル
+     */
+    async importGuests(file: File): Promise<ImportResponse> {
+        const res = await adminFetch(`${API_V1}/amida/imports`, {
+            method: 'POST',
+            headers: {
+                'Content-Type': 'text/csv'
+            },
+            body: file
+        })
+        return parseResponse<ImportResponse>(res, 'Failed to import guests')
     },
 
     /**
      * あみだくじの結果（ゲストと景品のペア）を取得します
-     * サーバー側でシャッフルされた結果が返されます
+     * サーバー側で公開済みの結果のみが返されます
      */
     async fetchResults(): Promise<AmidaResultResponse> {
-        const res = await fetch(`${API_BASE}/amida/result`)
-        if (!res.ok) throw new Error('Failed to fetch amida results')
-        return res.json()
+        const res = await fetch(`${API_V1}/amida/result`)
+        return parseResponse<AmidaResultResponse>(res, 'Failed to fetch amida results')
+    },
+
+    /**
+     * 指定した番号の結果を1件公開します
+     * @param prize - 公開する番号
+     */
+    async revealPrize(prize: number): Promise<AmidaRevealResponse> {
+        const res = await adminFetch(`${API_V1}/amida/reveals`, {
+            method: 'POST',
+            headers: {
+                'Content-Type': 'application/json'
+            },
+            body: JSON.stringify({ prize })
+        })
+        return parseResponse<AmidaRevealResponse>(res, 'Failed to reveal amida result')
     }
 }
@@ -0,0 +1,81 @@
+/**
+ * APIのベースURL
+ *
+ * 本番ではバックエンドがフロントエンドと同じオリジンで配信するため `/api` を使います。
+ * 開発時は Vite の開発サーバーが `/api` をバックエンドへ転送します（`vite.config.ts`）。
+ * 別のサーバーのAPIを使う場合は `VITE_API_BASE` で指定してください。
+ */
+export const API_BASE: string = import.meta.env.VITE_API_BASE ?? '/api'
+
+/**
+ * バージョン付きAPIのベースURL
+ */
+export const API_V1: string = `${API_BASE}/v1`
+
+/**
+ * APIのエラーコード（サーバーの `ErrorCode` と対応）
+ */
+export type ApiErrorCode =
+    | 'unauthorized'
+    | 'forbidden'
+    | 'too_many_attempts'
+    | 'not_found'
+    | 'invalid_request'
+    | 'game_not_found'
+    | 'game_over'
+    | 'round_not_found'
+    | 'invalid_phase'
+    | 'auto_draw_active'
+    | 'auto_draw_inactive'
+    | 'prize_not_found'
+    | 'amida_not_ready'
+    | 'unknown_guest'
+    | 'already_absent'
+    | 'not_revealed'
+    | 'no_eligible_guest'
+    | 'player_not_found'
+    | 'invalid_player'
+    | 'duplicate_player'
+    | 'player_in_use'
+    | 'card_not_found'
+    | 'unsupported_variant'
+    | 'not_bingo'
+    | 'invalid_card'
+    | 'cards_exhausted'
+    | 'unreadable_image'
+    | 'already_won'
+    | 'claim_not_found'
+    | 'no_tie'
+    | 'invalid_encoding'
+    | 'missing_header'
+    | 'missing_name_column'
+    | 'unknown_column'
+    | 'simulation_busy'
+
+/**
+ * APIがエラーを返した場合の例外
+ */
+export class ApiError extends Error {
+    readonly status: number
+    readonly code: ApiErrorCode | null
+
+    constructor(status: number, code: ApiErrorCode | null, message: string) {
+        super(message)
+        this.name = 'ApiError'
+        this.status = status
+        this.code = code
+    }
+}
+
+/**
+ * レスポンスのJSONを返します
+ *
+ * エラーの場合はサーバーのエラーコードとメッセージを持つ `ApiError` を投げます。
+ * @param res - レスポンス
+ * @param fallback - エラーの内容を読み取れない場合のメッセージ
+ */
+export async function parseResponse<T>(res: Response, fallback: string): Promise<T> {
+    if (res.ok) return res.json()
+    const body = await res.json().catch(() => null)
+    throw new ApiError(res.status, body?.code ?? null, body?.message ?? fallback)
+}
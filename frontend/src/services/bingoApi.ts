@@ -1,11 +1,120 @@
+import { adminFetch } from './adminAuth'
+import { API_V1, ApiError, parseResponse } from './apiBase'
+import type { CardId } from './cardApi'
+import type { PlayerId } from './playerApi'
+
+export type DrawStatus = 'drawn' | 'game_over' | 'reset'
+
+/**
+ * ビンゴのゲームID（現在は1つのゲームのみ）
+ */
+const GAME_URL = `${API_V1}/games/default`
+
+const SESSION_URL = `${API_V1}/session`
+
 export interface NumberResponse {
     number: number | null
     history: number[]
-    message: string
+    status: DrawStatus
+    seed: number
+}
+
+export type BingoVariant = 'ball75' | 'ball90' | 'custom'
+
+export type GamePhase = 'setup' | 'locked' | 'running' | 'paused' | 'finished' | 'archived'
+
+export type PhaseAction = 'lock' | 'unlock' | 'start' | 'pause' | 'resume' | 'finish' | 'archive' | 'reset'
+
+export interface PhaseTransition {
+    from: GamePhase
+    to: GamePhase
+    action: PhaseAction
+    at: string
+}
+
+export interface GameStatusResponse {
+    id: string
+    round: number
+    version: number
+    variant: BingoVariant
+    phase: GamePhase
+    actions: PhaseAction[]
+    last: number | null
+    history: number[]
+    remaining: number
+    seed: number
+}
+
+export type AutoDrawState = 'idle' | 'running' | 'paused' | 'stopped'
+
+export type AutoDrawStopReason = 'manual' | 'claimed' | 'exhausted' | 'phase_changed' | 'reset'
+
+export interface AutoDrawStatus {
+    state: AutoDrawState
+    interval_secs: number
+    next_draw_at: string | null
+    draws: number
+    last: number | null
+    stop_reason: AutoDrawStopReason | null
+}
+
+export interface GameResponse {
+    id: string
+    round: number
+    max_number: number
+    history: number[]
+    remaining: number
+    finished: boolean
+    phase: GamePhase
+    actions: PhaseAction[]
+    transitions: PhaseTransition[]
+    seed: number
+}
+
+export type WinPattern = 'single_line' | 'two_lines' | 'four_corners' | 'cross' | 'blackout'
+
+export interface BingoClaim {
+    player: PlayerId
+    card: CardId | null
+    draw_count: number
+    last: number | null
+    draw_index: number
+    number: number | null
+    at: string
+}
+
+export interface Round {
+    number: number
+    pattern: WinPattern
+    prize: string | null
+    seed: number
+    winners: PlayerId[]
+    started_at: string
+    phase: GamePhase
+    history: number[]
+    drawn_at: string[]
+    claims: BingoClaim[]
+    transitions: PhaseTransition[]
+    ended_at: string | null
+}
+
+export interface RoundSummary {
+    number: number
+    pattern: WinPattern
+    prize: string | null
     seed: number
+    phase: GamePhase
+    draws: number
+    winners: PlayerId[]
+    started_at: string
+    ended_at: string | null
 }
 
-const API_BASE = 'http://localhost:3000'
+export interface SessionResponse {
+    seed: number
+    current_round: number
+    rounds: RoundSummary[]
+}
 
 /**
  * ビンゴゲームに関するAPI呼び出しを行うサービス
@@ -16,28 +125,151 @@ export const bingoApi = {
      * 
      * サーバー側で乱数生成を行い、新しい番号とこれまでの履歴を返します。
      * すべての番号が出尽くした場合は null が返る可能性があります。
+     * 一時停止中など抽選できないフェーズの場合は `invalid_phase` の ApiError を投げます。
      */
     async fetchNextNumber(): Promise<NumberResponse | null> {
         try {
-            const res = await fetch(`${API_BASE}/next_number`)
-            if (!res.ok) throw new Error('Network response was not ok')
-            return await res.json()
+            const res = await adminFetch(`${GAME_URL}/draws`, { method: 'POST' })
+            return await parseResponse<NumberResponse>(res, 'Network response was not ok')
         } catch (e) {
+            if (e instanceof ApiError && e.code === 'game_over') {
+                // 出尽くした場合は現在の履歴を返す
+                const game = await bingoApi.fetchGame()
+                return game ? { number: null, history: game.history, status: 'game_over', seed: game.seed } : null
+            }
+            if (e instanceof ApiError && e.code === 'invalid_phase') {
+                throw e
+            }
             console.error('Failed to fetch next number:', e)
             return null
         }
     },
 
+    /**
+     * ビンゴゲームの表示用の状態を取得します
+     *
+     * 状態は変更しません。画面の再読み込み時に履歴を復元するために使います。
+     */
+    async fetchStatus(): Promise<GameStatusResponse | null> {
+        try {
+            const res = await fetch(`${GAME_URL}/status`)
+            return await parseResponse<GameStatusResponse>(res, 'Network response was not ok')
+        } catch (e) {
+            console.error('Failed to fetch game status:', e)
+            return null
+        }
+    },
+
+    /**
+     * ビンゴゲームの現在の状態を取得します
+     */
+    async fetchGame(): Promise<GameResponse | null> {
+        try {
+            const res = await fetch(GAME_URL)
+            return await parseResponse<GameResponse>(res, 'Network response was not ok')
+        } catch (e) {
+            console.error('Failed to fetch game:', e)
+            return null
+        }
+    },
+
+    /**
+     * ゲームのフェーズを遷移します
+     *
+     * 現在のフェーズで許可されない操作の場合は `invalid_phase` の ApiError を投げます。
+     */
+    async transition(action: PhaseAction): Promise<GameResponse> {
+        const res = await adminFetch(`${GAME_URL}/transitions`, {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ action }),
+        })
+        return parseResponse<GameResponse>(res, 'Failed to change game phase')
+    },
+
+    /**
+     * 自動抽選の状況を取得します
+     */
+    async fetchAutoDraw(): Promise<AutoDrawStatus | null> {
+        try {
+            const res = await fetch(`${GAME_URL}/auto-draw`)
+            return await parseResponse<AutoDrawStatus>(res, 'Network response was not ok')
+        } catch (e) {
+            console.error('Failed to fetch auto draw status:', e)
+            return null
+        }
+    },
+
+    /**
+     * 自動抽選を開始します
+     *
+     * @param intervalSecs 抽選間隔（秒）。省略時はサーバーの設定値
+     */
+    async startAutoDraw(intervalSecs?: number): Promise<AutoDrawStatus> {
+        const res = await adminFetch(`${GAME_URL}/auto-draw`, {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ interval_secs: intervalSecs }),
+        })
+        return parseResponse<AutoDrawStatus>(res, 'Failed to start auto draw')
+    },
+
+    /**
+     * 自動抽選の間隔を変更する・一時停止する・再開します
+     */
+    async updateAutoDraw(changes: { interval_secs?: number; paused?: boolean }): Promise<AutoDrawStatus> {
+        const res = await adminFetch(`${GAME_URL}/auto-draw`, {
+            method: 'PATCH',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify(changes),
+        })
+        return parseResponse<AutoDrawStatus>(res, 'Failed to update auto draw')
+    },
+
+    /**
+     * 自動抽選を停止します
+     */
+    async stopAutoDraw(): Promise<AutoDrawStatus> {
+        const res = await adminFetch(`${GAME_URL}/auto-draw`, { method: 'DELETE' })
+        return parseResponse<AutoDrawStatus>(res, 'Failed to stop auto draw')
+    },
+
+    /**
+     * セッション（全てのラウンド）の概要を取得します
+     */
+    async fetchSession(): Promise<SessionResponse | null> {
+        try {
+            const res = await fetch(SESSION_URL)
+            return await parseResponse<SessionResponse>(res, 'Network response was not ok')
+        } catch (e) {
+            console.error('Failed to fetch session:', e)
+            return null
+        }
+    },
+
+    /**
+     * 進行中のラウンドを終え、次のラウンドを開始します
+     *
+     * 進行中のラウンドが終了していない場合は `invalid_phase` の ApiError を投げます。
+     */
+    async startRound(pattern?: WinPattern, prize?: string): Promise<Round> {
+        const res = await adminFetch(`${SESSION_URL}/rounds`, {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ pattern, prize }),
+        })
+        return parseResponse<Round>(res, 'Failed to start next round')
+    },
+
     /**
      * ビンゴゲームをリセットします
      * 
-     * 履歴をクリアし、新しいシード値でゲームを再開します。
+     * 抽選履歴を消去し、ゲームを最初からやり直します。
      */
-    async resetGame(): Promise<NumberResponse | null> {
+    async resetGame(): Promise<GameResponse | null> {
         try {
-            const res = await fetch(`${API_BASE}/reset`, { method: 'POST' })
-            if (!res.ok) throw new Error('Network response was not ok')
-            return await res.json()
+            const res = await adminFetch(`${GAME_URL}/draws`, { method: 'DELETE' })
+            return await parseResponse<GameResponse>(res, 'Network response was not ok')
         } catch (e) {
             console.error('Failed to reset game:', e)
             throw e
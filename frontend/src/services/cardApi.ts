@@ -0,0 +1,145 @@
+import { adminFetch } from './adminAuth'
+import { API_V1, parseResponse } from './apiBase'
+import type { AutoDrawStatus, BingoClaim, GamePhase, WinPattern } from './bingoApi'
+import type { Player, PlayerId } from './playerApi'
+import type { Winner } from './winnerApi'
+
+/**
+ * カードID（発行順にサーバーが振る）
+ */
+export type CardId = number
+
+export interface BingoCard {
+    id: CardId
+    player: PlayerId
+    join_code: string
+    /** マスの数字（`[行][列]`、中央のフリーマスは null） */
+    numbers: (number | null)[][]
+    issued_at: string
+}
+
+export interface PlayerCardResponse {
+    card: BingoCard
+    player: Player
+    round: number
+    pattern: WinPattern
+    phase: GamePhase
+    version: number
+    last: number | null
+    draw_count: number
+    /** 抽選済みの数字の印（サーバーが付ける） */
+    marks: boolean[][]
+    /** 当たりの形を作るマス（揃っていない場合は null） */
+    winning: boolean[][] | null
+    claimed: boolean
+}
+
+/**
+ * 印刷したカードのQRコードの確認結果
+ */
+export interface CardVerificationResponse {
+    card: CardId
+    numbers: (number | null)[][]
+    /** カードを発行した参加者（サーバーを起動せずに印刷したカードは null） */
+    player: Player | null
+    round: number
+    pattern: WinPattern
+    phase: GamePhase
+    last: number | null
+    draw_count: number
+    marks: boolean[][]
+    winning: boolean[][] | null
+    claimed: boolean
+}
+
+export interface CardClaimResponse {
+    claim: BingoClaim
+    auto_draw: AutoDrawStatus
+    /** 記録した当選者（すでに当選している場合は null） */
+    winner: Winner | null
+}
+
+const CARDS_URL = `${API_V1}/cards`
+
+const joinUrl = (code: string) => `${API_V1}/join/${encodeURIComponent(code.trim())}`
+
+/**
+ * ビンゴカードのAPI呼び出しを行うサービス
+ */
+export const cardApi = {
+    /**
+     * 発行したカードの一覧を取得します（管理者のみ）
+     */
+    async fetchCards(): Promise<BingoCard[]> {
+        const res = await adminFetch(CARDS_URL)
+        const data = await parseResponse<{ cards: BingoCard[] }>(res, 'Failed to fetch cards')
+        return data.cards
+    },
+
+    /**
+     * 参加者にカードを発行します（すでに持っている場合は同じカードを返す）
+     *
+     * 抽選が始まった後は `invalid_phase` の ApiError を投げます。
+     */
+    async issueCard(player: PlayerId): Promise<BingoCard> {
+        const res = await adminFetch(CARDS_URL, {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ player }),
+        })
+        return parseResponse<BingoCard>(res, 'Failed to issue card')
+    },
+
+    /**
+     * 印刷したカードのQRコードを確認し、抽選履歴を重ねたカードを取得します（管理者のみ）
+     *
+     * 偽造・改変されたカードの場合は `invalid_card` の ApiError を投げます。
+     */
+    async verify(payload: string): Promise<CardVerificationResponse> {
+        const res = await adminFetch(`${CARDS_URL}/verifications`, {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ payload }),
+        })
+        return parseResponse<CardVerificationResponse>(res, 'Failed to verify card')
+    },
+
+    /**
+     * 印刷したカードを撮影したPNG画像を送り、サーバーでQRコードを読み取って確認します（管理者のみ）
+     *
+     * QRコードが読み取れない場合は `unreadable_image` の ApiError を投げます。
+     */
+    async verifyImage(image: Blob): Promise<CardVerificationResponse> {
+        const res = await adminFetch(`${CARDS_URL}/verifications`, {
+            method: 'POST',
+            headers: { 'Content-Type': 'image/png' },
+            body: image,
+        })
+        return parseResponse<CardVerificationResponse>(res, 'Failed to verify card')
+    },
+
+    /**
+     * 参加コードのカードの状態を取得します
+     *
+     * 前回の `ETag` を渡すと、変化がない場合は null を返します。
+     * 存在しない参加コードの場合は `card_not_found` の ApiError を投げます。
+     */
+    async fetchCard(code: string, etag?: string): Promise<{ card: PlayerCardResponse; etag: string | null } | null> {
+        const res = await fetch(joinUrl(code), {
+            headers: etag ? { 'If-None-Match': etag } : {},
+        })
+        if (res.status === 304) return null
+        const card = await parseResponse<PlayerCardResponse>(res, 'Failed to fetch card')
+        return { card, etag: res.headers.get('ETag') }
+    },
+
+    /**
+     * 参加コードのカードで申告します
+     *
+     * 当たりの形が揃っていない場合は `not_bingo` の ApiError を投げます。
+     */
+    async claim(code: string): Promise<CardClaimResponse> {
+        const res = await fetch(`${joinUrl(code)}/claims`, { method: 'POST' })
+        return parseResponse<CardClaimResponse>(res, 'Failed to claim bingo')
+    },
+}
@@ -0,0 +1,79 @@
+import { adminFetch } from './adminAuth'
+import { API_V1, parseResponse } from './apiBase'
+
+/**
+ * 参加者ID（名簿への登録順にサーバーが振る）
+ */
+export type PlayerId = number
+
+export interface Player {
+    id: PlayerId
+    name: string
+    furigana: string | null
+    team: string | null
+    avatar: string | null
+}
+
+export interface PlayerProfile {
+    name: string
+    furigana?: string
+    team?: string
+    avatar?: string
+}
+
+const PLAYERS_URL = `${API_V1}/players`
+
+/**
+ * ビンゴとあみだくじで共有する参加者名簿のAPI呼び出しを行うサービス
+ */
+export const playerApi = {
+    /**
+     * 登録されている参加者を取得します
+     */
+    async fetchPlayers(): Promise<Player[]> {
+        try {
+            const res = await fetch(PLAYERS_URL)
+            const data = await parseResponse<{ players: Player[] }>(res, 'Network response was not ok')
+            return data.players
+        } catch (e) {
+            console.error('Failed to fetch players:', e)
+            return []
+        }
+    },
+
+    /**
+     * 参加者を登録します
+     *
+     * 同じ表示名の参加者がいる場合は `duplicate_player` の ApiError を投げます。
+     */
+    async createPlayer(profile: PlayerProfile): Promise<Player> {
+        const res = await adminFetch(PLAYERS_URL, {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify(profile),
+        })
+        return parseResponse<Player>(res, 'Failed to register player')
+    },
+
+    /**
+     * 参加者の情報を変更します（表示名以外は空文字列で消去）
+     */
+    async updatePlayer(id: PlayerId, changes: Partial<PlayerProfile>): Promise<Player> {
+        const res = await adminFetch(`${PLAYERS_URL}/${id}`, {
+            method: 'PATCH',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify(changes),
+        })
+        return parseResponse<Player>(res, 'Failed to update player')
+    },
+
+    /**
+     * 参加者を名簿から削除します
+     *
+     * 申告・当選者・あみだくじの枠で参照されている場合は `player_in_use` の ApiError を投げます。
+     */
+    async deletePlayer(id: PlayerId): Promise<Player> {
+        const res = await adminFetch(`${PLAYERS_URL}/${id}`, { method: 'DELETE' })
+        return parseResponse<Player>(res, 'Failed to delete player')
+    },
+}
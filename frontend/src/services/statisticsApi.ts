@@ -0,0 +1,70 @@
+import { API_V1, parseResponse } from './apiBase'
+import type { WinPattern } from './bingoApi'
+
+export interface NumberStats {
+    number: number
+    count: number
+    /** 抽選された順番の平均（一度も抽選されていない場合は null） */
+    mean_position: number | null
+    /** 抽選された順番（1から、ラウンド順） */
+    positions: number[]
+}
+
+export interface FirstBingoStats {
+    rounds: number
+    mean_draws: number | null
+    min_draws: number | null
+    max_draws: number | null
+}
+
+export interface ChiSquareTest {
+    statistic: number
+    degrees_of_freedom: number
+    p_value: number
+    /** 有意水準 5% で偏りがあるとは言えない場合に true */
+    uniform: boolean
+}
+
+export interface WinnerProbability {
+    cards: number
+    pattern: WinPattern
+    simulations: number
+    seed: number
+    /** `by_draw[N-1]` が N番目の抽選までに当選者が出る確率 */
+    by_draw: number[]
+}
+
+export interface DrawStatistics {
+    rounds: number
+    draws: number
+    max_number: number
+    numbers: NumberStats[]
+    first_bingo: FirstBingoStats
+    uniformity: ChiSquareTest | null
+    /** 75ボール以外のゲームでは null */
+    winner_probability: WinnerProbability | null
+}
+
+export interface StatisticsOptions {
+    /** 当選確率のシミュレーションの回数（1〜10000） */
+    simulations?: number
+    /** 仮想カードの枚数（省略時は発行済みのカードの枚数） */
+    cards?: number
+}
+
+/**
+ * 抽選の統計のAPI呼び出しを行うサービス
+ */
+export const statisticsApi = {
+    /**
+     * 終了したラウンドの抽選の統計と、当選確率のシミュレーションを取得します
+     */
+    async fetchStatistics(options: StatisticsOptions = {}): Promise<DrawStatistics> {
+        const params = new URLSearchParams()
+        if (options.simulations !== undefined) params.set('simulations', String(options.simulations))
+        if (options.cards !== undefined) params.set('cards', String(options.cards))
+        const query = params.toString()
+        const res = await fetch(`${API_V1}/statistics${query ? `?${query}` : ''}`)
+        return parseResponse<DrawStatistics>(res, 'Failed to fetch statistics')
+    },
+}
@@ -0,0 +1,135 @@
+import { adminFetch } from './adminAuth'
+import { API_V1, parseResponse } from './apiBase'
+import type { WinPattern } from './bingoApi'
+import type { CardId } from './cardApi'
+import type { PlayerId } from './playerApi'
+
+/**
+ * ビンゴの景品ID（登録順にサーバーが振る）
+ */
+export type PrizeId = number
+
+export interface BingoPrize {
+    id: PrizeId
+    name: string
+}
+
+export interface Winner {
+    player: PlayerId
+    round: number
+    pattern: WinPattern
+    /** 当たりの形が揃った数字の抽選順（1から） */
+    draw_index: number
+    number: number | null
+    card: CardId | null
+    /** 受け取る景品（景品が残っていなかった場合は null） */
+    prize: BingoPrize | null
+    /** 景品を分け合う人数 */
+    share: number
+    at: string
+}
+
+export interface WinnersResponse {
+    remaining: BingoPrize[]
+    winners: Winner[]
+}
+
+/**
+ * 同じ数字で揃った当選者の順位の決め方
+ */
+export type TieBreakMethod = 'random_order' | 'highest_number'
+
+export interface TieBreakDraw {
+    player: PlayerId
+    number: number
+}
+
+export interface TieBreak {
+    round: number
+    draw_index: number
+    method: TieBreakMethod
+    /** くじ引きに使った乱数のシード値（ラウンドのシード値と draw_index から求める） */
+    seed: number
+    algorithm: string
+    entrants: PlayerId[]
+    /** 当選者が引いた数字（highest_number のみ） */
+    draws: TieBreakDraw[]
+    /** 決まった順位（先頭が分け合っていた景品を受け取る） */
+    order: PlayerId[]
+    at: string
+}
+
+export interface TieBreakResponse {
+    tie_break: TieBreak
+    winners: Winner[]
+}
+
+export interface TieBreaksResponse {
+    tie_breaks: TieBreak[]
+}
+
+const WINNERS_URL = `${API_V1}/winners`
+
+/**
+ * ビンゴの景品と当選者のAPI呼び出しを行うサービス
+ */
+export const winnerApi = {
+    /**
+     * まだ渡していない景品と、これまでの当選者を取得します
+     */
+    async fetchWinners(): Promise<WinnersResponse> {
+        const res = await fetch(WINNERS_URL)
+        return parseResponse<WinnersResponse>(res, 'Failed to fetch winners')
+    },
+
+    /**
+     * 司会者が確認した申告を当選者として記録します（管理者のみ）
+     *
+     * すでに当選している参加者の場合は `already_won` の ApiError を投げます。
+     */
+    async recordWinner(player: PlayerId): Promise<Winner> {
+        const res = await adminFetch(WINNERS_URL, {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ player }),
+        })
+        return parseResponse<Winner>(res, 'Failed to record winner')
+    },
+
+    /**
+     * まだ渡していない景品を置き換えます（管理者のみ、指定した順に渡す）
+     */
+    async replacePrizes(prizes: string[]): Promise<WinnersResponse> {
+        const res = await adminFetch(`${WINNERS_URL}/prizes`, {
+            method: 'PUT',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ prizes }),
+        })
+        return parseResponse<WinnersResponse>(res, 'Failed to replace prizes')
+    },
+
+    /**
+     * 全てのラウンドのくじ引きの記録を取得します（会場の画面での再生用）
+     */
+    async fetchTieBreaks(): Promise<TieBreaksResponse> {
+        const res = await fetch(`${WINNERS_URL}/tie-breaks`)
+        return parseResponse<TieBreaksResponse>(res, 'Failed to fetch tie-breaks')
+    },
+
+    /**
+     * 同じ数字で揃った当選者の順位をくじ引きで決めます（管理者のみ）
+     *
+     * 景品を分け合っている当選者がいない場合は `no_tie` の ApiError を投げます。
+     */
+    async breakTie(
+        method: TieBreakMethod = 'random_order',
+        drawIndex?: number,
+    ): Promise<TieBreakResponse> {
+        const res = await adminFetch(`${WINNERS_URL}/tie-breaks`, {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify({ method, draw_index: drawIndex }),
+        })
+        return parseResponse<TieBreakResponse>(res, 'Failed to break tie')
+    },
+}
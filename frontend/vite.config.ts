@@ -5,6 +5,12 @@ import vue from '@vitejs/plugin-vue'
 // https://vite.dev/config/
 export default defineConfig({
     plugins: [vue()],
+    server: {
+        // 開発時は /api をバックエンドへ転送する
+        proxy: {
+            '/api': 'http://localhost:3000',
+        },
+    },
     test: {
         environment: 'jsdom',
     },